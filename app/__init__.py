@@ -4,7 +4,7 @@ from .config import DEFAULT_CONFIG, ensure_logging_dir, load_config
 from .audio_capture import AudioCapture
 from .transcribe import TranscriptionWorker, TranscriptionResult
 from .hotkeys import HotkeyManager
-from .output import type_text
+from .output import focus_window, type_text
 
 __all__ = [
     "DEFAULT_CONFIG",
@@ -14,6 +14,7 @@ __all__ = [
     "TranscriptionWorker",
     "TranscriptionResult",
     "HotkeyManager",
+    "focus_window",
     "type_text",
 ]
 
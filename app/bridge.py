@@ -9,13 +9,59 @@ Supported commands::
 
     {"cmd": "start"}
     {"cmd": "stop"}
+    {"cmd": "cancel"}
+    {"cmd": "pause"}
+    {"cmd": "resume"}
     {"cmd": "stats"}
+    {"cmd": "ping"}
+    {"cmd": "version", "req_id": 5}
+    {"cmd": "list_devices", "req_id": 1}
+    {"cmd": "set_device", "id": "3"}
+    {"cmd": "list_models", "req_id": 2}
+    {"cmd": "list_languages", "req_id": 4}
+    {"cmd": "set_output_method", "method": "clipboard"}
+    {"cmd": "set_trim", "mode": "whitespace"}
+    {"cmd": "set_type_delay", "delay_ms": 5}
+    {"cmd": "set_partials", "enabled": false}
+    {"cmd": "set_voice_formatting", "enabled": true}
+    {"cmd": "set_vad", "silence_threshold": 0.01, "min_silence_ms": 200}
+    {"cmd": "set_dataset", "enabled": true, "dir": "dataset"}
+    {"cmd": "set_output_target", "hwnd": 123456}
+    {"cmd": "type_text", "text": "..."}
+    {"cmd": "reload_postprocess"}
+    {"cmd": "benchmark", "path": "sounds/benchmark.wav", "req_id": 3}
     {"cmd": "shutdown"}
 
-Any unrecognised command results in an ``invalid_command`` event. All events
+Commands carrying a ``req_id`` get it echoed back on the reply event so the
+caller can correlate responses. Any unrecognised command results in an
+``invalid_command`` event. All events
 include a ``timestamp`` (seconds since epoch) and a ``event`` field to help the
 caller dispatch them appropriately.
 
+``partial_transcription`` events (``{"event": "partial_transcription", "text": ...}``)
+are reserved for interim hypotheses. The current recognizer transcribes each
+recording once after ``stop`` and produces no interim results, so no partials are
+emitted yet; ``set_partials`` only stores the caller's preference. The final text
+always arrives as ``transcription_result``.
+
+``set_vad`` is accepted for forward compatibility only: silence-based auto-stop
+is not implemented yet, so valid parameters are answered with an
+``invalid_command`` event whose ``message`` is ``vad_unsupported``.
+
+``transcription_result`` reports automatic corrections in three fields:
+``dict_corrections`` (replacements from the post-processing dictionary),
+``model_corrections`` (corrections made by the recognizer itself, 0 when the
+model does not report them) and ``corrections`` (kept for older callers, equal
+to ``dict_corrections``). Callers decide which of the first two to count.
+
+With ``set_voice_formatting`` enabled, spoken formatting commands in the final
+text (e.g. "换行" / "new line", "新段落" / "new paragraph") are replaced with the
+corresponding line breaks before output. A command only matches when it stands
+alone between punctuation or whitespace, so words such as "新行业" or "linen"
+are left untouched. The phrase-to-action mapping lives in
+``app/voice_formatting.py``; callers only toggle it. The
+``text`` reported on ``transcription_result`` is the formatted text.
+
 The bridge applies the configured post-processing, optional dataset capture and
 continues to inject text to the focused window just like the CLI entry point.
 
@@ -37,6 +83,8 @@ import threading
 import time
 from typing import Any, Dict, Optional
 
+import sounddevice as sd
+
 if __package__ in (None, ""):
     # 允许以 "python app/bridge.py" 运行：注入项目根目录
     import os
@@ -44,8 +92,18 @@ if __package__ in (None, ""):
 
     sys.path.append(str(pathlib.Path(__file__).resolve().parents[1]))
 
-from app import TranscriptionResult, TranscriptionWorker, load_config, type_text
+from app import TranscriptionResult, TranscriptionWorker, focus_window, load_config, type_text
+from app.config import load_postprocess_config
 from app.plugins.dataset_recorder import wrap_result_handler
+from app.voice_formatting import apply_voice_formatting
+from funasr_config import MODELS, get_asr_model_choices, get_asr_model_languages
+
+# 桥接版本（随 CHANGELOG 更新），通过 version 指令回报给调用方
+BRIDGE_VERSION = "2.1.1"
+
+# 输出前去除结尾字符的模式：none / whitespace / punctuation（空白与句末标点）
+TRIM_MODES = ("none", "whitespace", "punctuation")
+_TRAILING_PUNCTUATION = ".,!?;:。，！？；：、…"
 
 # 在导入模块后才分流 stdout
 # - 保存原始 stdout 用于事件输出
@@ -94,7 +152,10 @@ class BridgeApp:
         self._shutdown = False
 
         self.config = config
+        # 模型在 TranscriptionWorker 初始化时加载，记录耗时供 benchmark 回报
+        load_start = time.time()
         self.worker = TranscriptionWorker(config_path=config_path, on_result=None)
+        self._model_load_seconds = time.time() - load_start
 
         # 事件输出通道：
         # - 保存原始 stdout 用于 JSON 事件
@@ -105,7 +166,21 @@ class BridgeApp:
         output_method = output_cfg.get("method", "auto")
         append_newline = output_cfg.get("append_newline", False)
 
-        handler = self._build_result_handler(output_method, append_newline)
+        self._output_method = output_method
+        # set_output_method 收到 auto 时恢复为配置文件中的输出方式
+        self._default_output_method = output_method
+        self._append_newline = append_newline
+        self._trim_mode = "none"
+        # 模拟键入时每个字符之间的间隔（毫秒，0 表示尽快输出）
+        self._type_delay_ms = 0
+        # 是否上报中间识别结果（partial_transcription）；当前识别器不产生中间结果，仅保存开关
+        self._show_partials = True
+        # 是否将口述的格式指令（换行、新段落）转换为实际格式
+        self._voice_formatting = False
+        # 录音开始时的前台窗口句柄（由调用方下发），输出前恢复焦点；None 表示输出到当前焦点
+        self._output_target: Optional[int] = None
+        handler = self._build_result_handler(append_newline)
+        self._base_handler = handler
         if save_dataset:
             handler = wrap_result_handler(handler, self.worker, dataset_dir)
 
@@ -133,12 +208,13 @@ class BridgeApp:
             except Exception as exc:  # noqa: BLE001
                 logger.debug("写入事件异常: %s", exc)
 
-    def _emit_stats(self, event: str = "stats") -> None:
-        self.emit_event(event, stats=self.worker.transcription_stats)
+    def _emit_stats(self, event: str = "stats", req_id: Any = None) -> None:
+        self.emit_event(event, stats=self.worker.transcription_stats, req_id=req_id)
 
-    def _build_result_handler(self, output_method: str, append_newline: bool):
+    def _build_result_handler(self, append_newline: bool):
         def _handle_result(result: TranscriptionResult) -> None:
             stats = self.worker.transcription_stats
+            text = self._apply_voice_formatting(self._apply_trim(result.text))
             if result.error:
                 logger.error("转写失败: %s", result.error)
                 self.emit_event(
@@ -154,7 +230,14 @@ class BridgeApp:
                     stats.get("pending"),
                     stats.get("completed"),
                 )
-                type_text(result.text, append_newline=append_newline, method=output_method)
+                self._restore_output_target()
+                # 每次输出时读取当前方式，支持运行时切换
+                type_text(
+                    text,
+                    append_newline=append_newline,
+                    method=self._output_method,
+                    delay_ms=self._type_delay_ms,
+                )
                 logger.info("[bridge] 输出文本完成")
             except Exception as exc:  # noqa: BLE001
                 logger.error("输出文本失败: %s", exc, exc_info=True)
@@ -166,12 +249,14 @@ class BridgeApp:
 
             self.emit_event(
                 "transcription_result",
-                text=result.text,
+                text=text,
                 raw_text=result.raw_text,
                 duration=result.duration,
                 inference_latency=result.inference_latency,
                 confidence=result.confidence,
                 corrections=getattr(result, "corrections", 0),
+                dict_corrections=getattr(result, "corrections", 0),
+                model_corrections=getattr(result, "model_corrections", 0),
                 stats=stats,
             )
 
@@ -228,8 +313,336 @@ class BridgeApp:
         )
         logger.info("[bridge] 已退出录音状态 is_recording=False")
 
-    def handle_stats(self) -> None:
-        self._emit_stats()
+    def handle_cancel(self) -> None:
+        logger.info("[bridge] 收到 cancel 命令，停止录音并丢弃音频")
+        try:
+            self.worker.stop(discard=True)
+        except Exception as exc:  # noqa: BLE001
+            logger.error("[bridge] 取消录音失败: %s", exc, exc_info=True)
+            self.emit_event("recording_error", message=str(exc))
+
+        with self._state_lock:
+            self._is_recording = False
+
+        # 被丢弃的录音以 cancelled 结果告知调用方，不产生文本输出
+        self.emit_event("transcription_result", text="", cancelled=True)
+        self.emit_event(
+            "recording_state",
+            is_recording=False,
+            cancelled=True,
+            stats=self.worker.transcription_stats,
+        )
+
+    def _handle_pause_change(self, paused: bool) -> None:
+        changed = self.worker.pause() if paused else self.worker.resume()
+        if not changed:
+            logger.debug("[bridge] 当前未在录音，忽略 %s", "pause" if paused else "resume")
+            self.emit_event(
+                "recording_paused",
+                paused=False,
+                skipped="not_recording",
+            )
+            return
+        self.emit_event("recording_paused", paused=paused)
+
+    def handle_pause(self) -> None:
+        logger.info("[bridge] 收到 pause 命令，暂停录音")
+        self._handle_pause_change(True)
+
+    def handle_resume(self) -> None:
+        logger.info("[bridge] 收到 resume 命令，继续录音")
+        self._handle_pause_change(False)
+
+    def handle_stats(self, req_id: Any = None) -> None:
+        self._emit_stats(req_id=req_id)
+
+    def handle_ping(self) -> None:
+        # 心跳：Tauri 侧据此判断桥接进程是否仍在处理 stdin
+        self.emit_event("pong")
+
+    def handle_version(self, req_id: Any = None) -> None:
+        self.emit_event(
+            "version",
+            version=BRIDGE_VERSION,
+            python=sys.version.split()[0],
+            req_id=req_id,
+        )
+
+    def handle_list_devices(self, req_id: Any = None) -> None:
+        try:
+            devices = sd.query_devices()
+            default_input = sd.default.device[0]
+        except Exception as exc:  # noqa: BLE001
+            logger.error("[bridge] 查询音频设备失败: %s", exc, exc_info=True)
+            self.emit_event("device_error", message=str(exc), req_id=req_id)
+            return
+
+        inputs = [
+            {
+                "id": str(idx),
+                "name": info.get("name", ""),
+                "is_default": idx == default_input,
+            }
+            for idx, info in enumerate(devices)
+            if info.get("max_input_channels", 0) > 0
+        ]
+        self.emit_event("device_list", devices=inputs, req_id=req_id)
+
+    def handle_set_device(self, device_id: Any, req_id: Any = None) -> None:
+        # 设备编号以字符串传入；纯数字按索引处理，其余按名称匹配，空值恢复默认设备
+        device: Any = None
+        if device_id is not None and str(device_id).strip():
+            text = str(device_id).strip()
+            device = int(text) if text.isdigit() else text
+        # 下次开始录音时生效
+        self.worker.audio.device = device
+        logger.info("[bridge] 录音设备已设置为: %s", device if device is not None else "默认")
+        self.emit_event("device_set", device=device, req_id=req_id)
+
+    def handle_list_models(self, req_id: Any = None) -> None:
+        # 模型在进程启动时加载（FUNASR_ASR_MODEL），切换模型需重启桥接
+        active = MODELS["asr"]["name"]
+        models = [
+            {"name": name, "active": name == active}
+            for name in get_asr_model_choices()
+        ]
+        self.emit_event("model_list", models=models, active=active, req_id=req_id)
+
+    def handle_list_languages(self, req_id: Any = None) -> None:
+        model = MODELS["asr"]["name"]
+        languages = [
+            {"code": code, "name": name}
+            for code, name in get_asr_model_languages(model)
+        ]
+        self.emit_event("language_list", model=model, languages=languages, req_id=req_id)
+
+    def handle_set_output_method(self, method: Any, req_id: Any = None) -> None:
+        value = str(method or "").strip().lower()
+        if value not in ("auto", "type", "clipboard"):
+            self.emit_event(
+                "invalid_command",
+                message="invalid_output_method",
+                method=method,
+                req_id=req_id,
+            )
+            return
+        if value == "auto":
+            value = self._default_output_method
+        self._output_method = value
+        logger.info("[bridge] 输出方式已设置为: %s", value)
+        self.emit_event("output_method_set", method=value, req_id=req_id)
+
+    def handle_set_type_delay(self, delay_ms: Any, req_id: Any = None) -> None:
+        if isinstance(delay_ms, bool) or not isinstance(delay_ms, int) or not 0 <= delay_ms <= 1000:
+            self.emit_event(
+                "invalid_command",
+                message="invalid_type_delay",
+                delay_ms=delay_ms,
+                req_id=req_id,
+            )
+            return
+        self._type_delay_ms = delay_ms
+        logger.info("[bridge] 键入间隔已设置为: %sms", delay_ms)
+        self.emit_event("type_delay_set", delay_ms=delay_ms, req_id=req_id)
+
+    def _apply_trim(self, text: Optional[str]) -> str:
+        value = text or ""
+        if self._trim_mode == "whitespace":
+            return value.rstrip()
+        if self._trim_mode == "punctuation":
+            value = value.rstrip()
+            while value and value[-1] in _TRAILING_PUNCTUATION:
+                value = value[:-1].rstrip()
+        return value
+
+    def _apply_voice_formatting(self, text: str) -> str:
+        if not self._voice_formatting or not text:
+            return text
+        return apply_voice_formatting(text)
+
+    def handle_set_voice_formatting(self, enabled: Any, req_id: Any = None) -> None:
+        if not isinstance(enabled, bool):
+            self.emit_event(
+                "invalid_command",
+                message="invalid_enabled",
+                cmd="set_voice_formatting",
+                req_id=req_id,
+            )
+            return
+        self._voice_formatting = enabled
+        logger.info("[bridge] 语音格式指令已%s", "开启" if enabled else "关闭")
+        self.emit_event("voice_formatting_set", enabled=enabled, req_id=req_id)
+
+    def handle_set_trim(self, mode: Any, req_id: Any = None) -> None:
+        value = str(mode or "").strip().lower()
+        if value not in TRIM_MODES:
+            self.emit_event(
+                "invalid_command",
+                message="invalid_trim_mode",
+                mode=mode,
+                req_id=req_id,
+            )
+            return
+        self._trim_mode = value
+        logger.info("[bridge] 结尾裁剪模式已设置为: %s", value)
+        self.emit_event("trim_set", mode=value, req_id=req_id)
+
+    def handle_set_partials(self, enabled: Any, req_id: Any = None) -> None:
+        if not isinstance(enabled, bool):
+            self.emit_event(
+                "invalid_command",
+                message="invalid_enabled",
+                cmd="set_partials",
+                req_id=req_id,
+            )
+            return
+        self._show_partials = enabled
+        logger.info("[bridge] 中间结果上报已%s", "开启" if enabled else "关闭")
+        self.emit_event("partials_set", enabled=enabled, req_id=req_id)
+
+    def handle_set_vad(self, threshold: Any, min_silence_ms: Any, req_id: Any = None) -> None:
+        # 录音链路尚未实现静音自动停止，没有任何模块读取这些参数：
+        # 校验参数后明确回复 unsupported，而不是假装已生效
+        try:
+            threshold_value = float(threshold)
+            silence_value = int(min_silence_ms)
+        except (TypeError, ValueError):
+            threshold_value, silence_value = -1.0, -1
+        if not 0.0 < threshold_value <= 1.0 or silence_value <= 0:
+            self.emit_event(
+                "invalid_command",
+                message="invalid_vad",
+                cmd="set_vad",
+                req_id=req_id,
+            )
+            return
+        logger.info("[bridge] 暂不支持静音检测参数，忽略 set_vad: 阈值=%s, 最短静音=%sms", threshold_value, silence_value)
+        self.emit_event(
+            "invalid_command",
+            message="vad_unsupported",
+            cmd="set_vad",
+            req_id=req_id,
+        )
+
+    def _restore_output_target(self) -> None:
+        target = self._output_target
+        if target is None:
+            return
+        try:
+            focus_window(target)
+        except Exception as exc:  # noqa: BLE001
+            logger.warning("恢复目标窗口失败: %s", exc)
+
+    def handle_set_output_target(self, hwnd: Any, req_id: Any = None) -> None:
+        try:
+            target = int(hwnd) if hwnd is not None else None
+        except (TypeError, ValueError):
+            self.emit_event(
+                "invalid_command",
+                message="invalid_output_target",
+                hwnd=hwnd,
+                req_id=req_id,
+            )
+            return
+        self._output_target = target or None
+        logger.info("[bridge] 输出目标窗口已设置为: %s", self._output_target)
+        self.emit_event("output_target_set", hwnd=self._output_target, req_id=req_id)
+
+    def handle_set_dataset(self, enabled: Any, dataset_dir: Any, req_id: Any = None) -> None:
+        enabled = bool(enabled)
+        target_dir = str(dataset_dir or self._dataset_dir or "dataset")
+        try:
+            if enabled:
+                handler = wrap_result_handler(self._base_handler, self.worker, target_dir)
+            else:
+                handler = self._base_handler
+        except Exception as exc:  # noqa: BLE001
+            logger.error("[bridge] 设置数据集采集失败: %s", exc, exc_info=True)
+            self.emit_event(
+                "invalid_command",
+                message="invalid_dataset_dir",
+                dir=target_dir,
+                req_id=req_id,
+            )
+            return
+        self.worker.on_result = handler
+        self._save_dataset = enabled
+        self._dataset_dir = target_dir
+        logger.info("[bridge] 数据集采集已%s: %s", "启用" if enabled else "停用", target_dir)
+        self.emit_event("dataset_set", enabled=enabled, dir=target_dir, req_id=req_id)
+
+    def handle_type_text(self, text: Any, req_id: Any = None) -> None:
+        value = text if isinstance(text, str) else ""
+        if not value:
+            self.emit_event(
+                "invalid_command",
+                message="missing_text",
+                req_id=req_id,
+            )
+            return
+        try:
+            type_text(
+                value,
+                append_newline=self._append_newline,
+                method=self._output_method,
+                delay_ms=self._type_delay_ms,
+            )
+        except Exception as exc:  # noqa: BLE001
+            logger.error("重新输出文本失败: %s", exc, exc_info=True)
+            self.emit_event("output_error", error=str(exc), req_id=req_id)
+            return
+        logger.info("[bridge] 已重新输出文本，长度=%s", len(value))
+        self.emit_event("text_typed", length=len(value), req_id=req_id)
+
+    def handle_reload_postprocess(self, req_id: Any = None) -> None:
+        # 替换词典在每次转写时读取，这里仅校验并回报当前规则数
+        post_cfg = load_postprocess_config()
+        rules = len(post_cfg.get("replace_map", {}) or {})
+        logger.info("[bridge] 后处理配置已重新加载，规则数=%s", rules)
+        self.emit_event("postprocess_reloaded", rules=rules, req_id=req_id)
+
+    def handle_benchmark(self, path: Any, req_id: Any = None) -> None:
+        """对指定音频做一次完整推理并回报耗时；在后台线程执行，避免阻塞命令处理。"""
+        with self._state_lock:
+            busy = self._is_recording
+        if busy:
+            self.emit_event("benchmark_error", message="recording_in_progress", req_id=req_id)
+            return
+        if not isinstance(path, str) or not path.strip():
+            self.emit_event("benchmark_error", message="invalid_path", req_id=req_id)
+            return
+
+        def _run() -> None:
+            start = time.time()
+            try:
+                asr_result = self.worker.fun_server.transcribe_audio(
+                    path,
+                    options=self.worker.config.get("asr"),
+                )
+            except Exception as exc:  # noqa: BLE001
+                logger.error("[bridge] 性能测试失败: %s", exc, exc_info=True)
+                self.emit_event("benchmark_error", message=str(exc), req_id=req_id)
+                return
+            inference = time.time() - start
+            if not asr_result.get("success"):
+                self.emit_event(
+                    "benchmark_error",
+                    message=str(asr_result.get("error", "unknown")),
+                    req_id=req_id,
+                )
+                return
+            logger.info("[bridge] 性能测试完成：推理 %.3fs", inference)
+            self.emit_event(
+                "benchmark_result",
+                model=MODELS["asr"]["name"],
+                load_seconds=self._model_load_seconds,
+                inference_seconds=inference,
+                total_seconds=self._model_load_seconds + inference,
+                text=asr_result.get("text", ""),
+                req_id=req_id,
+            )
+
+        threading.Thread(target=_run, name="bridge-benchmark", daemon=True).start()
 
     def handle_shutdown(self) -> None:
         self.emit_event("shutdown_requested")
@@ -261,7 +674,7 @@ class BridgeApp:
             logger.debug("清理 FunASR 服务器时发生错误（忽略）: %s", exc)
 
         logger.info("[bridge] shutdown 资源清理完成，准备发送事件")
-        self.emit_event("bridge_shutdown", stats=self.worker.transcription_stats)
+        self.emit_event("bridge_shutdown", reason="graceful", stats=self.worker.transcription_stats)
 
     # ------------------------------------------------------------------
     def run(self) -> None:
@@ -316,13 +729,58 @@ class BridgeApp:
                     continue
 
                 logger.info("[bridge] 收到命令: %s", cmd)
+                req_id = payload.get("req_id")
                 try:
                     if cmd == "start":
                         self.handle_start()
                     elif cmd == "stop":
                         self.handle_stop()
+                    elif cmd == "cancel":
+                        self.handle_cancel()
+                    elif cmd == "pause":
+                        self.handle_pause()
+                    elif cmd == "resume":
+                        self.handle_resume()
                     elif cmd == "stats":
-                        self.handle_stats()
+                        self.handle_stats(req_id)
+                    elif cmd == "ping":
+                        self.handle_ping()
+                    elif cmd == "version":
+                        self.handle_version(req_id)
+                    elif cmd == "list_devices":
+                        self.handle_list_devices(req_id)
+                    elif cmd == "set_device":
+                        self.handle_set_device(payload.get("id"), req_id)
+                    elif cmd == "list_models":
+                        self.handle_list_models(req_id)
+                    elif cmd == "list_languages":
+                        self.handle_list_languages(req_id)
+                    elif cmd == "set_output_method":
+                        self.handle_set_output_method(payload.get("method"), req_id)
+                    elif cmd == "set_type_delay":
+                        self.handle_set_type_delay(payload.get("delay_ms"), req_id)
+                    elif cmd == "set_trim":
+                        self.handle_set_trim(payload.get("mode"), req_id)
+                    elif cmd == "set_vad":
+                        self.handle_set_vad(
+                            payload.get("silence_threshold"),
+                            payload.get("min_silence_ms"),
+                            req_id,
+                        )
+                    elif cmd == "set_voice_formatting":
+                        self.handle_set_voice_formatting(payload.get("enabled"), req_id)
+                    elif cmd == "set_partials":
+                        self.handle_set_partials(payload.get("enabled"), req_id)
+                    elif cmd == "set_output_target":
+                        self.handle_set_output_target(payload.get("hwnd"), req_id)
+                    elif cmd == "set_dataset":
+                        self.handle_set_dataset(payload.get("enabled"), payload.get("dir"), req_id)
+                    elif cmd == "type_text":
+                        self.handle_type_text(payload.get("text"), req_id)
+                    elif cmd == "reload_postprocess":
+                        self.handle_reload_postprocess(req_id)
+                    elif cmd == "benchmark":
+                        self.handle_benchmark(payload.get("path"), req_id)
                     elif cmd == "shutdown":
                         self.handle_shutdown()
                         break
@@ -332,6 +790,7 @@ class BridgeApp:
                             "invalid_command",
                             message="unknown_cmd",
                             cmd=cmd,
+                            req_id=req_id,
                         )
                 except Exception as exc:  # noqa: BLE001
                     logger.error("[bridge] 处理命令 %s 时异常: %s", cmd, exc, exc_info=True)
@@ -340,6 +799,7 @@ class BridgeApp:
                         message=str(exc),
                         cmd=cmd,
                         stage="handle_cmd",
+                        req_id=req_id,
                     )
         except Exception as exc:  # noqa: BLE001
             logger.error("[bridge] run 循环发生未捕获异常: %s", exc, exc_info=True)
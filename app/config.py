@@ -94,14 +94,24 @@ def ensure_logging_dir(config: Dict[str, Any]) -> str:
 def load_postprocess_config(path: Optional[str] = None) -> Dict[str, Any]:
     """Load postprocess replacement config from JSON file.
 
-    If not provided, reads from project_root/config/postprocess.json.
-    Returns a dict with keys: replace_map (dict[str,str]), case_insensitive (bool).
+    If not provided, reads from $SK_CONFIG_DIR/postprocess.json when the
+    config directory has been relocated, otherwise from
+    project_root/config/postprocess.json.
+    Returns a dict with keys: replace_map (dict[str,str]), case_insensitive (bool),
+    regex_keys (set[str]). Entries in replace_map are either plain strings
+    (literal replacement) or objects ``{"value": ..., "type": "regex"}`` whose
+    key is treated as a regular expression. Object entries with
+    ``"enabled": false`` are kept on disk but skipped here.
     Missing file gracefully returns empty mapping with case_insensitive=True.
     """
     # Determine default path relative to project root
     if path is None:
-        project_root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
-        path = os.path.join(project_root, "config", "postprocess.json")
+        config_dir = os.environ.get("SK_CONFIG_DIR")
+        if config_dir:
+            path = os.path.join(config_dir, "postprocess.json")
+        else:
+            project_root = os.path.dirname(os.path.dirname(os.path.abspath(__file__)))
+            path = os.path.join(project_root, "config", "postprocess.json")
 
     try:
         if os.path.exists(path):
@@ -113,18 +123,30 @@ def load_postprocess_config(path: Optional[str] = None) -> Dict[str, Any]:
                 replace_map = {}
             # Ensure map keys/values are strings
             safe_map: Dict[str, str] = {}
+            regex_keys = set()
             for k, v in replace_map.items():
                 try:
                     if k is None or v is None:
                         continue
+                    if isinstance(v, dict):
+                        value = v.get("value")
+                        if value is None or v.get("enabled", True) is False:
+                            continue
+                        if str(v.get("type", "literal")).lower() == "regex":
+                            regex_keys.add(str(k))
+                        v = value
                     safe_map[str(k)] = str(v)
                 except Exception:
                     continue
-            return {"replace_map": safe_map, "case_insensitive": case_insensitive}
+            return {
+                "replace_map": safe_map,
+                "case_insensitive": case_insensitive,
+                "regex_keys": regex_keys,
+            }
     except Exception:
         # Fall through to defaults on any error
         pass
 
-    return {"replace_map": {}, "case_insensitive": True}
+    return {"replace_map": {}, "case_insensitive": True, "regex_keys": set()}
 
 
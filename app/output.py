@@ -5,12 +5,16 @@ from __future__ import annotations
 import ctypes
 import ctypes.wintypes as wintypes
 import logging
+import time
 
 
 logger = logging.getLogger(__name__)
 
 SendInput = ctypes.windll.user32.SendInput
 GetMessageExtraInfo = ctypes.windll.user32.GetMessageExtraInfo
+IsWindow = ctypes.windll.user32.IsWindow
+SetForegroundWindow = ctypes.windll.user32.SetForegroundWindow
+GetForegroundWindow = ctypes.windll.user32.GetForegroundWindow
 
 INPUT_KEYBOARD = 1
 KEYEVENTF_KEYUP = 0x0002
@@ -83,7 +87,24 @@ def _emit_unicode_char(char: str) -> bool:
     return True
 
 
-def type_text(text: str, append_newline: bool = False, method: str = "auto") -> None:
+def focus_window(hwnd: int) -> bool:
+    """将指定窗口切换到前台，用于在输出前恢复录音开始时的目标窗口。"""
+    if not hwnd:
+        return False
+    handle = wintypes.HWND(hwnd)
+    if not IsWindow(handle):
+        logger.debug("目标窗口已不存在，hwnd=%s", hwnd)
+        return False
+    if GetForegroundWindow() == hwnd:
+        return True
+    if not SetForegroundWindow(handle):
+        logger.warning("切换目标窗口到前台失败，hwnd=%s", hwnd)
+        return False
+    return True
+
+
+def type_text(text: str, append_newline: bool = False, method: str = "auto", delay_ms: int = 0) -> None:
+    """注入文本；delay_ms 为模拟键入时每个字符之间的间隔（0 表示尽快输出，剪贴板方式不受影响）。"""
     if not text:
         return
 
@@ -101,33 +122,35 @@ def type_text(text: str, append_newline: bool = False, method: str = "auto") ->
         order = ["type", "clipboard", "unicode"]
 
     for mode in order:
-        if mode == "type" and _type_with_keyboard(payload):
+        if mode == "type" and _type_with_keyboard(payload, delay_ms):
             return
         if mode == "clipboard" and _try_clipboard_injection(payload):
             return
-        if mode == "unicode" and _type_with_unicode(payload):
+        if mode == "unicode" and _type_with_unicode(payload, delay_ms):
             return
 
     logger.error("所有文本注入方式均失败: %s", payload)
 
 
-def _type_with_keyboard(payload: str) -> bool:
+def _type_with_keyboard(payload: str, delay_ms: int = 0) -> bool:
     try:
         import keyboard
 
-        keyboard.write(payload, delay=0)
+        keyboard.write(payload, delay=max(delay_ms, 0) / 1000.0)
         return True
     except Exception as exc:  # noqa: BLE001
         logger.warning("keyboard.write 失败: %s", exc)
         return False
 
 
-def _type_with_unicode(payload: str) -> bool:
+def _type_with_unicode(payload: str, delay_ms: int = 0) -> bool:
     success = True
     for char in payload:
         if not _emit_unicode_char(char):
             success = False
             break
+        if delay_ms > 0:
+            time.sleep(delay_ms / 1000.0)
     return success
 
 
@@ -33,6 +33,8 @@ class TranscriptionResult:
     error: Optional[str] = None
     # 自动修正次数（仅统计对最终 text 的替换次数，排除 raw_text）
     corrections: int = 0
+    # 识别模型自身的修正次数（模型未提供时为 0）
+    model_corrections: int = 0
 
 
 class TranscriptionWorker:
@@ -64,6 +66,8 @@ class TranscriptionWorker:
 
         self._running = threading.Event()
         self._recording = threading.Event()
+        # 暂停期间丢弃采集到的音频帧，会话保持不变
+        self._paused = threading.Event()
         self._stop_requested = threading.Event()
         self._capture_thread: Optional[threading.Thread] = None
         self._state_lock = threading.RLock()
@@ -219,6 +223,7 @@ class TranscriptionWorker:
             logger.info("Transcription worker starting (session_id=%s)", session_id)
             self._running.set()
             self._stop_requested.clear()
+            self._paused.clear()
             with self._buffer_lock:
                 self._buffer.clear()
                 self._session_bytes = 0
@@ -228,11 +233,12 @@ class TranscriptionWorker:
             self._capture_thread.start()
             self._current_session_id = session_id
 
-    def stop(self, _from_capture_thread: bool = False) -> None:
+    def stop(self, _from_capture_thread: bool = False, discard: bool = False) -> None:
         """停止录音并提交转录任务
         
         Args:
             _from_capture_thread: 内部参数，标识是否从capture线程调用（避免死锁）
+            discard: 为 True 时丢弃本次音频，不提交转写（用于取消录音）
         """
         # 第一阶段：在锁内快速更新状态并保存资源引用
         with self._state_lock:
@@ -246,6 +252,7 @@ class TranscriptionWorker:
             self._stop_requested.set()
             self._running.clear()
             self._recording.clear()
+            self._paused.clear()
             
             # 保存当前会话的线程引用，避免操作到新会话的线程
             capture_thread_to_join = self._capture_thread
@@ -261,6 +268,16 @@ class TranscriptionWorker:
             if capture_thread_to_join and capture_thread_to_join.is_alive():
                 capture_thread_to_join.join(timeout=5)
 
+        if discard:
+            with self._buffer_lock:
+                self._buffer.clear()
+                self._session_bytes = 0
+            self.audio.flush()
+            logger.info("录音已取消，丢弃本次音频 (session_id=%s)", session_id)
+            with self._state_lock:
+                self._current_session_id = None
+            return
+
         combined = self._combine_buffer()
         self.audio.flush()
 
@@ -291,6 +308,24 @@ class TranscriptionWorker:
         with self._state_lock:
             self._current_session_id = None
 
+    def pause(self) -> bool:
+        """暂停当前录音会话（不结束会话、不触发转写）。未在录音时返回 False。"""
+        with self._state_lock:
+            if not self._running.is_set():
+                return False
+            self._paused.set()
+            logger.info("Transcription worker paused (session_id=%s)", self._current_session_id)
+            return True
+
+    def resume(self) -> bool:
+        """继续被暂停的录音会话。未在录音时返回 False。"""
+        with self._state_lock:
+            if not self._running.is_set():
+                return False
+            self._paused.clear()
+            logger.info("Transcription worker resumed (session_id=%s)", self._current_session_id)
+            return True
+
     def _capture_loop(self) -> None:
         queue_obj = self.audio.queue
         while self._recording.is_set():
@@ -301,6 +336,9 @@ class TranscriptionWorker:
                     break
                 continue
 
+            if self._paused.is_set():
+                continue
+
             try:
                 with self._buffer_lock:
                     if isinstance(frame, np.ndarray):
@@ -405,37 +443,36 @@ class TranscriptionWorker:
             post_cfg = load_postprocess_config()
             replace_map = post_cfg.get("replace_map", {}) or {}
             case_insensitive = bool(post_cfg.get("case_insensitive", True))
+            regex_keys = post_cfg.get("regex_keys", set()) or set()
 
             # todo:后期词多了使用前缀树加速
             def _apply_replacements(value: str) -> tuple[str, int]:
                 """返回 (替换后的文本, 发生的替换次数)。仅统计发生了多少次替换。"""
                 if not value or not replace_map:
                     return value, 0
-                try:
-                    corrections_count = 0
-                    if case_insensitive:
-                        import re
-                        for src, dst in replace_map.items():
-                            if not src:
-                                continue
-                            pattern = re.compile(re.escape(src), flags=re.IGNORECASE)
+                import re
+                corrections_count = 0
+                for src, dst in replace_map.items():
+                    if not src:
+                        continue
+                    # 每个条目单独处理：某条正则在 Python 下无法编译或替换时只跳过该条
+                    try:
+                        if src in regex_keys or case_insensitive:
+                            source = src if src in regex_keys else re.escape(src)
+                            pattern = re.compile(source, flags=re.IGNORECASE if case_insensitive else 0)
                             new_val = pattern.sub(dst, value)
-                            if new_val != value:
-                                logger.info("后处理替换: %r -> %r", src, dst)
-                                corrections_count += 1
-                            value = new_val
-                    else:
-                        for src, dst in replace_map.items():
-                            if not src:
-                                continue
-                            if src in value:
-                                logger.info("后处理替换: %r -> %r", src, dst)
-                                corrections_count += 1
-                                value = value.replace(src, dst)
-                    return value, corrections_count
-                except Exception as exc:
-                    logger.warning("应用后处理替换失败: %s", exc)
-                    return value, 0
+                        elif src in value:
+                            new_val = value.replace(src, dst)
+                        else:
+                            continue
+                    except Exception as exc:
+                        logger.warning("后处理替换条目 %r 无效，已跳过: %s", src, exc)
+                        continue
+                    if new_val != value:
+                        logger.info("后处理替换: %r -> %r", src, dst)
+                        corrections_count += 1
+                    value = new_val
+                return value, corrections_count
 
             final_text, corr_text = _apply_replacements(final_text)
             raw_text, _ = _apply_replacements(raw_text)
@@ -455,6 +492,10 @@ class TranscriptionWorker:
             except Exception as exc:  # noqa: BLE001
                 logger.error("处理转写结果时出错: %s", exc)
 
+    @property
+    def is_paused(self) -> bool:
+        return self._paused.is_set()
+
     @property
     def is_running(self) -> bool:
         return self._running.is_set()
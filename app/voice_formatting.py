@@ -0,0 +1,28 @@
+"""Spoken formatting commands ("换行" / "new line" ...) for the bridge output."""
+
+from __future__ import annotations
+
+import re
+
+# 指令两侧可一并吞掉的标点与空白（不含换行，避免吃掉前一条指令产生的换行）
+_SEPARATORS = r"[ \t,，.。;；]*"
+# 指令必须独立成段：前后只能是字符串边界、空白或标点，避免命中 "一个新行业" / "linen" / "renew"
+_BEFORE = r"(?<![^\s,，.。;；!?！？、：:])"
+_AFTER = r"(?![^\s,，.。;；!?！？、：:])"
+
+
+def _command(phrases: str) -> "re.Pattern[str]":
+    return re.compile(_SEPARATORS + _BEFORE + "(?:" + phrases + ")" + _AFTER + _SEPARATORS, re.IGNORECASE)
+
+
+# 语音格式指令：口述短语 -> 替换文本（连同两侧的标点与空白一起替换）；段落需先于换行匹配
+VOICE_FORMATTING_RULES = [
+    (_command(r"新段落|另起一段|new\s+paragraph"), "\n\n"),
+    (_command(r"换行|new\s+line"), "\n"),
+]
+
+
+def apply_voice_formatting(text: str) -> str:
+    for pattern, replacement in VOICE_FORMATTING_RULES:
+        text = pattern.sub(replacement, text)
+    return text
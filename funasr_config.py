@@ -10,13 +10,12 @@ import os
 # 模型版本，可通过环境变量覆盖
 MODEL_REVISION = os.environ.get("FUNASR_MODEL_REVISION", "v2.0.5")
 
+DEFAULT_ASR_MODEL = "iic/speech_paraformer-large_asr_nat-zh-cn-16k-common-vocab8404-onnx"
+
 # 模型配置（默认使用 ONNX 版本，仍可通过环境变量覆盖）
 MODELS = {
     "asr": {
-        "name": os.environ.get(
-            "FUNASR_ASR_MODEL",
-            "iic/speech_paraformer-large_asr_nat-zh-cn-16k-common-vocab8404-onnx",
-        ),
+        "name": os.environ.get("FUNASR_ASR_MODEL") or DEFAULT_ASR_MODEL,
         "type": "asr",
     },
     "vad": {
@@ -35,6 +34,38 @@ MODELS = {
     },
 }
 
+# 可选的 ASR 模型（供界面选择）：默认模型 + 环境变量 FUNASR_ASR_MODEL_CHOICES（逗号分隔）
+def get_asr_model_choices():
+    """返回可选 ASR 模型名称列表（去重，保持顺序，包含当前生效的模型）"""
+    extra = os.environ.get("FUNASR_ASR_MODEL_CHOICES", "")
+    names = [DEFAULT_ASR_MODEL, MODELS["asr"]["name"]]
+    names += [item.strip() for item in extra.split(",") if item.strip()]
+    return list(dict.fromkeys(names))
+
+
+# 各模型支持的识别语言 (代码, 名称)；未登记的模型按名称推断
+_LANGUAGE_NAMES = {
+    "zh": "中文",
+    "en": "English",
+    "yue": "粤语",
+    "ja": "日本語",
+    "ko": "한국어",
+}
+
+
+def get_asr_model_languages(name):
+    """返回指定 ASR 模型支持的语言列表 [(code, name), ...]"""
+    lowered = (name or "").lower()
+    if "sensevoice" in lowered:
+        codes = ["zh", "en", "yue", "ja", "ko"]
+    elif "-en-" in lowered or lowered.endswith("-en"):
+        codes = ["en"]
+    else:
+        # Paraformer 中文模型（vocab8404）可识别中英混合
+        codes = ["zh", "en"]
+    return [(code, _LANGUAGE_NAMES[code]) for code in codes]
+
+
 # 获取模型列表（用于下载脚本）
 def get_models_for_download():
     """返回用于下载的模型配置列表"""
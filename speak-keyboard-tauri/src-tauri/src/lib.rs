@@ -2,13 +2,19 @@ use tauri::{Manager, Emitter, menu::{Menu, MenuItem}, tray::{TrayIconBuilder, Tr
 use tauri::path::BaseDirectory;
 use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
 use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_clipboard_manager::ClipboardExt;
+use tauri_plugin_opener::OpenerExt;
+use tauri_plugin_deep_link::DeepLinkExt;
+use tracing::{debug, error, info, warn};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::prelude::*;
 // 进程与异步IO
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::{Arc, Mutex};
 use std::ffi::OsString;
 use std::time::{Duration, Instant};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use tokio::process::{Command, ChildStdin};
 use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
 use serde_json::Value;
@@ -16,775 +22,6905 @@ use serde::{Deserialize, Serialize};
 use chrono::Local;
 use indexmap::IndexMap;
 use std::fs;
-use std::io::{Read, Write};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::str::FromStr;
+use fs2::FileExt;
 
 const DEFAULT_RECORDING_HOTKEY: &str = "F2";
+// 快捷键模式：toggle（按一次开始、再按一次停止）/ push_to_talk（按住录音、松开停止）
+const HOTKEY_MODE_TOGGLE: &str = "toggle";
+const HOTKEY_MODE_PUSH_TO_TALK: &str = "push_to_talk";
+// 快捷键去抖间隔（毫秒）：默认值与允许范围
+const DEFAULT_HOTKEY_DEBOUNCE_MS: u64 = 200;
+const MAX_HOTKEY_DEBOUNCE_MS: u64 = 2000;
+// 最短录音时长上限（毫秒）；短于设定值的录音视为误触并取消
+const MAX_MIN_RECORDING_MS: u64 = 5000;
+// 退出时等待桥接清理的默认时长与上限（毫秒）
+const DEFAULT_SHUTDOWN_GRACE_MS: u64 = 3000;
+const MAX_SHUTDOWN_GRACE_MS: u64 = 15000;
+// 双击修饰键快捷键：两次单击的最大间隔（毫秒）及允许范围
+const DEFAULT_DOUBLE_TAP_WINDOW_MS: u64 = 400;
+const MIN_DOUBLE_TAP_WINDOW_MS: u64 = 150;
+const MAX_DOUBLE_TAP_WINDOW_MS: u64 = 1000;
+// 双击检测的按键状态轮询间隔
+const DOUBLE_TAP_POLL_INTERVAL: Duration = Duration::from_millis(10);
+// 捕获快捷键：等待按键的默认与最长时间
+const DEFAULT_HOTKEY_CAPTURE_TIMEOUT_MS: u64 = 5000;
+const MAX_HOTKEY_CAPTURE_TIMEOUT_MS: u64 = 30000;
+// 节省时间倍率：节省时间 = 录音时长 × 倍率（默认按 2.2 倍估算手动输入耗时）
+const DEFAULT_TIME_SAVED_MULTIPLIER: f64 = 2.2;
+const MAX_TIME_SAVED_MULTIPLIER: f64 = 20.0;
+// stats-updated 事件的最小间隔（毫秒）：间隔内的更新合并，结束时发送最新一次；0 表示不限制
+const DEFAULT_STATS_EMIT_INTERVAL_MS: u64 = 250;
+const MAX_STATS_EMIT_INTERVAL_MS: u64 = 5000;
+// 桥接进程重启退避上限（秒）
+const DEFAULT_BRIDGE_BACKOFF_CAP_SECS: u64 = 30;
+// 桥接心跳：每隔 BRIDGE_PING_INTERVAL 发送 ping，BRIDGE_PONG_TIMEOUT 内未收到 pong 视为卡死
+const BRIDGE_PING_INTERVAL: Duration = Duration::from_secs(10);
+const BRIDGE_PONG_TIMEOUT: Duration = Duration::from_secs(5);
+// 按住说话最长持续时间：丢失松开事件时自动停止
+const PUSH_TO_TALK_MAX_HOLD: Duration = Duration::from_secs(30);
+// 等待桥接应答（带 req_id 的请求）的超时时间
+const BRIDGE_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+// 转写性能测试：内置测试音频与等待应答的超时（首次推理可能较慢）
+const BENCHMARK_CLIP: &str = "sounds/benchmark.wav";
+const BENCHMARK_TIMEOUT: Duration = Duration::from_secs(120);
+// 悬浮窗不透明度允许范围（过低会导致窗口几乎不可见）
+const MIN_WIDGET_OPACITY: f64 = 0.2;
+const MAX_WIDGET_OPACITY: f64 = 1.0;
+// 文本输出方式：type（模拟键入）/ clipboard（复制后粘贴）
+const OUTPUT_METHOD_TYPE: &str = "type";
+const OUTPUT_METHOD_CLIPBOARD: &str = "clipboard";
+// 未设置输出方式时桥接使用配置文件中的 output.method（默认 auto）
+const OUTPUT_METHOD_AUTO: &str = "auto";
+// 结尾裁剪模式
+const TRIM_TRAILING_NONE: &str = "none";
+const TRIM_TRAILING_WHITESPACE: &str = "whitespace";
+const TRIM_TRAILING_PUNCTUATION: &str = "punctuation";
+// 模拟键入时每个字符之间的最大间隔（毫秒）
+const MAX_TYPE_DELAY_MS: u64 = 50;
+// 界面主题：跟随系统 / 浅色 / 深色
+const THEME_SYSTEM: &str = "system";
+const THEME_LIGHT: &str = "light";
+const THEME_DARK: &str = "dark";
+// 返回给前端的错误文案语言（日志仍为中文）
+const LOCALE_ZH: &str = "zh";
+const LOCALE_EN: &str = "en";
+// 录音结束后延迟开启悬浮窗鼠标穿透，便于用户继续操作
+const WIDGET_CLICK_THROUGH_DELAY: Duration = Duration::from_millis(1500);
+// 录音结束后自动隐藏悬浮窗的最长延迟（秒）
+const MAX_WIDGET_AUTOHIDE_SECS: u64 = 600;
+// 单次录音最长时长（秒）：超时自动停止，0 表示不限制
+const DEFAULT_MAX_RECORDING_SECS: u64 = 300;
+const MIN_MAX_RECORDING_SECS: u64 = 10;
+const MAX_MAX_RECORDING_SECS: u64 = 3600;
+// 静音检测参数允许范围
+const MIN_VAD_SILENCE_THRESHOLD: f64 = 0.001;
+const MAX_VAD_SILENCE_THRESHOLD: f64 = 0.5;
+const MIN_VAD_SILENCE_MS: u64 = 50;
+const MAX_VAD_SILENCE_MS: u64 = 10000;
+// 悬浮窗显示方式：始终显示 / 仅录音时显示
+const WIDGET_VISIBILITY_ALWAYS: &str = "always";
+const WIDGET_VISIBILITY_RECORDING_ONLY: &str = "recording_only";
+// 悬浮窗锚定：四个角之一，custom 表示使用自由拖动保存的坐标
+const WIDGET_ANCHOR_CUSTOM: &str = "custom";
+const WIDGET_ANCHOR_CORNERS: [&str; 4] = ["top_left", "top_right", "bottom_left", "bottom_right"];
+// 锚定边距上限（逻辑像素）；实际放置时还会限制在工作区内
+const MAX_WIDGET_ANCHOR_MARGIN: i32 = 2000;
+// 锚定模式下检测主显示器工作区变化（分辨率、任务栏、缩放）的间隔
+const WIDGET_ANCHOR_POLL_INTERVAL: Duration = Duration::from_secs(3);
+// 录音开始/结束提示音（打包为资源文件）
+const SOUND_RECORD_START: &str = "sounds/record-start.wav";
+const SOUND_RECORD_STOP: &str = "sounds/record-stop.wav";
+// 本地 HTTP 接口（仅监听 127.0.0.1）：默认端口与单个请求头的最大字节数
+const DEFAULT_HTTP_API_PORT: u16 = 17890;
+const HTTP_API_MAX_REQUEST_BYTES: usize = 8192;
+const HTTP_API_READ_TIMEOUT: Duration = Duration::from_secs(5);
+// 日志文件（位于配置目录，按天滚动）与默认级别
+const LOG_FILE_NAME: &str = "app.log";
+const DEFAULT_LOG_LEVEL: &str = "info";
+// 桥接 stderr 日志（位于配置目录）：超过上限时轮转为 bridge.log.1（仅保留一代）
+const BRIDGE_LOG_FILE_NAME: &str = "bridge.log";
+const BRIDGE_LOG_MAX_BYTES: u64 = 5 * 1024 * 1024;
+// 日志尾部读取：单次最多返回的行数与向前读取的块大小
+const LOG_TAIL_MAX_LINES: usize = 1000;
+const LOG_TAIL_CHUNK_BYTES: usize = 16 * 1024;
+// 转写历史（config/history.jsonl）：默认保留条数与允许上限
+const HISTORY_FILE_NAME: &str = "history.jsonl";
+const DEFAULT_HISTORY_LIMIT: usize = 500;
+const MAX_HISTORY_LIMIT: usize = 10000;
+// postprocess.json 外部修改后的重新加载去抖间隔
+const POSTPROCESS_RELOAD_DELAY: Duration = Duration::from_millis(300);
+// ui_settings.json 当前结构版本（旧文件缺少 schema_version 时视为 0，加载时迁移）
+const UI_SETTINGS_SCHEMA_VERSION: u32 = 2;
+// type_text 命令单次允许输出的最大字符数
+const MAX_TYPE_TEXT_CHARS: usize = 10000;
+// 系统托盘 ID 与录音中图标（打包为资源文件，见 tauri.conf.json）
+const TRAY_ID: &str = "main";
+const SETTINGS_WINDOW_LABEL: &str = "settings";
+const TRAY_RECORDING_ICON: &str = "icons/tray-recording.png";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct UiSettings {
+    // 结构版本，见 migrate_ui_settings
+    #[serde(default)]
+    schema_version: u32,
     #[serde(default = "default_recording_hotkey")] 
     recording_hotkey: String,
+    #[serde(default = "default_hotkey_mode")]
+    hotkey_mode: String,
+    // 可选：独立的开始/停止快捷键（未设置则仅使用 recording_hotkey 切换）
+    #[serde(default)]
+    start_hotkey: Option<String>,
+    #[serde(default)]
+    stop_hotkey: Option<String>,
+    // 可选：取消录音快捷键（丢弃本次录音，不产生输出）
+    #[serde(default)]
+    cancel_hotkey: Option<String>,
+    // 可选：重新输出上一次转写结果的快捷键
+    #[serde(default)]
+    retype_hotkey: Option<String>,
+    // 可选：显示/隐藏悬浮窗的快捷键（不影响录音）
+    #[serde(default)]
+    widget_toggle_hotkey: Option<String>,
+    // 命名的快捷键方案；上面的顶层快捷键字段始终为当前激活方案的按键
+    #[serde(default)]
+    hotkey_profiles: Vec<HotkeyProfile>,
+    #[serde(default)]
+    active_hotkey_profile: Option<String>,
+    #[serde(default = "default_hotkey_debounce_ms")]
+    hotkey_debounce_ms: u64,
+    // 最短录音时长（毫秒，0 表示不限制）
+    #[serde(default)]
+    min_recording_ms: u64,
+    // 退出时等待桥接进程完成清理的最长时间（毫秒）
+    #[serde(default = "default_shutdown_grace_ms")]
+    shutdown_grace_ms: u64,
+    // 录音快捷键为单独修饰键（如 Ctrl）时，两次单击的最大间隔
+    #[serde(default = "default_double_tap_window_ms")]
+    double_tap_window_ms: u64,
+    // 桥接进程最多重启次数（None 表示不限）
+    #[serde(default)]
+    bridge_max_restart_attempts: Option<u32>,
+    #[serde(default = "default_bridge_backoff_cap_secs")]
+    bridge_backoff_cap_secs: u64,
+    // 自定义 bridge 可执行文件路径：设置且有效时优先于打包的 bridge 与 Python 回退
+    #[serde(default)]
+    bridge_exe_override: Option<String>,
+    #[serde(default = "default_time_saved_multiplier")]
+    time_saved_multiplier: f64,
+    // 根据历史录音（字数/时长）自动估算倍率；样本不足时仍使用 time_saved_multiplier
+    #[serde(default)]
+    auto_time_multiplier: bool,
+    // 词典替换是否计入自动修正次数（模型自身修正始终计入）
+    #[serde(default = "default_count_dictionary_as_corrections")]
+    count_dictionary_as_corrections: bool,
+    // stats-updated 事件最小间隔（毫秒），连续听写时减少前端重复渲染
+    #[serde(default = "default_stats_emit_interval_ms")]
+    stats_emit_interval_ms: u64,
+    // 悬浮窗位置与尺寸（物理像素）
+    #[serde(default)]
+    widget_position: Option<WindowGeometry>,
+    // 悬浮窗锚定到主显示器的某个角（custom 时使用 widget_position）
+    #[serde(default)]
+    widget_anchor: WidgetAnchor,
+    // 设置窗口位置与尺寸（物理像素）；保存时所在的显示器已断开则不恢复
+    #[serde(default)]
+    settings_window_rect: Option<WindowGeometry>,
+    // 悬浮窗置顶（与 tauri.conf.json 中 alwaysOnTop 默认值一致）
+    #[serde(default = "default_widget_always_on_top")]
+    widget_always_on_top: bool,
+    // 悬浮窗不透明度（1.0 为完全不透明）
+    #[serde(default = "default_widget_opacity")]
+    widget_opacity: f64,
+    // 录音输入设备（None 表示系统默认设备）；桥接重启后重新下发
+    #[serde(default)]
+    audio_device: Option<String>,
+    // 转写模型名称（空字符串表示使用桥接默认模型），启动桥接时通过 FUNASR_ASR_MODEL 传入
+    #[serde(default)]
+    model: String,
+    // 文本输出方式（None 表示沿用桥接配置）
+    #[serde(default)]
+    output_method: Option<String>,
+    // 输出前去除结尾字符：none / whitespace / punctuation（空白与句末标点）
+    #[serde(default = "default_trim_trailing")]
+    trim_trailing: String,
+    // 悬浮窗实时显示中间识别结果的偏好；当前识别器只在停止后整段识别、不产生中间结果，
+    // 此开关暂不生效，仅保存供后续支持流式识别时使用
+    #[serde(default = "default_show_partials")]
+    show_partials: bool,
+    // 语音格式指令：将口述的“换行”“新段落”等转换为实际格式（短语映射由桥接维护，这里只控制开关）
+    #[serde(default)]
+    voice_formatting: bool,
+    // 模拟键入时每个字符之间的间隔（毫秒，0 表示尽快输出）；远程桌面等丢输入的环境可适当调大
+    #[serde(default)]
+    type_delay_ms: u64,
+    // 静音检测参数（下发给桥接，启动时重新应用）
+    #[serde(default)]
+    vad: VadSettings,
+    // 录音开始/结束时播放提示音
+    #[serde(default)]
+    sound_feedback: bool,
+    // 录音期间阻止系统休眠与关闭显示器
+    #[serde(default)]
+    prevent_sleep: bool,
+    // 单次录音最长秒数，超时自动发送 stop（0 表示不限制）
+    #[serde(default = "default_max_recording_secs")]
+    max_recording_secs: u64,
+    // 本地 HTTP 接口（供脚本/外部工具触发录音）
+    #[serde(default)]
+    http_api_enabled: bool,
+    #[serde(default = "default_http_api_port")]
+    http_api_port: u16,
+    // 访问令牌：请求需携带 Authorization: Bearer <token>；首次启用时自动生成
+    #[serde(default)]
+    http_api_token: Option<String>,
+    // 日志级别：off / error / warn / info / debug / trace
+    #[serde(default = "default_log_level")]
+    log_level: String,
+    // 转写历史保留条数（0 表示不记录）
+    #[serde(default = "default_history_limit")]
+    history_limit: usize,
+    // 首次运行：完成引导前为 true（旧配置缺少该字段时同样视为首次运行）
+    #[serde(default = "default_first_run")]
+    first_run: bool,
+    // 数据集采集（保存音频与转写文本对），出于隐私默认关闭；目录为空时使用项目根目录下的 dataset
+    #[serde(default)]
+    dataset_collection: bool,
+    #[serde(default)]
+    dataset_dir: Option<String>,
+    // 启动时仅显示托盘图标，不弹出悬浮窗（开机自启时始终如此）
+    #[serde(default)]
+    start_minimized: bool,
+    // 开机自启时延迟启动桥接进程，等待系统音频服务就绪（手动启动不受影响）
+    #[serde(default = "default_autostart_delay_secs")]
+    autostart_delay_secs: u64,
+    #[serde(default = "default_theme")]
+    theme: String,
+    // 错误提示语言：zh / en
+    #[serde(default = "default_locale")]
+    locale: String,
+    // 空闲时悬浮窗鼠标穿透（录音中或设置窗口打开时自动恢复可点击）
+    #[serde(default)]
+    widget_click_through: bool,
+    // 悬浮窗显示方式：always（始终显示）/ recording_only（录音开始时显示，结束后隐藏）
+    #[serde(default = "default_widget_visibility_mode")]
+    widget_visibility_mode: String,
+    // 录音结束后延迟隐藏的秒数：always 模式下 None 表示不自动隐藏，recording_only 模式下 None 表示立即隐藏
+    #[serde(default)]
+    widget_autohide_secs: Option<u64>,
+    // 免打扰应用：前台窗口标题包含其中任一项（不区分大小写）时拒绝开始录音
+    #[serde(default)]
+    blocked_app_titles: Vec<String>,
 }
 
-fn default_recording_hotkey() -> String {
-    DEFAULT_RECORDING_HOTKEY.to_string()
+fn default_widget_visibility_mode() -> String {
+    WIDGET_VISIBILITY_ALWAYS.to_string()
 }
 
-impl Default for UiSettings {
-    fn default() -> Self {
-        UiSettings {
-            recording_hotkey: default_recording_hotkey(),
-        }
-    }
+// 静音检测：低于 silence_threshold（归一化音量 0~1）持续 min_silence_ms 视为静音，
+// 默认值与桥接配置 vad.stop_threshold / vad.min_silence_ms 一致
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct VadSettings {
+    #[serde(default = "default_vad_silence_threshold")]
+    silence_threshold: f64,
+    #[serde(default = "default_vad_min_silence_ms")]
+    min_silence_ms: u64,
 }
 
-fn resolve_ui_settings_path() -> PathBuf {
-    resolve_tauri_config_path("ui_settings.json")
+fn default_vad_silence_threshold() -> f64 {
+    0.01
 }
 
-fn load_ui_settings() -> UiSettings {
-    let path = resolve_ui_settings_path();
-    if !path.exists() {
-        return UiSettings::default();
-    }
+fn default_vad_min_silence_ms() -> u64 {
+    200
+}
 
-    match fs::read_to_string(&path) {
-        Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
-        Err(err) => {
-            println!("读取 ui_settings.json 失败: {}，使用默认值", err);
-            UiSettings::default()
+impl Default for VadSettings {
+    fn default() -> Self {
+        VadSettings {
+            silence_threshold: default_vad_silence_threshold(),
+            min_silence_ms: default_vad_min_silence_ms(),
         }
     }
 }
 
-fn save_ui_settings(settings: &UiSettings) -> Result<(), String> {
-    let path = resolve_ui_settings_path();
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+impl VadSettings {
+    fn validate(&self) -> Result<(), String> {
+        if !self.silence_threshold.is_finite() || !(MIN_VAD_SILENCE_THRESHOLD..=MAX_VAD_SILENCE_THRESHOLD).contains(&self.silence_threshold) {
+            return Err(format!(
+                "静音阈值需在 {} ~ {} 之间",
+                MIN_VAD_SILENCE_THRESHOLD, MAX_VAD_SILENCE_THRESHOLD
+            ));
+        }
+        if !(MIN_VAD_SILENCE_MS..=MAX_VAD_SILENCE_MS).contains(&self.min_silence_ms) {
+            return Err(format!(
+                "最短静音时长需在 {} ~ {} 毫秒之间",
+                MIN_VAD_SILENCE_MS, MAX_VAD_SILENCE_MS
+            ));
+        }
+        Ok(())
     }
 
-    let data = serde_json::to_string_pretty(settings).map_err(|e| format!("序列化配置失败: {}", e))?;
-    fs::write(&path, data).map_err(|e| format!("写入 ui_settings.json 失败: {}", e))
+    fn bridge_payload(&self) -> Value {
+        serde_json::json!({
+            "cmd": "set_vad",
+            "silence_threshold": self.silence_threshold,
+            "min_silence_ms": self.min_silence_ms,
+        })
+    }
 }
 
-// 录音状态管理
-#[derive(Default)]
-struct AppState {
-    // 录音状态（由桥接事件驱动）
-    is_recording: std::sync::Mutex<bool>,
-    // 全局快捷键按下态（用于边沿检测）
-    hotkey_down: std::sync::Mutex<bool>,
-    // 去抖动：最近一次切换时间
-    last_toggle: std::sync::Mutex<Option<Instant>>,
-    // 当前已注册的录音快捷键（序列化字符串，如 "F2"）
-    recording_hotkey: Mutex<String>,
-    // 使用统计文件锁，避免并发读写冲突
-    usage_lock: std::sync::Mutex<()>,
+fn default_trim_trailing() -> String {
+    TRIM_TRAILING_NONE.to_string()
 }
 
-// 桥接进程状态（保存 stdin 句柄供命令写入）
-struct BridgeState {
-    stdin: Arc<tokio::sync::Mutex<Option<ChildStdin>>>,
-    should_restart: Arc<AtomicBool>,
+fn default_theme() -> String {
+    THEME_SYSTEM.to_string()
 }
 
-impl Drop for BridgeState {
-    fn drop(&mut self) {
-        let stdin_arc = Arc::clone(&self.stdin);
-        tauri::async_runtime::block_on(async move {
-            let mut guard = stdin_arc.lock().await;
-            if let Some(mut stdin) = guard.take() {
-                println!("[tauri] Drop: 发送 shutdown 指令给桥接进程");
-                let payload = serde_json::json!({"cmd": "shutdown"}).to_string() + "\n";
-                if let Err(err) = stdin.write_all(payload.as_bytes()).await {
-                    println!("[tauri] Drop: shutdown 写入失败: {}", err);
-                    return;
-                }
-                if let Err(err) = stdin.flush().await {
-                    println!("[tauri] Drop: shutdown 刷新失败: {}", err);
-                    return;
-                }
-                tokio::time::sleep(Duration::from_millis(200)).await;
-            } else {
-                println!("[tauri] Drop: stdin 已为空，跳过 shutdown 指令");
-            }
-        });
-    }
+fn default_max_recording_secs() -> u64 {
+    DEFAULT_MAX_RECORDING_SECS
 }
 
-// -----------------------------
-// 全局快捷键：读写配置 + 注册/注销
-// -----------------------------
-
-fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
-    Shortcut::from_str(hotkey)
-        .map_err(|e| format!("无法解析快捷键 {}: {}", hotkey, e))
+fn default_locale() -> String {
+    LOCALE_ZH.to_string()
 }
 
-fn register_recording_hotkey(app: &tauri::AppHandle, hotkey: &str) -> Result<(), String> {
-    // 先注销所有之前注册的快捷键
-    let gs = app.global_shortcut();
-    gs.unregister_all()
-        .map_err(|e| format!("注销旧快捷键失败: {}", e))?;
-
-    let shortcut = parse_hotkey(hotkey)?;
-    let hotkey_string = format!("{}", shortcut);
-    let handler_hotkey = hotkey_string.clone();
-
-    gs.on_shortcut(shortcut, move |app_handle, _shortcut, event| {
-        match event.state {
-            ShortcutState::Pressed => {
-                let handle_for_task = app_handle.clone();
-                let handle_for_error = app_handle.clone();
-                let hotkey_for_task = handler_hotkey.clone();
-                tauri::async_runtime::spawn(async move {
-                    if let Err(err) = handle_recording_hotkey(handle_for_task.clone(), hotkey_for_task.clone()).await {
-                        println!("处理快捷键 {} 失败: {}", hotkey_for_task, err);
-                        let app_state = handle_for_error.state::<AppState>();
-                        let mut down = app_state.hotkey_down.lock().unwrap();
-                        *down = false;
-                    }
-                });
-            }
-            ShortcutState::Released => {
-                let app_state = app_handle.state::<AppState>();
-                let mut down = app_state.hotkey_down.lock().unwrap();
-                *down = false;
-            }
-        }
-    })
-    .map_err(|e| format!("注册快捷键失败: {}", e))?;
-
-    // 更新 AppState 中的记录
-    let app_state = app.state::<AppState>();
-    {
-        let mut guard = app_state.recording_hotkey.lock().unwrap();
-        *guard = hotkey_string.clone();
-    }
+fn default_autostart_delay_secs() -> u64 {
+    DEFAULT_AUTOSTART_DELAY_SECS
+}
 
-    Ok(())
+fn default_recording_hotkey() -> String {
+    DEFAULT_RECORDING_HOTKEY.to_string()
 }
 
-async fn handle_recording_hotkey(app: tauri::AppHandle, shortcut: String) -> Result<(), String> {
-    let app_state = app.state::<AppState>();
+fn default_hotkey_mode() -> String {
+    HOTKEY_MODE_TOGGLE.to_string()
+}
 
-    // 边沿检测：只在从未按下 -> 按下 的边沿触发
-    {
-        let mut down = app_state.hotkey_down.lock().unwrap();
-        if *down {
-            return Ok(());
-        }
-        *down = true;
-    }
+fn default_shutdown_grace_ms() -> u64 {
+    DEFAULT_SHUTDOWN_GRACE_MS
+}
 
-    // 去抖：200ms 内忽略重复触发
-    let debounce = {
-        let mut last = app_state.last_toggle.lock().unwrap();
-        let now = Instant::now();
-        let within = if let Some(prev) = *last {
-            now.duration_since(prev) < Duration::from_millis(200)
-        } else {
-            false
-        };
-        *last = Some(now);
-        within
-    };
+fn default_double_tap_window_ms() -> u64 {
+    DEFAULT_DOUBLE_TAP_WINDOW_MS
+}
 
-    if debounce {
-        let mut down = app_state.hotkey_down.lock().unwrap();
-        *down = false;
-        return Ok(());
-    }
+fn default_hotkey_debounce_ms() -> u64 {
+    DEFAULT_HOTKEY_DEBOUNCE_MS
+}
 
-    println!("全局快捷键 {} 被按下", shortcut);
+fn default_bridge_backoff_cap_secs() -> u64 {
+    DEFAULT_BRIDGE_BACKOFF_CAP_SECS
+}
 
-    let currently = { *app_state.is_recording.lock().unwrap() };
-    let cmd_name = if currently { "stop" } else { "start" }.to_string();
-    println!("[tauri] 快捷键路径：当前 is_recording={}，准备发送 {} 指令", currently, cmd_name);
+fn default_widget_always_on_top() -> bool {
+    true
+}
 
-    let bridge_state = app.state::<BridgeState>();
-    {
-        let mut guard = bridge_state.stdin.lock().await;
-        if let Some(stdin) = guard.as_mut() {
-            let payload = serde_json::json!({"cmd": cmd_name}).to_string() + "\n";
-            stdin
-                .write_all(payload.as_bytes())
-                .await
-                .map_err(|e| format!("[tauri] 快捷键路径写入 {} 失败: {}", cmd_name, e))?;
-            stdin
-                .flush()
-                .await
-                .map_err(|e| format!("[tauri] 快捷键路径刷新 {} 失败: {}", cmd_name, e))?;
-            println!("[tauri] 快捷键路径已发送 {} 指令", cmd_name);
-        } else {
-            let mut down = app_state.hotkey_down.lock().unwrap();
-            *down = false;
-            return Err(format!("[tauri] 快捷键路径发送 {} 失败：stdin 不可用", cmd_name));
-        }
-    }
+fn default_stats_emit_interval_ms() -> u64 {
+    DEFAULT_STATS_EMIT_INTERVAL_MS
+}
 
-    if let Some(window) = app.get_webview_window("widget") {
-        let _ = window.emit("global-shortcut-pressed", shortcut.clone());
-    }
+fn default_count_dictionary_as_corrections() -> bool {
+    true
+}
 
-    {
-        let mut down = app_state.hotkey_down.lock().unwrap();
-        *down = false;
-    }
+fn default_show_partials() -> bool {
+    true
+}
 
-    Ok(())
+fn default_widget_opacity() -> f64 {
+    MAX_WIDGET_OPACITY
 }
 
-// 自启动：获取当前状态
-#[tauri::command]
-fn get_autostart_enabled(app: tauri::AppHandle) -> Result<bool, String> {
-    Ok(app.autolaunch().is_enabled().map_err(|e| e.to_string())?)
+fn default_http_api_port() -> u16 {
+    DEFAULT_HTTP_API_PORT
 }
 
-// 自启动：设置状态
-#[tauri::command]
-fn set_autostart_enabled(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
-    let api = app.autolaunch();
-    if enabled {
-        api.enable().map_err(|e| e.to_string())?;
-    } else {
-        api.disable().map_err(|e| e.to_string())?;
-    }
-    Ok(true)
+fn default_log_level() -> String {
+    DEFAULT_LOG_LEVEL.to_string()
 }
 
-// -----------------------------
-// 配置：替换词典 读/写（postprocess.json）
-// -----------------------------
+fn default_first_run() -> bool {
+    true
+}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct PostprocessConfig {
-    #[serde(default = "default_case_insensitive")]
-    case_insensitive: bool,
-    #[serde(default)]
-    replace_map: IndexMap<String, String>,
+fn default_history_limit() -> usize {
+    DEFAULT_HISTORY_LIMIT
 }
 
-fn default_case_insensitive() -> bool { true }
+fn default_time_saved_multiplier() -> f64 {
+    DEFAULT_TIME_SAVED_MULTIPLIER
+}
 
-#[derive(Deserialize, Debug)]
-struct SavePostprocessPayload {
-    #[serde(default)]
-    case_insensitive: Option<bool>,
-    // 允许任意 JSON 值，后续统一字符串化
-    #[serde(default)]
-    replace_map: IndexMap<String, Value>,
+fn is_valid_time_saved_multiplier(value: f64) -> bool {
+    value.is_finite() && value > 0.0 && value <= MAX_TIME_SAVED_MULTIPLIER
 }
 
-fn find_project_root_for_config() -> PathBuf {
-    let mut dir = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
-    for _ in 0..5 {
-        if dir.join("app").join("bridge.py").exists() {
-            return dir;
-        }
-        if !dir.pop() { break; }
+// 读取生效的倍率：配置无效时回退默认值
+fn effective_time_saved_multiplier(settings: &UiSettings) -> f64 {
+    if is_valid_time_saved_multiplier(settings.time_saved_multiplier) {
+        settings.time_saved_multiplier
+    } else {
+        DEFAULT_TIME_SAVED_MULTIPLIER
     }
-    std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."))
 }
 
-fn resolve_tauri_config_path(file_name: &str) -> PathBuf {
-    let root = find_project_root_for_config();
-    root.join("speak-keyboard-tauri").join("config").join(file_name)
+impl Default for UiSettings {
+    fn default() -> Self {
+        UiSettings {
+            schema_version: UI_SETTINGS_SCHEMA_VERSION,
+            recording_hotkey: default_recording_hotkey(),
+            hotkey_mode: default_hotkey_mode(),
+            start_hotkey: None,
+            stop_hotkey: None,
+            cancel_hotkey: None,
+            retype_hotkey: None,
+            widget_toggle_hotkey: None,
+            hotkey_profiles: Vec::new(),
+            active_hotkey_profile: None,
+            hotkey_debounce_ms: default_hotkey_debounce_ms(),
+            min_recording_ms: 0,
+            shutdown_grace_ms: default_shutdown_grace_ms(),
+            double_tap_window_ms: default_double_tap_window_ms(),
+            bridge_max_restart_attempts: None,
+            bridge_backoff_cap_secs: default_bridge_backoff_cap_secs(),
+            bridge_exe_override: None,
+            time_saved_multiplier: default_time_saved_multiplier(),
+            auto_time_multiplier: false,
+            count_dictionary_as_corrections: default_count_dictionary_as_corrections(),
+            stats_emit_interval_ms: default_stats_emit_interval_ms(),
+            widget_position: None,
+            settings_window_rect: None,
+            widget_anchor: WidgetAnchor::default(),
+            widget_always_on_top: default_widget_always_on_top(),
+            widget_opacity: default_widget_opacity(),
+            audio_device: None,
+            model: String::new(),
+            output_method: None,
+            trim_trailing: default_trim_trailing(),
+            show_partials: default_show_partials(),
+            voice_formatting: false,
+            type_delay_ms: 0,
+            vad: VadSettings::default(),
+            sound_feedback: false,
+            prevent_sleep: false,
+            max_recording_secs: default_max_recording_secs(),
+            http_api_enabled: false,
+            http_api_port: default_http_api_port(),
+            http_api_token: None,
+            log_level: default_log_level(),
+            history_limit: default_history_limit(),
+            first_run: default_first_run(),
+            dataset_collection: false,
+            dataset_dir: None,
+            start_minimized: false,
+            autostart_delay_secs: default_autostart_delay_secs(),
+            theme: default_theme(),
+            locale: default_locale(),
+            widget_click_through: false,
+            widget_visibility_mode: default_widget_visibility_mode(),
+            widget_autohide_secs: None,
+            blocked_app_titles: Vec::new(),
+        }
+    }
 }
 
-fn resolve_postprocess_path() -> PathBuf {
-    let root = find_project_root_for_config();
-    root.join("config").join("postprocess.json")
+fn resolve_ui_settings_path() -> PathBuf {
+    resolve_tauri_config_path("ui_settings.json")
 }
 
-fn read_postprocess_config_from_disk() -> Result<PostprocessConfig, String> {
-    let path = resolve_postprocess_path();
+fn load_ui_settings() -> UiSettings {
+    let path = resolve_ui_settings_path();
     if !path.exists() {
-        return Ok(PostprocessConfig { case_insensitive: true, replace_map: IndexMap::new() });
+        return UiSettings::default();
     }
-    let mut file = fs::File::open(&path).map_err(|e| format!("无法打开配置文件: {}", e))?;
-    let mut buf = String::new();
-    file.read_to_string(&mut buf).map_err(|e| format!("读取配置失败: {}", e))?;
 
-    // 优先严格反序列化为 PostprocessConfig（可保留键的插入顺序）
-    match serde_json::from_str::<PostprocessConfig>(&buf) {
-        Ok(cfg) => return Ok(cfg),
-        Err(_e) => {
-            // 兼容旧格式或非字符串值：宽松解析再清洗
-            let val: Value = serde_json::from_str(&buf).map_err(|e| format!("配置 JSON 解析失败: {}", e))?;
-            let case_insensitive = val.get("case_insensitive").and_then(|v| v.as_bool()).unwrap_or(true);
-            let mut map: IndexMap<String, String> = IndexMap::new();
-            if let Some(obj) = val.get("replace_map").and_then(|v| v.as_object()) {
-                for (k, v) in obj.iter() {
-                    let s = match v {
-                        Value::String(s) => s.clone(),
-                        other => other.to_string(),
-                    };
-                    map.insert(k.clone(), s);
-                }
-            }
-            return Ok(PostprocessConfig { case_insensitive, replace_map: map });
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(err) => {
+            warn!("读取 ui_settings.json 失败: {}，使用默认值", err);
+            return UiSettings::default();
+        }
+    };
+    let Ok(raw) = serde_json::from_str::<Value>(&content) else {
+        return UiSettings::default();
+    };
+    let (value, migrated) = migrate_ui_settings(raw);
+    let settings: UiSettings = serde_json::from_value(value).unwrap_or_default();
+    if migrated {
+        info!("ui_settings.json 已迁移到版本 {}", UI_SETTINGS_SCHEMA_VERSION);
+        if let Err(e) = save_ui_settings(&settings) {
+            warn!("保存迁移后的 ui_settings.json 失败: {}", e);
         }
     }
+    settings
 }
 
-fn validate_and_clean_payload(payload: SavePostprocessPayload) -> Result<PostprocessConfig, String> {
-    let mut cleaned: IndexMap<String, String> = IndexMap::new();
-    // 用于 case_insensitive 去重：规范化键(lowercase) -> 原始键
-    let mut norm_to_key: IndexMap<String, String> = IndexMap::new();
-    let case_insensitive = payload.case_insensitive.unwrap_or(true);
+// 将旧版本的配置逐级升级到 UI_SETTINGS_SCHEMA_VERSION，返回升级后的 JSON 及是否发生迁移
+fn migrate_ui_settings(mut value: Value) -> (Value, bool) {
+    let Some(obj) = value.as_object_mut() else {
+        return (value, false);
+    };
+    let version = obj.get("schema_version").and_then(|v| v.as_u64()).unwrap_or(0);
+    if version >= UI_SETTINGS_SCHEMA_VERSION as u64 {
+        return (value, false);
+    }
 
-    // 清洗：trim、转字符串、去空、长度限制（<=16）
-    let mut errors: Vec<String> = Vec::new();
-    for (k, v) in payload.replace_map.into_iter() {
-        let key_trim = k.trim();
-        let val_str = match v {
-            Value::String(s) => s,
-            other => other.to_string(),
-        };
-        let val_trimmed = val_str.trim();
-
-        if key_trim.is_empty() || val_trimmed.is_empty() { continue; }
-        if key_trim.chars().count() > 16 { errors.push(format!("键过长(>16): {}", key_trim)); continue; }
-        if val_trimmed.chars().count() > 16 { errors.push(format!("值过长(>16): {}", val_trimmed)); continue; }
-
-        let val_final = val_trimmed.to_string();
-        // 大小写不敏感：用 lower 做去重，但保存原始大小写键
-        let store_key = key_trim.to_string();
-        if case_insensitive {
-            let key_norm = key_trim.to_lowercase();
-            if let Some(old_key) = norm_to_key.get(&key_norm).cloned() {
-                let _ = cleaned.shift_remove(&old_key);
+    // v0 -> v1：可选快捷键曾以空字符串表示停用，统一为 null；枚举类取值统一为小写
+    if version < 1 {
+        for key in ["start_hotkey", "stop_hotkey", "cancel_hotkey", "retype_hotkey", "output_method", "audio_device"] {
+            let blank = obj.get(key).and_then(|v| v.as_str()).map(|v| v.trim().is_empty()).unwrap_or(false);
+            if blank {
+                obj.insert(key.to_string(), Value::Null);
             }
-            norm_to_key.insert(key_norm, store_key.clone());
-            cleaned.insert(store_key, val_final);
-        } else {
-            if cleaned.contains_key(&store_key) {
-                let _ = cleaned.shift_remove(&store_key);
+        }
+        for key in ["hotkey_mode", "output_method", "log_level"] {
+            if let Some(lowered) = obj.get(key).and_then(|v| v.as_str()).map(|v| v.trim().to_ascii_lowercase()) {
+                obj.insert(key.to_string(), Value::String(lowered));
             }
-            cleaned.insert(store_key, val_final);
         }
     }
 
-    if !errors.is_empty() {
-        return Err(errors.join("; "));
+    // v1 -> v2：自动隐藏延迟 0 统一为 null（不自动隐藏 / recording_only 下立即隐藏），超出上限的取上限
+    if version < 2 {
+        if let Some(secs) = obj.get("widget_autohide_secs").and_then(|v| v.as_u64()) {
+            let migrated = if secs == 0 { Value::Null } else { Value::from(secs.min(MAX_WIDGET_AUTOHIDE_SECS)) };
+            obj.insert("widget_autohide_secs".to_string(), migrated);
+        }
     }
 
-    // 条目数限制：最多200条
-    if cleaned.len() > 200 {
-        return Err(format!("替换词典超出上限：{} 条（最多 200 条）", cleaned.len()));
-    }
+    obj.insert("schema_version".to_string(), Value::from(UI_SETTINGS_SCHEMA_VERSION));
+    (value, true)
+}
 
-    // 如果不区分大小写，为了与 Python 侧匹配，需要把保存的键恢复为原样大小写。
-    // 这里选择将键以当前 cleaned 的键直接保存（已转为 lower），Python 侧基于 re.IGNORECASE 进行匹配，大小写无关。
+// 设置文件自身的读写错误使用默认语言文案：此处不能再查询语言设置，否则写入失败时会递归读取配置
+fn save_ui_settings(settings: &UiSettings) -> Result<(), String> {
+    let message = |key: &str, e: &dyn std::fmt::Display| format_message(lookup_message(&default_locale(), key), &[e]);
+    let path = resolve_ui_settings_path();
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| message("config_dir_create_failed", &e))?;
+    }
 
-    Ok(PostprocessConfig { case_insensitive, replace_map: cleaned })
+    let data = serde_json::to_string_pretty(settings).map_err(|e| message("config_serialize_failed", &e))?;
+    fs::write(&path, data).map_err(|e| message("ui_settings_write_failed", &e))
 }
 
-fn write_postprocess_config_to_disk(cfg: &PostprocessConfig) -> Result<(), String> {
-    let path = resolve_postprocess_path();
-    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?; }
+// 前端可见的错误文案：(键, 中文, English)，占位符 {0}、{1}… 由 tf 依次替换
+const MESSAGES: &[(&str, &str, &str)] = &[
+    ("hotkey_empty", "快捷键不能为空", "Hotkey cannot be empty"),
+    ("hotkey_needs_key", "快捷键必须包含具体按键，例如 Ctrl+Shift+K", "Hotkey must include a key, e.g. Ctrl+Shift+K"),
+    ("hotkey_double_tap_unsupported", "当前平台不支持双击修饰键快捷键，请包含具体按键，例如 Ctrl+Shift+K", "Double-tap modifier hotkeys are not supported on this platform; include a key, e.g. Ctrl+Shift+K"),
+    ("hotkey_parse_failed", "无法解析快捷键 {0}: {1}", "Cannot parse hotkey {0}: {1}"),
+    ("hotkey_conflict", "快捷键冲突：{0} 与 {1} 均为 {2}", "Hotkey conflict: {0} and {1} are both {2}"),
+    ("hotkey_unregister_failed", "注销旧快捷键失败: {0}", "Failed to unregister previous hotkeys: {0}"),
+    ("hotkey_register_failed", "注册快捷键 {0} 失败: {1}", "Failed to register hotkey {0}: {1}"),
+    ("hotkey_state_failed", "更新快捷键状态失败: {0}", "Failed to update hotkey state: {0}"),
+    ("hotkey_purpose_recording", "录音快捷键", "recording hotkey"),
+    ("hotkey_purpose_start", "开始快捷键", "start hotkey"),
+    ("hotkey_purpose_stop", "停止快捷键", "stop hotkey"),
+    ("hotkey_purpose_cancel", "取消快捷键", "cancel hotkey"),
+    ("hotkey_purpose_retype", "重新输出快捷键", "retype hotkey"),
+    ("hotkey_purpose_widget_toggle", "悬浮窗显示/隐藏快捷键", "widget toggle hotkey"),
+    ("config_dir_create_failed", "创建配置目录失败: {0}", "Failed to create config directory: {0}"),
+    ("config_serialize_failed", "序列化配置失败: {0}", "Failed to serialize config: {0}"),
+    ("config_open_failed", "无法打开配置文件: {0}", "Cannot open config file: {0}"),
+    ("config_read_failed", "读取配置失败: {0}", "Failed to read config: {0}"),
+    ("config_parse_failed", "配置 JSON 解析失败: {0}", "Failed to parse config JSON: {0}"),
+    ("ui_settings_write_failed", "写入 ui_settings.json 失败: {0}", "Failed to write ui_settings.json: {0}"),
+    ("entry_key_empty", "键为空", "Key is empty"),
+    ("entry_value_empty", "值为空（键: {0}）", "Value is empty (key: {0})"),
+    ("entry_key_too_long", "键过长(>{0}): {1}", "Key too long (>{0}): {1}"),
+    ("entry_value_too_long", "值过长(>{0}): {1}", "Value too long (>{0}): {1}"),
+    ("entry_bad_regex", "正则表达式无效（键: {0}）: {1}", "Invalid regular expression (key: {0}): {1}"),
+    ("entry_regex_unsupported", "正则表达式使用了 Python 不支持的语法 {1}（键: {0}）", "Regular expression uses syntax Python does not support: {1} (key: {0})"),
+    ("entries_rejected", "{0} 个条目未通过校验", "{0} entries failed validation"),
+    ("too_many_entries", "替换词典超出上限：{0} 条（最多 {1} 条）", "Too many replacement entries: {0} (max {1})"),
+];
 
-    let tmp_path = path.with_extension("json.tmp");
-    let data = serde_json::to_string_pretty(cfg).map_err(|e| format!("序列化配置失败: {}", e))?;
-    {
-        let mut f = fs::File::create(&tmp_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
-        f.write_all(data.as_bytes()).map_err(|e| format!("写入临时文件失败: {}", e))?;
-        f.sync_all().ok();
-    }
-    // 尝试原子替换
-    match fs::rename(&tmp_path, &path) {
-        Ok(_) => Ok(()),
-        Err(_e) => {
-            // Windows 上若目标存在可能失败：先删除再重命名
-            let _ = fs::remove_file(&path);
-            fs::rename(&tmp_path, &path).map_err(|e| format!("替换配置文件失败: {}", e))
-        }
+// 按指定语言查找文案；未知语言回退中文，未知键原样返回（便于发现遗漏）
+fn lookup_message(locale: &str, key: &str) -> String {
+    match MESSAGES.iter().find(|(k, _, _)| *k == key) {
+        Some((_, _, en)) if locale == LOCALE_EN => en.to_string(),
+        Some((_, zh, _)) => zh.to_string(),
+        None => key.to_string(),
     }
 }
 
-// 读取配置
-#[tauri::command]
-fn get_postprocess_config() -> Result<PostprocessConfig, String> {
-    read_postprocess_config_from_disk()
+fn format_message(template: String, args: &[&dyn std::fmt::Display]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(template, |text, (i, arg)| text.replace(&format!("{{{}}}", i), &arg.to_string()))
 }
 
-// 保存配置
-#[tauri::command]
-fn save_postprocess_config(payload: SavePostprocessPayload) -> Result<bool, String> {
-    let cfg = validate_and_clean_payload(payload)?;
-    write_postprocess_config_to_disk(&cfg)?;
-    Ok(true)
+// 当前提示语言保存在内存中（启动时由 ui_settings 初始化，set_locale 与配置导入时更新），
+// 查找文案不读取配置文件
+fn locale_cache() -> &'static std::sync::RwLock<String> {
+    static LOCALE: std::sync::OnceLock<std::sync::RwLock<String>> = std::sync::OnceLock::new();
+    LOCALE.get_or_init(|| std::sync::RwLock::new(default_locale()))
 }
 
-// -----------------------------
-// 使用统计：读/写（usage_stats.json）
-// -----------------------------
+fn current_locale() -> String {
+    locale_cache().read().map(|l| l.clone()).unwrap_or_else(|_| default_locale())
+}
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct UsageTotals {
-    #[serde(default)]
-    time_saved_sec: f64,
-    #[serde(default)]
-    total_chars: u64,
-    #[serde(default)]
-    corrections: u64,
+fn set_current_locale(locale: &str) {
+    if let Ok(mut guard) = locale_cache().write() {
+        *guard = locale.to_string();
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct UsageToday {
-    date: String,
-    #[serde(default)]
-    time_saved_sec: f64,
-    #[serde(default)]
-    total_chars: u64,
-    #[serde(default)]
-    corrections: u64,
+// 按当前 locale 设置返回文案
+fn t(key: &str) -> String {
+    lookup_message(&current_locale(), key)
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
-struct UsageStatsFile {
-    totals: UsageTotals,
-    today: UsageToday,
+fn tf(key: &str, args: &[&dyn std::fmt::Display]) -> String {
+    format_message(t(key), args)
 }
 
-fn resolve_usage_stats_path() -> PathBuf {
-    let root = find_project_root_for_config();
-    root.join("speak-keyboard-tauri").join("config").join("usage_stats.json")
+// Tauri命令：获取错误提示语言
+#[tauri::command]
+fn get_locale() -> String {
+    current_locale()
 }
 
-fn current_date_string() -> String {
-    Local::now().format("%Y-%m-%d").to_string()
+// Tauri命令：设置错误提示语言并通知各窗口
+#[tauri::command]
+fn set_locale(app: tauri::AppHandle, locale: String) -> Result<(), String> {
+    let locale = locale.trim().to_lowercase();
+    if locale != LOCALE_ZH && locale != LOCALE_EN {
+        return Err(format!("无效的语言: {}（可选 {} / {}）", locale, LOCALE_ZH, LOCALE_EN));
+    }
+    let mut settings = load_ui_settings();
+    settings.locale = locale.clone();
+    save_ui_settings(&settings)?;
+    set_current_locale(&locale);
+    let _ = app.emit("locale-changed", serde_json::json!({ "locale": locale }));
+    Ok(())
 }
 
-fn default_usage_stats() -> UsageStatsFile {
-    UsageStatsFile {
-        totals: UsageTotals { time_saved_sec: 0.0, total_chars: 0, corrections: 0 },
-        today: UsageToday { date: current_date_string(), time_saved_sec: 0.0, total_chars: 0, corrections: 0 },
+// Tauri命令：是否首次运行（前端据此决定是否显示引导向导）
+#[tauri::command]
+fn get_first_run() -> bool {
+    load_ui_settings().first_run
+}
+
+// Tauri命令：完成引导，之后不再显示向导
+#[tauri::command]
+fn complete_onboarding() -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    if !settings.first_run {
+        return Ok(());
     }
+    settings.first_run = false;
+    save_ui_settings(&settings)
 }
 
-fn read_usage_stats_from_disk() -> Result<UsageStatsFile, String> {
-    let path = resolve_usage_stats_path();
-    if !path.exists() {
-        return Ok(default_usage_stats());
+// -----------------------------
+// 日志：写入 config/app.log（按天滚动）并输出到控制台，级别可运行时调整
+// -----------------------------
+
+type LogReloadHandle = tracing_subscriber::reload::Handle<LevelFilter, tracing_subscriber::Registry>;
+
+struct LogState {
+    // 初始化失败（例如已存在全局订阅者）时为 None
+    reload: Option<LogReloadHandle>,
+    // 日志文件写入端；配置目录切换后替换为新目录下的 app.log
+    file: LogFileWriter,
+}
+
+// 可替换目标目录的日志文件写入端（tracing 订阅者只能初始化一次）
+#[derive(Clone)]
+struct LogFileWriter(Arc<Mutex<tracing_appender::rolling::RollingFileAppender>>);
+
+impl LogFileWriter {
+    fn new(dir: &std::path::Path) -> Self {
+        LogFileWriter(Arc::new(Mutex::new(tracing_appender::rolling::daily(dir, LOG_FILE_NAME))))
     }
-    match fs::read_to_string(&path) {
-        Ok(content) => match serde_json::from_str::<UsageStatsFile>(&content) {
-            Ok(mut stats) => {
-                // 基本健壮性：字段可能缺失
-                if stats.today.date.trim().is_empty() {
-                    stats.today.date = current_date_string();
-                }
-                Ok(stats)
-            }
-            Err(e) => {
-                println!("读取 usage_stats.json 解析失败: {}，将使用默认值", e);
-                Ok(default_usage_stats())
-            }
-        },
-        Err(e) => {
-            println!("读取 usage_stats.json 失败: {}，将使用默认值", e);
-            Ok(default_usage_stats())
+
+    fn switch_dir(&self, dir: &std::path::Path) {
+        let appender = tracing_appender::rolling::daily(dir, LOG_FILE_NAME);
+        if let Ok(mut guard) = self.0.lock() {
+            *guard = appender;
         }
     }
 }
 
-fn write_usage_stats_to_disk(stats: &UsageStatsFile) -> Result<(), String> {
-    let path = resolve_usage_stats_path();
-    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?; }
-    let tmp_path = path.with_extension("json.tmp");
-    let data = serde_json::to_string_pretty(stats).map_err(|e| format!("序列化统计失败: {}", e))?;
-    {
-        let mut f = fs::File::create(&tmp_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
-        f.write_all(data.as_bytes()).map_err(|e| format!("写入临时文件失败: {}", e))?;
-        f.sync_all().ok();
-    }
-    match fs::rename(&tmp_path, &path) {
-        Ok(_) => Ok(()),
-        Err(_) => {
-            let _ = fs::remove_file(&path);
-            fs::rename(&tmp_path, &path).map_err(|e| format!("替换统计文件失败: {}", e))
+impl Write for LogFileWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self.0.lock() {
+            Ok(mut guard) => guard.write(buf),
+            Err(_) => Ok(buf.len()),
         }
     }
-}
 
-fn rollover_today_if_needed(stats: &mut UsageStatsFile) -> bool {
-    let today = current_date_string();
-    if stats.today.date != today {
-        stats.today.date = today;
-        stats.today.time_saved_sec = 0.0;
-        stats.today.total_chars = 0;
-        stats.today.corrections = 0;
-        true
-    } else {
-        false
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self.0.lock() {
+            Ok(mut guard) => guard.flush(),
+            Err(_) => Ok(()),
+        }
     }
 }
 
-#[derive(Serialize)]
-struct UsageStatsSnapshot {
-    today_sec: f64,
-    total_sec: f64,
-    today_chars: u64,
-    total_chars: u64,
-    today_corrections: u64,
-    total_corrections: u64,
+fn parse_log_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level.trim())
+        .map_err(|_| format!("无效的日志级别: {}（可选 off/error/warn/info/debug/trace）", level))
 }
 
-fn accumulate_saved_time(app: &tauri::AppHandle, saved_sec: f64) -> Result<UsageStatsSnapshot, String> {
-    let state = app.state::<AppState>();
-    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
-
-    let mut stats = read_usage_stats_from_disk()?;
-    let _changed = rollover_today_if_needed(&mut stats);
-    let inc = if saved_sec.is_finite() && saved_sec > 0.0 { saved_sec } else { 0.0 };
-    stats.today.time_saved_sec += inc;
-    stats.totals.time_saved_sec += inc;
-    write_usage_stats_to_disk(&stats)?;
-    Ok(UsageStatsSnapshot {
-        today_sec: stats.today.time_saved_sec,
-        total_sec: stats.totals.time_saved_sec,
-        today_chars: stats.today.total_chars,
-        total_chars: stats.totals.total_chars,
-        today_corrections: stats.today.corrections,
-        total_corrections: stats.totals.corrections,
-    })
-}
+fn init_logging() -> LogState {
+    let level = parse_log_level(&load_ui_settings().log_level).unwrap_or(LevelFilter::INFO);
+    let (filter, reload) = tracing_subscriber::reload::Layer::new(level);
 
-fn accumulate_chars_and_corrections(app: &tauri::AppHandle, add_chars: u64, add_corrections: u64) -> Result<UsageStatsSnapshot, String> {
-    let state = app.state::<AppState>();
-    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let log_path = resolve_tauri_config_path(LOG_FILE_NAME);
+    let log_dir = log_path.parent().map(PathBuf::from).unwrap_or_else(|| PathBuf::from("."));
+    let file = LogFileWriter::new(&log_dir);
+    let writer = file.clone();
 
-    let mut stats = read_usage_stats_from_disk()?;
-    let _changed = rollover_today_if_needed(&mut stats);
-    if add_chars > 0 {
-        stats.today.total_chars = stats.today.total_chars.saturating_add(add_chars);
-        stats.totals.total_chars = stats.totals.total_chars.saturating_add(add_chars);
-    }
-    if add_corrections > 0 {
-        stats.today.corrections = stats.today.corrections.saturating_add(add_corrections);
-        stats.totals.corrections = stats.totals.corrections.saturating_add(add_corrections);
+    let result = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_ansi(false).with_writer(move || writer.clone()))
+        .try_init();
+    match result {
+        Ok(()) => LogState { reload: Some(reload), file },
+        Err(e) => {
+            eprintln!("初始化日志失败: {}", e);
+            LogState { reload: None, file }
+        }
     }
-    write_usage_stats_to_disk(&stats)?;
-    Ok(UsageStatsSnapshot {
-        today_sec: stats.today.time_saved_sec,
-        total_sec: stats.totals.time_saved_sec,
-        today_chars: stats.today.total_chars,
-        total_chars: stats.totals.total_chars,
-        today_corrections: stats.today.corrections,
-        total_corrections: stats.totals.corrections,
-    })
 }
 
+// Tauri命令：获取日志级别
 #[tauri::command]
-fn get_usage_stats(app: tauri::AppHandle) -> Result<UsageStatsSnapshot, String> {
-    let state = app.state::<AppState>();
-    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
-
-    let mut stats = read_usage_stats_from_disk()?;
-    let changed = rollover_today_if_needed(&mut stats);
-    if changed { write_usage_stats_to_disk(&stats)?; }
-    Ok(UsageStatsSnapshot {
-        today_sec: stats.today.time_saved_sec,
-        total_sec: stats.totals.time_saved_sec,
-        today_chars: stats.today.total_chars,
-        total_chars: stats.totals.total_chars,
-        today_corrections: stats.today.corrections,
-        total_corrections: stats.totals.corrections,
-    })
+fn get_log_level() -> String {
+    load_ui_settings().log_level
 }
 
-// Tauri命令：开始录音
+// Tauri命令：设置日志级别（立即生效并持久化）
 #[tauri::command]
-async fn start_recording(_state: tauri::State<'_, AppState>, bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
-    // 将 start 指令写入桥接进程
-    let stdin_arc = bridge.stdin.clone();
-    let mut guard = stdin_arc.lock().await;
-    if let Some(stdin) = guard.as_mut() {
-        println!("[tauri] 准备发送 start 指令到桥接进程");
-        let payload = serde_json::json!({"cmd": "start"}).to_string() + "\n";
-        if let Err(e) = stdin.write_all(payload.as_bytes()).await { println!("[tauri] 写入 start 指令失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
-        if let Err(e) = stdin.flush().await { println!("[tauri] 刷新 start 指令失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
-        println!("[tauri] start 指令已写入，等待桥接事件更新状态");
-        // 状态将由事件回传更新
-        Ok(())
-    } else {
-        Err("桥接进程未就绪，无法开始录音".to_string())
+fn set_log_level(state: State<'_, LogState>, level: String) -> Result<(), String> {
+    let filter = parse_log_level(&level)?;
+    if let Some(reload) = state.reload.as_ref() {
+        reload.reload(filter).map_err(|e| format!("调整日志级别失败: {}", e))?;
     }
+    let mut settings = load_ui_settings();
+    settings.log_level = level.trim().to_lowercase();
+    save_ui_settings(&settings)?;
+    info!("日志级别已调整为 {}", settings.log_level);
+    Ok(())
 }
 
-// Tauri命令：停止录音
-#[tauri::command]
-async fn stop_recording(_state: tauri::State<'_, AppState>, bridge: tauri::State<'_, BridgeState>) -> Result<String, String> {
-    // 将 stop 指令写入桥接进程
-    let stdin_arc = bridge.stdin.clone();
-    let mut guard = stdin_arc.lock().await;
-    if let Some(stdin) = guard.as_mut() {
-        println!("[tauri] 准备发送 stop 指令到桥接进程");
-        let payload = serde_json::json!({"cmd": "stop"}).to_string() + "\n";
-        if let Err(e) = stdin.write_all(payload.as_bytes()).await { println!("[tauri] 写入 stop 指令失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
-        if let Err(e) = stdin.flush().await { println!("[tauri] 刷新 stop 指令失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
-        println!("[tauri] stop 指令已写入，等待桥接事件更新状态");
-        // 返回简单确认字符串，实际结果通过事件回传
-        Ok("ok".to_string())
-    } else {
-        Err("桥接进程未就绪，无法停止录音".to_string())
-    }
+// 按天滚动后的文件名为 app.log.YYYY-MM-DD，取名称排序最新的一个
+fn find_latest_log_file() -> Option<PathBuf> {
+    let log_path = resolve_tauri_config_path(LOG_FILE_NAME);
+    let dir = log_path.parent()?;
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().starts_with(LOG_FILE_NAME))
+        .filter(|entry| entry.path().is_file())
+        .max_by_key(|entry| entry.file_name())
+        .map(|entry| entry.path())
 }
 
-// 切换录音：以后端状态为准，避免前端状态不同步导致无法停止
-#[tauri::command]
-async fn toggle_recording(state: tauri::State<'_, AppState>, bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
-    let currently_recording = { *state.is_recording.lock().unwrap() };
-    println!("[tauri] toggle_recording 调用：当前 is_recording={}，将发送{}", currently_recording, if currently_recording { "stop" } else { "start" });
-    if currently_recording {
-        let stdin_arc = bridge.stdin.clone();
-        let mut guard = stdin_arc.lock().await;
-        if let Some(stdin) = guard.as_mut() {
-            let payload = serde_json::json!({"cmd": "stop"}).to_string() + "\n";
-            if let Err(e) = stdin.write_all(payload.as_bytes()).await { println!("[tauri] toggle_recording: 写入 stop 失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
-            if let Err(e) = stdin.flush().await { println!("[tauri] toggle_recording: 刷新 stop 失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
-            println!("[tauri] toggle_recording: stop 指令已写入（本地预切换为 false，最终以事件为准）");
-            // 预先切换为 false，最终以事件为准
-            {
-                let mut rec = state.is_recording.lock().unwrap();
-                *rec = false;
-            }
-            Ok(())
-        } else {
-            println!("[tauri] toggle_recording: stdin 不可用，无法发送 stop");
-            Err("桥接进程未就绪，无法停止录音".to_string())
-        }
-    } else {
-        let stdin_arc = bridge.stdin.clone();
-        let mut guard = stdin_arc.lock().await;
-        if let Some(stdin) = guard.as_mut() {
-            let payload = serde_json::json!({"cmd": "start"}).to_string() + "\n";
-            if let Err(e) = stdin.write_all(payload.as_bytes()).await { println!("[tauri] toggle_recording: 写入 start 失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
-            if let Err(e) = stdin.flush().await { println!("[tauri] toggle_recording: 刷新 start 失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
-            println!("[tauri] toggle_recording: start 指令已写入（本地预切换为 true，最终以事件为准）");
-            // 预先切换为 true，最终以事件为准
-            {
-                let mut rec = state.is_recording.lock().unwrap();
-                *rec = true;
-            }
-            Ok(())
-        } else {
-            println!("[tauri] toggle_recording: stdin 不可用，无法发送 start");
-            Err("桥接进程未就绪，无法开始录音".to_string())
-        }
+// 从文件末尾分块向前读取，直到凑够 lines 行（不读取整个文件）
+fn read_log_tail(path: &std::path::Path, lines: usize) -> Result<Vec<String>, String> {
+    let mut file = match fs::File::open(path) {
+        Ok(file) => file,
+        // 查找后被滚动或删除，视为暂无日志
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("打开日志文件失败: {}", e)),
+    };
+    let len = file.seek(SeekFrom::End(0)).map_err(|e| format!("定位日志文件失败: {}", e))?;
+
+    let mut pos = len;
+    let mut tail: Vec<u8> = Vec::new();
+    let mut newlines = 0usize;
+    let mut chunk = vec![0u8; LOG_TAIL_CHUNK_BYTES];
+    // 需要 lines + 1 个换行符才能确定最前一行完整（末尾换行单独计）
+    while pos > 0 && newlines <= lines {
+        let size = LOG_TAIL_CHUNK_BYTES.min(pos as usize);
+        pos -= size as u64;
+        file.seek(SeekFrom::Start(pos)).map_err(|e| format!("定位日志文件失败: {}", e))?;
+        file.read_exact(&mut chunk[..size]).map_err(|e| format!("读取日志文件失败: {}", e))?;
+        newlines += chunk[..size].iter().filter(|&&b| b == b'\n').count();
+        tail.splice(0..0, chunk[..size].iter().copied());
     }
+
+    // 可能截断在多字节字符中间或正在写入的半行，使用 lossy 解码容错
+    let text = String::from_utf8_lossy(&tail);
+    let all: Vec<&str> = text.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    Ok(all[start..].iter().map(|s| s.to_string()).collect())
 }
 
-// Tauri命令：获取录音状态
+// Tauri命令：读取最近的日志（最多 LOG_TAIL_MAX_LINES 行；日志不存在时返回空列表）
 #[tauri::command]
-fn get_recording_state(state: tauri::State<'_, AppState>) -> bool {
-    *state.is_recording.lock().unwrap()
+fn get_recent_logs(lines: usize) -> Result<Vec<String>, String> {
+    let lines = lines.min(LOG_TAIL_MAX_LINES);
+    if lines == 0 {
+        return Ok(Vec::new());
+    }
+    match find_latest_log_file() {
+        Some(path) => read_log_tail(&path, lines),
+        None => Ok(Vec::new()),
+    }
 }
 
-#[derive(Clone, Serialize)]
-struct RecordingHotkeyInfo {
-    current: String,
+fn bridge_log_paths() -> (PathBuf, PathBuf) {
+    let path = resolve_tauri_config_path(BRIDGE_LOG_FILE_NAME);
+    let rotated = path.with_extension("log.1");
+    (path, rotated)
 }
 
-#[derive(Deserialize)]
-struct SetRecordingHotkeyPayload {
-    hotkey: String,
+fn file_size(path: &std::path::Path) -> u64 {
+    fs::metadata(path).map(|m| m.len()).unwrap_or(0)
 }
 
-#[tauri::command]
-fn get_recording_hotkey(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<RecordingHotkeyInfo, String> {
-    let current = {
-        let guard = state.recording_hotkey.lock().map_err(|e| format!("获取当前快捷键失败: {}", e))?;
-        guard.clone()
-    };
-
-    if current.is_empty() {
-        return Ok(RecordingHotkeyInfo {
-            current: DEFAULT_RECORDING_HOTKEY.to_string(),
-        });
-    }
-
-    // 确保已注册（处理第一次启动时未注册的情况）
-    if let Err(err) = register_recording_hotkey(&app, &current) {
-        println!("当前快捷键注册失败 {}，将尝试回退默认值: {}", current, err);
-        register_recording_hotkey(&app, DEFAULT_RECORDING_HOTKEY)?;
-        return Ok(RecordingHotkeyInfo {
-            current: DEFAULT_RECORDING_HOTKEY.to_string(),
-        });
+// 追加一行桥接 stderr 输出；超过大小上限时先轮转
+fn append_bridge_log(app: &tauri::AppHandle, line: &[u8]) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _guard = state.bridge_log_lock.lock().map_err(|e| format!("获取桥接日志锁失败: {}", e))?;
+    let (path, rotated) = bridge_log_paths();
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?; }
+    if file_size(&path) + line.len() as u64 > BRIDGE_LOG_MAX_BYTES {
+        let _ = fs::remove_file(&rotated);
+        fs::rename(&path, &rotated).map_err(|e| format!("轮转桥接日志失败: {}", e))?;
     }
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("打开桥接日志失败: {}", e))?;
+    file.write_all(line).map_err(|e| format!("写入桥接日志失败: {}", e))
+}
 
-    Ok(RecordingHotkeyInfo { current })
+// Tauri命令：桥接日志占用的字节数（含轮转出的 bridge.log.1）
+#[tauri::command]
+fn get_bridge_log_size() -> u64 {
+    let (path, rotated) = bridge_log_paths();
+    file_size(&path) + file_size(&rotated)
 }
 
+// Tauri命令：清空桥接日志（截断 bridge.log 并删除 bridge.log.1），返回释放的字节数
 #[tauri::command]
-fn set_recording_hotkey(app: tauri::AppHandle, payload: SetRecordingHotkeyPayload, state: State<'_, AppState>) -> Result<bool, String> {
-    let new_hotkey = payload.hotkey.trim();
-    if new_hotkey.is_empty() {
-        return Err("快捷键不能为空".to_string());
+fn clear_bridge_log(state: State<'_, AppState>) -> Result<u64, String> {
+    let _guard = state.bridge_log_lock.lock().map_err(|e| format!("获取桥接日志锁失败: {}", e))?;
+    let (path, rotated) = bridge_log_paths();
+    let mut freed = 0;
+    if path.exists() {
+        freed += file_size(&path);
+        // 截断而非删除：桥接运行期间文件保持存在
+        fs::OpenOptions::new()
+            .write(true)
+            .truncate(true)
+            .open(&path)
+            .map_err(|e| format!("清空桥接日志失败: {}", e))?;
     }
-
-    // 简单校验：不能只包含修饰键
-    let upper = new_hotkey.to_ascii_uppercase();
-    let is_only_modifier = matches!(upper.as_str(), "CTRL" | "SHIFT" | "ALT" | "META" | "COMMAND" | "CONTROL");
-    if is_only_modifier {
-        return Err("快捷键必须包含具体按键，例如 Ctrl+Shift+K".to_string());
+    if rotated.exists() {
+        freed += file_size(&rotated);
+        fs::remove_file(&rotated).map_err(|e| format!("删除 {} 失败: {}", rotated.display(), e))?;
     }
+    info!("[tauri] 已清空桥接日志，释放 {} 字节", freed);
+    Ok(freed)
+}
 
-    register_recording_hotkey(&app, new_hotkey)?;
+// 录音状态管理
+#[derive(Default)]
+struct AppState {
+    // 录音状态（由桥接事件驱动）
+    is_recording: std::sync::Mutex<bool>,
+    // 录音暂停状态（由桥接 recording_paused 事件驱动；停止录音时清除）
+    is_paused: Mutex<bool>,
+    // 录音开始时的前台窗口（输出目标），录音结束时清除
+    output_target: Mutex<Option<ForegroundWindow>>,
+    // 最近一次非空转写结果（不受 history_limit 影响）
+    last_transcription: Mutex<Option<String>>,
+    // 全局快捷键按下态（用于边沿检测）
+    hotkey_down: std::sync::Mutex<bool>,
+    // 去抖动：最近一次切换时间
+    last_toggle: std::sync::Mutex<Option<Instant>>,
+    // 去抖动间隔（毫秒，由 ui_settings.json 初始化）
+    hotkey_debounce_ms: Mutex<u64>,
+    // 最短录音时长（毫秒，由 ui_settings.json 初始化）
+    min_recording_ms: Mutex<u64>,
+    // 快捷键发起录音的时间，用于判断是否短于最短录音时长
+    recording_started_at: std::sync::Mutex<Option<Instant>>,
+    // 双击修饰键：两次单击的最大间隔（毫秒）与各修饰键最近一次“干净”单击的按下时间
+    double_tap_window_ms: Mutex<u64>,
+    modifier_last_tap: Mutex<std::collections::HashMap<String, Instant>>,
+    // 双击检测线程的代数：重新注册快捷键时递增，旧线程据此退出
+    double_tap_generation: AtomicU64,
+    // 正在捕获下一次按键（capture_next_hotkey），期间快捷键已全部注销
+    hotkey_capture_active: AtomicBool,
+    // 悬浮窗自动隐藏计时的代数：每次安排或取消时递增，过期的计时任务据此放弃隐藏
+    widget_autohide_generation: AtomicU64,
+    // stats-updated 事件节流状态
+    stats_emit: Mutex<StatsEmitState>,
+    // 录音超时自动停止计时的代数：录音开始时递增并启动计时，停止或桥接退出时递增以作废
+    max_recording_generation: AtomicU64,
+    // 当前已注册的录音快捷键（序列化字符串，如 "F2"）
+    recording_hotkey: Mutex<String>,
+    // 独立的开始/停止快捷键（None 表示未启用）
+    start_hotkey: Mutex<Option<String>>,
+    stop_hotkey: Mutex<Option<String>>,
+    // 取消录音快捷键（None 表示未启用）
+    cancel_hotkey: Mutex<Option<String>>,
+    // 重新输出上一次转写结果的快捷键（None 表示未启用）
+    retype_hotkey: Mutex<Option<String>>,
+    // 显示/隐藏悬浮窗的快捷键（None 表示未启用）
+    widget_toggle_hotkey: Mutex<Option<String>>,
+    // 启动时配置的录音快捷键注册失败、已回退为默认值的原因（None 表示配置已生效）
+    hotkey_registration_error: Mutex<Option<String>>,
+    // 快捷键模式（toggle / push_to_talk）
+    hotkey_mode: Mutex<String>,
+    // 按住说话：本次按下是否已发送 start（松开时据此决定是否发送 stop）
+    ptt_active: Mutex<bool>,
+    // 按住说话：按下会话序号，用于超时任务判断是否仍是同一次按下
+    ptt_session: Mutex<u64>,
+    // 按住说话：start 指令仍在发送中（此时松开由按下处理补发 stop）
+    ptt_starting: Mutex<bool>,
+    // 使用统计文件锁，避免并发读写冲突
+    usage_lock: std::sync::Mutex<()>,
+    // 转写历史文件锁，避免并发追加时行交错
+    history_lock: std::sync::Mutex<()>,
+    // bridge.log 写入、轮转与清空互斥，避免清空时与正在写入的行交错
+    bridge_log_lock: std::sync::Mutex<()>,
+    // 串行化 typing_samples.json 的读改写
+    typing_samples_lock: std::sync::Mutex<()>,
+    // 配置目录监听器（需保持存活）
+    config_watcher: Mutex<Option<notify::RecommendedWatcher>>,
+    // postprocess.json 重新加载去抖序号
+    postprocess_reload_seq: Mutex<u64>,
+    // 应用最近一次写入 postprocess.json 的内容，用于忽略自身写入触发的监听事件
+    postprocess_last_written: Mutex<Option<String>>,
+    // 窗口位置保存去抖：窗口 label -> 最近一次移动序号
+    window_geometry_seq: Mutex<std::collections::HashMap<String, u64>>,
+    // 托盘菜单中的“开始/停止录音”项（托盘创建后写入）
+    tray_toggle_item: Mutex<Option<MenuItem<tauri::Wry>>>,
+    // 本地 HTTP 接口监听任务（None 表示未运行）
+    http_api_task: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
+    // 最近一次转写性能测试结果
+    last_benchmark: Mutex<Option<TranscriptionBenchmark>>,
+    // 录音期间的防休眠请求（None 表示未持有）
+    sleep_inhibitor: Mutex<Option<SleepInhibitor>>,
+    // 转写语言列表缓存：(模型名, 语言列表)，切换模型时清空
+    language_cache: Mutex<Option<(String, Vec<LanguageInfo>)>>,
+}
+
+// 桥接进程状态（保存 stdin 句柄供命令写入）
+struct BridgeState {
+    stdin: Arc<tokio::sync::Mutex<Option<ChildStdin>>>,
+    should_restart: Arc<AtomicBool>,
+    // 用户手动重启：守护循环据此重置尝试次数并跳过退避
+    manual_restart: Arc<AtomicBool>,
+    // 唤醒处于退避等待中的守护循环
+    restart_notify: Arc<tokio::sync::Notify>,
+    // 子进程信息（pid、尝试次数、最近退出码），由守护循环更新
+    process_info: Arc<Mutex<BridgeProcessInfo>>,
+    // 最近一次收到 pong（或 bridge_ready）的时间；None 表示桥接尚未就绪
+    last_pong: Arc<Mutex<Option<Instant>>>,
+    // 等待应答的请求：req_id -> 应答发送端（stdout 循环按 req_id 分发）
+    pending_requests: Arc<Mutex<std::collections::HashMap<u64, tokio::sync::oneshot::Sender<Value>>>>,
+    next_request_id: Arc<AtomicU64>,
+    // 桥接回报 bridge_shutdown（reason=graceful）时唤醒等待退出的一方
+    shutdown_done: Arc<tokio::sync::Notify>,
+    // 用户主动停用桥接（stop_bridge）：守护循环挂起等待 start_bridge，而不是退出
+    disabled: Arc<AtomicBool>,
+    // 最近一次 bridge_error（事件只推送一次，这里保留供设置界面展示，用户可清除）
+    last_error: Arc<Mutex<Option<BridgeErrorRecord>>>,
+    // 桥接版本（首次查询后缓存，子进程重启时清空）
+    version: Arc<Mutex<Option<String>>>,
+}
+
+#[derive(Serialize, Clone)]
+struct BridgeErrorRecord {
+    message: String,
+    timestamp: String,
+}
+
+#[derive(Default, Clone)]
+struct BridgeProcessInfo {
+    pid: Option<u32>,
+    restart_attempts: u32,
+    last_exit_code: Option<i32>,
+    // 达到最大重启次数后停止重启，等待用户恢复
+    gave_up: bool,
+}
+
+#[derive(Serialize)]
+struct BridgeStatus {
+    running: bool,
+    pid: Option<u32>,
+    restart_attempts: u32,
+    last_exit_code: Option<i32>,
+    gave_up: bool,
+    // false 表示已被用户通过 stop_bridge 停用
+    enabled: bool,
+}
+
+impl Drop for BridgeState {
+    fn drop(&mut self) {
+        let stdin_arc = Arc::clone(&self.stdin);
+        let shutdown_done = Arc::clone(&self.shutdown_done);
+        tauri::async_runtime::block_on(async move {
+            shutdown_bridge_gracefully(&stdin_arc, &shutdown_done, "Drop").await;
+        });
+    }
+}
+
+fn effective_shutdown_grace_ms(settings: &UiSettings) -> u64 {
+    settings.shutdown_grace_ms.min(MAX_SHUTDOWN_GRACE_MS)
+}
+
+// 发送 shutdown 并等待桥接回报清理完成（bridge_shutdown，reason=graceful），
+// 最多等待 shutdown_grace_ms，避免退出被无限期阻塞
+async fn shutdown_bridge_gracefully(
+    stdin: &tokio::sync::Mutex<Option<ChildStdin>>,
+    shutdown_done: &tokio::sync::Notify,
+    context: &str,
+) {
+    let grace = Duration::from_millis(effective_shutdown_grace_ms(&load_ui_settings()));
+    // 发送前先登记等待，避免事件早于等待到达而丢失
+    let notified = shutdown_done.notified();
+    tokio::pin!(notified);
+    notified.as_mut().enable();
+    {
+        let mut guard = stdin.lock().await;
+        let Some(mut stdin) = guard.take() else {
+            info!("[tauri] {}: stdin 已为空，跳过 shutdown 指令", context);
+            return;
+        };
+        info!("[tauri] {}: 发送 shutdown 指令给桥接进程", context);
+        let payload = serde_json::json!({"cmd": "shutdown"}).to_string() + "\n";
+        if let Err(err) = stdin.write_all(payload.as_bytes()).await {
+            warn!("[tauri] {}: shutdown 写入失败: {}", context, err);
+            return;
+        }
+        if let Err(err) = stdin.flush().await {
+            warn!("[tauri] {}: shutdown 刷新失败: {}", context, err);
+            return;
+        }
+    }
+    match tokio::time::timeout(grace, notified).await {
+        Ok(()) => info!("[tauri] {}: 桥接进程已完成清理", context),
+        Err(_) => warn!("[tauri] {}: 等待桥接进程清理超时（{}ms）", context, grace.as_millis()),
+    }
+}
+
+// 向桥接进程写入一条指令（{"cmd": ...}）
+async fn send_bridge_command(app: &tauri::AppHandle, cmd: &str) -> Result<(), String> {
+    send_bridge_payload(app, serde_json::json!({"cmd": cmd})).await
+}
+
+// 请求/应答：为指令附加 req_id 并写入桥接，等待 stdout 循环分发回带相同 req_id 的应答
+async fn bridge_request(app: &tauri::AppHandle, cmd: Value) -> Result<Value, String> {
+    bridge_request_with_timeout(app, cmd, BRIDGE_REQUEST_TIMEOUT).await
+}
+
+async fn bridge_request_with_timeout(app: &tauri::AppHandle, mut cmd: Value, timeout: Duration) -> Result<Value, String> {
+    let name = cmd.get("cmd").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let (req_id, rx) = {
+        let bridge = app.state::<BridgeState>();
+        let req_id = bridge.next_request_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        bridge.pending_requests.lock().unwrap().insert(req_id, tx);
+        (req_id, rx)
+    };
+    let forget_request = || {
+        app.state::<BridgeState>().pending_requests.lock().unwrap().remove(&req_id);
+    };
+
+    match cmd.as_object_mut() {
+        Some(obj) => {
+            obj.insert("req_id".to_string(), Value::from(req_id));
+        }
+        None => {
+            forget_request();
+            return Err("桥接请求必须是 JSON 对象".to_string());
+        }
+    }
+    if let Err(e) = send_bridge_payload(app, cmd).await {
+        forget_request();
+        return Err(e);
+    }
+    match tokio::time::timeout(timeout, rx).await {
+        Ok(Ok(reply)) => Ok(reply),
+        Ok(Err(_)) => Err(format!("桥接进程已退出，未收到 {} 应答", name)),
+        Err(_) => {
+            forget_request();
+            Err(format!("等待 {} 应答超时", name))
+        }
+    }
+}
+
+// 向桥接进程写入一行 JSON（可携带额外参数）
+async fn send_bridge_payload(app: &tauri::AppHandle, payload: Value) -> Result<(), String> {
+    let cmd = payload.get("cmd").and_then(|v| v.as_str()).unwrap_or("").to_string();
+    let stdin_arc = app.state::<BridgeState>().stdin.clone();
+    let mut guard = stdin_arc.lock().await;
+    if let Some(stdin) = guard.as_mut() {
+        let line = payload.to_string() + "\n";
+        stdin
+            .write_all(line.as_bytes())
+            .await
+            .map_err(|e| format!("写入 {} 指令失败: {}", cmd, e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("刷新 {} 指令失败: {}", cmd, e))?;
+        debug!("[tauri] 已发送 {} 指令", cmd);
+        Ok(())
+    } else {
+        Err(format!("桥接进程未就绪，无法发送 {} 指令", cmd))
+    }
+}
+
+// -----------------------------
+// 全局快捷键：读写配置 + 注册/注销
+// -----------------------------
+
+fn parse_hotkey(hotkey: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(hotkey)
+        .map_err(|e| tf("hotkey_parse_failed", &[&hotkey, &e]))
+}
+
+// 规范化快捷键字符串（解析后再格式化），用于比较是否冲突
+fn normalize_hotkey(hotkey: &str) -> Result<String, String> {
+    if let Some(modifier) = DoubleTapModifier::parse(hotkey) {
+        return Ok(format!("双击 {}", modifier.name()));
+    }
+    Ok(format!("{}", parse_hotkey(hotkey)?))
+}
+
+// 检查多个快捷键之间是否重复：bindings 为 (用途, 快捷键)
+fn check_hotkey_conflicts(bindings: &[(&str, &str)]) -> Result<(), String> {
+    let mut seen: Vec<(&str, String)> = Vec::new();
+    for (purpose, hotkey) in bindings.iter() {
+        let normalized = normalize_hotkey(hotkey)?;
+        if let Some((other, _)) = seen.iter().find(|(_, k)| *k == normalized) {
+            return Err(tf("hotkey_conflict", &[other, purpose, hotkey]));
+        }
+        seen.push((purpose, normalized));
+    }
+    Ok(())
+}
+
+// 当前全部快捷键配置的快照，用于冲突检查与失败回滚
+#[derive(Clone)]
+struct HotkeyBindings {
+    recording: String,
+    start: Option<String>,
+    stop: Option<String>,
+    cancel: Option<String>,
+    retype: Option<String>,
+    widget_toggle: Option<String>,
+}
+
+impl HotkeyBindings {
+    fn from_state(state: &AppState) -> Self {
+        let recording = state.recording_hotkey.lock().map(|g| g.clone()).unwrap_or_default();
+        HotkeyBindings {
+            recording: if recording.is_empty() { DEFAULT_RECORDING_HOTKEY.to_string() } else { recording },
+            start: state.start_hotkey.lock().map(|g| g.clone()).unwrap_or(None),
+            stop: state.stop_hotkey.lock().map(|g| g.clone()).unwrap_or(None),
+            cancel: state.cancel_hotkey.lock().map(|g| g.clone()).unwrap_or(None),
+            retype: state.retype_hotkey.lock().map(|g| g.clone()).unwrap_or(None),
+            widget_toggle: state.widget_toggle_hotkey.lock().map(|g| g.clone()).unwrap_or(None),
+        }
+    }
+
+    // 写回辅助快捷键（录音快捷键由 register_recording_hotkey 负责更新）
+    fn store_auxiliary(&self, state: &AppState) {
+        if let Ok(mut guard) = state.start_hotkey.lock() { *guard = self.start.clone(); }
+        if let Ok(mut guard) = state.stop_hotkey.lock() { *guard = self.stop.clone(); }
+        if let Ok(mut guard) = state.cancel_hotkey.lock() { *guard = self.cancel.clone(); }
+        if let Ok(mut guard) = state.retype_hotkey.lock() { *guard = self.retype.clone(); }
+        if let Ok(mut guard) = state.widget_toggle_hotkey.lock() { *guard = self.widget_toggle.clone(); }
+    }
+
+    fn check_conflicts(&self) -> Result<(), String> {
+        let purposes: [String; 6] = ["recording", "start", "stop", "cancel", "retype", "widget_toggle"]
+            .map(|p| t(&format!("hotkey_purpose_{}", p)));
+        let mut bindings: Vec<(&str, &str)> = vec![(purposes[0].as_str(), self.recording.as_str())];
+        if let Some(k) = self.start.as_deref() { bindings.push((purposes[1].as_str(), k)); }
+        if let Some(k) = self.stop.as_deref() { bindings.push((purposes[2].as_str(), k)); }
+        if let Some(k) = self.cancel.as_deref() { bindings.push((purposes[3].as_str(), k)); }
+        if let Some(k) = self.retype.as_deref() { bindings.push((purposes[4].as_str(), k)); }
+        if let Some(k) = self.widget_toggle.as_deref() { bindings.push((purposes[5].as_str(), k)); }
+        check_hotkey_conflicts(&bindings)
+    }
+}
+
+// 应用新的快捷键配置：先检查冲突，注册失败时恢复之前的配置
+fn apply_hotkey_bindings(app: &tauri::AppHandle, bindings: &HotkeyBindings) -> Result<(), String> {
+    bindings.check_conflicts()?;
+    let app_state = app.state::<AppState>();
+    let previous = HotkeyBindings::from_state(&app_state);
+    bindings.store_auxiliary(&app_state);
+    if let Err(err) = register_recording_hotkey(app, &bindings.recording) {
+        previous.store_auxiliary(&app_state);
+        if let Err(e) = register_recording_hotkey(app, &previous.recording) {
+            warn!("恢复之前的快捷键失败: {}", e);
+        }
+        return Err(err);
+    }
+    Ok(())
+}
+
+// 注册只发送固定指令的快捷键（独立的开始/停止键）
+fn register_command_hotkey(app: &tauri::AppHandle, hotkey: &str, cmd: &'static str) -> Result<(), String> {
+    let shortcut = parse_hotkey(hotkey)?;
+    let hotkey_string = format!("{}", shortcut);
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            info!("全局快捷键 {} 被按下，发送 {} 指令", hotkey_string, cmd);
+            let handle_for_task = app_handle.clone();
+            let hotkey_for_task = hotkey_string.clone();
+            tauri::async_runtime::spawn(async move {
+                let app_state = handle_for_task.state::<AppState>();
+                let recording = *app_state.is_recording.lock().unwrap();
+                // 开始键在录音中、停止键在空闲时不做任何处理
+                if (cmd == "start") == recording {
+                    debug!("[tauri] 快捷键 {}：当前 is_recording={}，忽略 {} 指令", hotkey_for_task, recording, cmd);
+                    return;
+                }
+                if let Err(err) = ensure_bridge_enabled(&handle_for_task.state::<BridgeState>()) {
+                    warn!("处理快捷键 {} 失败: {}", hotkey_for_task, err);
+                    return;
+                }
+                if cmd == "start" {
+                    if ensure_recording_start_allowed(&handle_for_task).is_err() {
+                        return;
+                    }
+                    let target = capture_output_target(&app_state);
+                    if let Err(err) = send_bridge_payload(&handle_for_task, target).await {
+                        warn!("处理快捷键 {} 失败: {}", hotkey_for_task, err);
+                        return;
+                    }
+                } else {
+                    *app_state.recording_started_at.lock().unwrap() = None;
+                }
+                if let Err(err) = send_bridge_command(&handle_for_task, cmd).await {
+                    warn!("处理快捷键 {} 失败: {}", hotkey_for_task, err);
+                    return;
+                }
+                if cmd == "start" {
+                    *app_state.recording_started_at.lock().unwrap() = Some(Instant::now());
+                }
+                if let Some(window) = handle_for_task.get_webview_window("widget") {
+                    let _ = window.emit("global-shortcut-pressed", hotkey_for_task);
+                }
+            });
+        })
+        .map_err(|e| tf("hotkey_register_failed", &[&hotkey, &e]))
+}
+
+// 注册录音快捷键之外的辅助快捷键（读取 AppState 中的当前配置）
+fn register_auxiliary_hotkeys(app: &tauri::AppHandle) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+    let start = app_state.start_hotkey.lock().unwrap().clone();
+    let stop = app_state.stop_hotkey.lock().unwrap().clone();
+    let cancel = app_state.cancel_hotkey.lock().unwrap().clone();
+    let retype = app_state.retype_hotkey.lock().unwrap().clone();
+    let widget_toggle = app_state.widget_toggle_hotkey.lock().unwrap().clone();
+    if let Some(hotkey) = start {
+        register_command_hotkey(app, &hotkey, "start")?;
+    }
+    if let Some(hotkey) = stop {
+        register_command_hotkey(app, &hotkey, "stop")?;
+    }
+    if let Some(hotkey) = cancel {
+        register_cancel_hotkey(app, &hotkey)?;
+    }
+    if let Some(hotkey) = retype {
+        register_retype_hotkey(app, &hotkey)?;
+    }
+    if let Some(hotkey) = widget_toggle {
+        register_widget_toggle_hotkey(app, &hotkey)?;
+    }
+    Ok(())
+}
+
+// 辅助快捷键用途（与 hotkey_purpose_* 文案对应）
+const AUXILIARY_HOTKEY_PURPOSES: [&str; 5] = ["start", "stop", "cancel", "retype", "widget_toggle"];
+
+fn auxiliary_hotkey_slot<'a>(state: &'a AppState, purpose: &str) -> &'a Mutex<Option<String>> {
+    match purpose {
+        "start" => &state.start_hotkey,
+        "stop" => &state.stop_hotkey,
+        "cancel" => &state.cancel_hotkey,
+        "retype" => &state.retype_hotkey,
+        _ => &state.widget_toggle_hotkey,
+    }
+}
+
+// 单独注册一个辅助快捷键（不注销其他快捷键）
+fn register_auxiliary_hotkey(app: &tauri::AppHandle, purpose: &str, hotkey: &str) -> Result<(), String> {
+    match purpose {
+        "start" => register_command_hotkey(app, hotkey, "start"),
+        "stop" => register_command_hotkey(app, hotkey, "stop"),
+        "cancel" => register_cancel_hotkey(app, hotkey),
+        "retype" => register_retype_hotkey(app, hotkey),
+        _ => register_widget_toggle_hotkey(app, hotkey),
+    }
+}
+
+// 显示/隐藏悬浮窗：与 toggle_window_visibility("widget") 相同，不影响录音状态
+fn register_widget_toggle_hotkey(app: &tauri::AppHandle, hotkey: &str) -> Result<(), String> {
+    let shortcut = parse_hotkey(hotkey)?;
+    let hotkey_string = format!("{}", shortcut);
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            info!("全局快捷键 {} 被按下，切换悬浮窗显示", hotkey_string);
+            if let Err(err) = toggle_window_visibility(app_handle.clone(), "widget") {
+                warn!("处理快捷键 {} 失败: {}", hotkey_string, err);
+            }
+        })
+        .map_err(|e| tf("hotkey_register_failed", &[&hotkey, &e]))
+}
+
+fn register_retype_hotkey(app: &tauri::AppHandle, hotkey: &str) -> Result<(), String> {
+    let shortcut = parse_hotkey(hotkey)?;
+    let hotkey_string = format!("{}", shortcut);
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            info!("全局快捷键 {} 被按下，重新输出上一次转写结果", hotkey_string);
+            let handle_for_task = app_handle.clone();
+            let hotkey_for_task = hotkey_string.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = retype_last(handle_for_task).await {
+                    warn!("处理快捷键 {} 失败: {}", hotkey_for_task, err);
+                }
+            });
+        })
+        .map_err(|e| tf("hotkey_register_failed", &[&hotkey, &e]))
+}
+
+fn register_cancel_hotkey(app: &tauri::AppHandle, hotkey: &str) -> Result<(), String> {
+    let shortcut = parse_hotkey(hotkey)?;
+    let hotkey_string = format!("{}", shortcut);
+    app.global_shortcut()
+        .on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            if event.state != ShortcutState::Pressed {
+                return;
+            }
+            let handle_for_task = app_handle.clone();
+            let hotkey_for_task = hotkey_string.clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(err) = handle_cancel_hotkey(handle_for_task).await {
+                    warn!("处理快捷键 {} 失败: {}", hotkey_for_task, err);
+                }
+            });
+        })
+        .map_err(|e| tf("hotkey_register_failed", &[&hotkey, &e]))
+}
+
+// 取消录音：仅在录音中生效，丢弃本次音频，不产生转写输出
+async fn handle_cancel_hotkey(app: tauri::AppHandle) -> Result<(), String> {
+    let currently = { *app.state::<AppState>().is_recording.lock().unwrap() };
+    if !currently {
+        return Ok(());
+    }
+    info!("[tauri] 取消快捷键被按下，发送 cancel 指令");
+    send_bridge_command(&app, "cancel").await?;
+    {
+        let app_state = app.state::<AppState>();
+        *app_state.is_recording.lock().unwrap() = false;
+        *app_state.ptt_active.lock().unwrap() = false;
+    }
+    set_paused_state(&app, false);
+    update_tray_recording_state(&app, false);
+    if let Some(window) = app.get_webview_window("widget") {
+        let _ = window.emit("recording-cancelled", ());
+    }
+    Ok(())
+}
+
+// 录音快捷键按下：真实按键与 trigger_hotkey_test 共用，保证去抖与边沿检测行为一致
+fn on_recording_hotkey_pressed(app: &tauri::AppHandle, hotkey: String) {
+    let handle_for_task = app.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = handle_recording_hotkey(handle_for_task.clone(), hotkey.clone()).await {
+            warn!("处理快捷键 {} 失败: {}", hotkey, err);
+            let app_state = handle_for_task.state::<AppState>();
+            let mut down = app_state.hotkey_down.lock().unwrap();
+            *down = false;
+        }
+    });
+}
+
+// 录音快捷键松开：重置按下态，按住说话模式下发送停止
+fn on_recording_hotkey_released(app: &tauri::AppHandle) {
+    let app_state = app.state::<AppState>();
+    {
+        let mut down = app_state.hotkey_down.lock().unwrap();
+        *down = false;
+    }
+    if is_push_to_talk(&app_state) {
+        let handle_for_task = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(err) = handle_push_to_talk_release(handle_for_task).await {
+                warn!("处理快捷键松开失败: {}", err);
+            }
+        });
+    }
+}
+
+// Tauri命令：模拟按下（released 为 true 时模拟松开）当前录音快捷键，走与真实按键相同的处理路径；
+// 按住说话模式下需再调用一次 released=true 结束录音
+#[tauri::command]
+fn trigger_hotkey_test(app: tauri::AppHandle, released: Option<bool>) -> Result<(), String> {
+    let hotkey = app.state::<AppState>().recording_hotkey.lock().unwrap().clone();
+    if released.unwrap_or(false) {
+        info!("[tauri] 模拟松开录音快捷键 {}", hotkey);
+        on_recording_hotkey_released(&app);
+    } else {
+        info!("[tauri] 模拟按下录音快捷键 {}", hotkey);
+        on_recording_hotkey_pressed(&app, hotkey);
+    }
+    Ok(())
+}
+
+// -----------------------------
+// 双击修饰键快捷键：global-shortcut 无法注册单独的修饰键，改为轮询按键状态。
+// 只有两次“干净”的单击（按下期间未按其他键）才触发，单次按下或 Ctrl+C 等组合键不受影响
+// -----------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DoubleTapModifier {
+    Ctrl,
+    Shift,
+    Alt,
+    Meta,
+}
+
+impl DoubleTapModifier {
+    fn parse(hotkey: &str) -> Option<Self> {
+        match hotkey.trim().to_ascii_uppercase().as_str() {
+            "CTRL" | "CONTROL" => Some(DoubleTapModifier::Ctrl),
+            "SHIFT" => Some(DoubleTapModifier::Shift),
+            "ALT" | "OPTION" => Some(DoubleTapModifier::Alt),
+            "META" | "COMMAND" | "SUPER" => Some(DoubleTapModifier::Meta),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            DoubleTapModifier::Ctrl => "Ctrl",
+            DoubleTapModifier::Shift => "Shift",
+            DoubleTapModifier::Alt => "Alt",
+            DoubleTapModifier::Meta => "Meta",
+        }
+    }
+}
+
+// 目前仅 Windows 可在不拦截按键的前提下读取修饰键状态
+const DOUBLE_TAP_SUPPORTED: bool = cfg!(windows);
+
+#[cfg(windows)]
+fn modifier_virtual_keys(modifier: DoubleTapModifier) -> &'static [u16] {
+    use windows::Win32::UI::Input::KeyboardAndMouse::{
+        VK_CONTROL, VK_LCONTROL, VK_LMENU, VK_LSHIFT, VK_LWIN, VK_MENU, VK_RCONTROL, VK_RMENU, VK_RSHIFT, VK_RWIN,
+        VK_SHIFT,
+    };
+    const CTRL: [u16; 3] = [VK_CONTROL.0, VK_LCONTROL.0, VK_RCONTROL.0];
+    const SHIFT: [u16; 3] = [VK_SHIFT.0, VK_LSHIFT.0, VK_RSHIFT.0];
+    const ALT: [u16; 3] = [VK_MENU.0, VK_LMENU.0, VK_RMENU.0];
+    const META: [u16; 2] = [VK_LWIN.0, VK_RWIN.0];
+    match modifier {
+        DoubleTapModifier::Ctrl => &CTRL,
+        DoubleTapModifier::Shift => &SHIFT,
+        DoubleTapModifier::Alt => &ALT,
+        DoubleTapModifier::Meta => &META,
+    }
+}
+
+#[cfg(windows)]
+fn virtual_key_down(vk: u16) -> bool {
+    use windows::Win32::UI::Input::KeyboardAndMouse::GetAsyncKeyState;
+    unsafe { (GetAsyncKeyState(vk as i32) as u16 & 0x8000) != 0 }
+}
+
+#[cfg(windows)]
+fn modifier_is_down(modifier: DoubleTapModifier) -> bool {
+    modifier_virtual_keys(modifier).iter().any(|vk| virtual_key_down(*vk))
+}
+
+// 除该修饰键外是否有其他按键（含鼠标按键）处于按下状态
+#[cfg(windows)]
+fn other_key_is_down(modifier: DoubleTapModifier) -> bool {
+    let own = modifier_virtual_keys(modifier);
+    (0x01u16..=0xFE).filter(|vk| !own.contains(vk)).any(virtual_key_down)
+}
+
+#[cfg(not(windows))]
+fn modifier_is_down(_modifier: DoubleTapModifier) -> bool {
+    false
+}
+
+#[cfg(not(windows))]
+fn other_key_is_down(_modifier: DoubleTapModifier) -> bool {
+    false
+}
+
+// 将虚拟键码转换为 global-shortcut 可解析的按键名；修饰键与鼠标按键返回 None
+#[cfg(windows)]
+fn virtual_key_name(vk: u16) -> Option<String> {
+    let name = match vk {
+        0x41..=0x5A => return Some(char::from(vk as u8).to_string()),
+        0x30..=0x39 => return Some(format!("Digit{}", vk - 0x30)),
+        0x60..=0x69 => return Some(format!("Numpad{}", vk - 0x60)),
+        0x70..=0x87 => return Some(format!("F{}", vk - 0x6F)),
+        0x08 => "Backspace",
+        0x09 => "Tab",
+        0x0D => "Enter",
+        0x13 => "Pause",
+        0x14 => "CapsLock",
+        0x1B => "Escape",
+        0x20 => "Space",
+        0x21 => "PageUp",
+        0x22 => "PageDown",
+        0x23 => "End",
+        0x24 => "Home",
+        0x25 => "ArrowLeft",
+        0x26 => "ArrowUp",
+        0x27 => "ArrowRight",
+        0x28 => "ArrowDown",
+        0x2C => "PrintScreen",
+        0x2D => "Insert",
+        0x2E => "Delete",
+        0x91 => "ScrollLock",
+        0xBA => "Semicolon",
+        0xBB => "Equal",
+        0xBC => "Comma",
+        0xBD => "Minus",
+        0xBE => "Period",
+        0xBF => "Slash",
+        0xC0 => "Backquote",
+        0xDB => "BracketLeft",
+        0xDC => "Backslash",
+        0xDD => "BracketRight",
+        0xDE => "Quote",
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+// 轮询等待下一次“修饰键 + 按键”组合，返回形如 "Ctrl+Shift+K" 的字符串；
+// 单独按 Esc 视为放弃，超时返回 None
+#[cfg(windows)]
+fn poll_next_key_combination(timeout: Duration) -> Result<Option<String>, String> {
+    let deadline = Instant::now() + timeout;
+    // 先等待触发捕获时仍按着的按键松开，避免把它们当作结果
+    let mut held: Vec<u16> = (0x08u16..=0xFE).filter(|vk| virtual_key_down(*vk)).collect();
+    while Instant::now() < deadline {
+        held.retain(|vk| virtual_key_down(*vk));
+        if let Some(vk) = (0x08u16..=0xFE)
+            .filter(|vk| !held.contains(vk))
+            .find(|vk| virtual_key_name(*vk).is_some() && virtual_key_down(*vk))
+        {
+            let key = virtual_key_name(vk).unwrap_or_default();
+            let modifiers: Vec<&str> = [
+                (DoubleTapModifier::Ctrl, "Ctrl"),
+                (DoubleTapModifier::Shift, "Shift"),
+                (DoubleTapModifier::Alt, "Alt"),
+                (DoubleTapModifier::Meta, "Super"),
+            ]
+            .into_iter()
+            .filter(|(m, _)| modifier_is_down(*m))
+            .map(|(_, name)| name)
+            .collect();
+            if modifiers.is_empty() && key == "Escape" {
+                return Err("已取消捕获快捷键".to_string());
+            }
+            let mut parts = modifiers;
+            parts.push(&key);
+            return Ok(Some(parts.join("+")));
+        }
+        std::thread::sleep(DOUBLE_TAP_POLL_INTERVAL);
+    }
+    Ok(None)
+}
+
+#[cfg(not(windows))]
+fn poll_next_key_combination(_timeout: Duration) -> Result<Option<String>, String> {
+    Err("当前平台不支持捕获快捷键，请手动输入".to_string())
+}
+
+// 停止当前的双击检测线程（线程在下一次轮询时退出）
+fn stop_double_tap_watcher(app: &tauri::AppHandle) {
+    app.state::<AppState>().double_tap_generation.fetch_add(1, Ordering::SeqCst);
+}
+
+// 启动双击检测线程：第二次按下时走与普通快捷键相同的按下路径，松开时走松开路径
+fn start_double_tap_watcher(app: &tauri::AppHandle, modifier: DoubleTapModifier) {
+    let generation = app.state::<AppState>().double_tap_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let app = app.clone();
+    std::thread::spawn(move || {
+        let label = modifier.name().to_string();
+        // 当前按下：(按下时间, 是否干净, 是否已触发)
+        let mut pressed: Option<(Instant, bool, bool)> = None;
+        loop {
+            let state = app.state::<AppState>();
+            if state.double_tap_generation.load(Ordering::SeqCst) != generation {
+                break;
+            }
+            let down = modifier_is_down(modifier);
+            match pressed {
+                None if down => {
+                    let now = Instant::now();
+                    let clean = !other_key_is_down(modifier);
+                    let window = Duration::from_millis(*state.double_tap_window_ms.lock().unwrap());
+                    let previous = state.modifier_last_tap.lock().unwrap().remove(&label);
+                    let triggered = clean && previous.map(|at| now.duration_since(at) <= window).unwrap_or(false);
+                    if triggered {
+                        info!("检测到双击 {}", label);
+                        on_recording_hotkey_pressed(&app, label.clone());
+                    }
+                    pressed = Some((now, clean, triggered));
+                }
+                Some((at, true, triggered)) if down => {
+                    if other_key_is_down(modifier) {
+                        pressed = Some((at, false, triggered));
+                    }
+                }
+                Some((at, clean, triggered)) if !down => {
+                    if triggered {
+                        on_recording_hotkey_released(&app);
+                    } else if clean {
+                        state.modifier_last_tap.lock().unwrap().insert(label.clone(), at);
+                    }
+                    pressed = None;
+                }
+                _ => {}
+            }
+            std::thread::sleep(DOUBLE_TAP_POLL_INTERVAL);
+        }
+        debug!("双击 {} 检测线程已退出", label);
+    });
+}
+
+fn register_recording_hotkey(app: &tauri::AppHandle, hotkey: &str) -> Result<(), String> {
+    // 先注销所有之前注册的快捷键
+    let gs = app.global_shortcut();
+    gs.unregister_all()
+        .map_err(|e| tf("hotkey_unregister_failed", &[&e]))?;
+    stop_double_tap_watcher(app);
+
+    let hotkey_string = if let Some(modifier) = DoubleTapModifier::parse(hotkey) {
+        if !DOUBLE_TAP_SUPPORTED {
+            return Err(t("hotkey_double_tap_unsupported"));
+        }
+        start_double_tap_watcher(app, modifier);
+        modifier.name().to_string()
+    } else {
+        let shortcut = parse_hotkey(hotkey)?;
+        let hotkey_string = format!("{}", shortcut);
+        let handler_hotkey = hotkey_string.clone();
+
+        gs.on_shortcut(shortcut, move |app_handle, _shortcut, event| {
+            match event.state {
+                ShortcutState::Pressed => on_recording_hotkey_pressed(app_handle, handler_hotkey.clone()),
+                ShortcutState::Released => on_recording_hotkey_released(app_handle),
+            }
+        })
+        .map_err(|e| tf("hotkey_register_failed", &[&hotkey, &e]))?;
+        hotkey_string
+    };
+
+    register_auxiliary_hotkeys(app)?;
+
+    // 更新 AppState 中的记录
+    let app_state = app.state::<AppState>();
+    {
+        let mut guard = app_state.recording_hotkey.lock().unwrap();
+        *guard = hotkey_string.clone();
+    }
+
+    Ok(())
+}
+
+// 快捷键结束录音时选择指令：距开始不足最短录音时长则取消（丢弃音频），否则停止并转写
+fn stop_or_cancel_command(state: &AppState) -> &'static str {
+    let started = state.recording_started_at.lock().unwrap().take();
+    let min_ms = *state.min_recording_ms.lock().unwrap();
+    match started {
+        Some(at) if min_ms > 0 && at.elapsed() < Duration::from_millis(min_ms) => {
+            info!("[tauri] 录音时长 {}ms 短于 {}ms，视为误触并取消", at.elapsed().as_millis(), min_ms);
+            "cancel"
+        }
+        _ => "stop",
+    }
+}
+
+fn is_push_to_talk(state: &AppState) -> bool {
+    state.hotkey_mode.lock().map(|m| m.as_str() == HOTKEY_MODE_PUSH_TO_TALK).unwrap_or(false)
+}
+
+// 按住说话：按下边沿发送 start，并启动最长按住超时
+async fn handle_push_to_talk_press(app: tauri::AppHandle, shortcut: String) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+
+    {
+        let mut down = app_state.hotkey_down.lock().unwrap();
+        if *down {
+            return Ok(());
+        }
+        *down = true;
+    }
+
+    let currently = { *app_state.is_recording.lock().unwrap() };
+    if currently {
+        // 已在录音（例如由界面按钮启动），按下不重复发送 start
+        return Ok(());
+    }
+    if ensure_recording_start_allowed(&app).is_err() {
+        return Ok(());
+    }
+
+    info!("全局快捷键 {} 被按下（按住说话）", shortcut);
+    // 先登记本次按下，再等待 start 发送完成，避免快速点按时松开事件被丢弃
+    let session = {
+        let mut active = app_state.ptt_active.lock().unwrap();
+        *active = true;
+        *app_state.ptt_starting.lock().unwrap() = true;
+        let mut seq = app_state.ptt_session.lock().unwrap();
+        *seq = seq.wrapping_add(1);
+        *seq
+    };
+
+    let target = capture_output_target(&app_state);
+    let started = match send_bridge_payload(&app, target).await {
+        Ok(()) => send_bridge_command(&app, "start").await,
+        Err(err) => Err(err),
+    };
+    let released = {
+        let mut active = app_state.ptt_active.lock().unwrap();
+        *app_state.ptt_starting.lock().unwrap() = false;
+        if started.is_err() {
+            *active = false;
+        }
+        !*active
+    };
+    started?;
+    *app_state.recording_started_at.lock().unwrap() = Some(Instant::now());
+
+    if released {
+        // start 发送期间已松开：补发 stop
+        let cmd = stop_or_cancel_command(&app_state);
+        return send_bridge_command(&app, cmd).await;
+    }
+
+    if let Some(window) = app.get_webview_window("widget") {
+        let _ = window.emit("global-shortcut-pressed", shortcut.clone());
+    }
+
+    // 丢失松开事件（如焦点切换）时，超时后自动停止
+    let handle_for_timeout = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(PUSH_TO_TALK_MAX_HOLD).await;
+        let expired = {
+            let state = handle_for_timeout.state::<AppState>();
+            let same_session = *state.ptt_session.lock().unwrap() == session;
+            let mut active = state.ptt_active.lock().unwrap();
+            if same_session && *active {
+                *active = false;
+                *state.hotkey_down.lock().unwrap() = false;
+                true
+            } else {
+                false
+            }
+        };
+        if expired {
+            info!("[tauri] 按住说话超过 {} 秒未松开，自动停止录音", PUSH_TO_TALK_MAX_HOLD.as_secs());
+            if let Err(err) = send_bridge_command(&handle_for_timeout, "stop").await {
+                warn!("[tauri] 按住说话超时停止失败: {}", err);
+            }
+        }
+    });
+
+    Ok(())
+}
+
+// 按住说话：松开边沿发送 stop（不做去抖，避免短句被丢弃）
+async fn handle_push_to_talk_release(app: tauri::AppHandle) -> Result<(), String> {
+    let was_active = {
+        let app_state = app.state::<AppState>();
+        let mut active = app_state.ptt_active.lock().unwrap();
+        let starting = *app_state.ptt_starting.lock().unwrap();
+        std::mem::replace(&mut *active, false) && !starting
+    };
+    if !was_active {
+        return Ok(());
+    }
+    let cmd = stop_or_cancel_command(&app.state::<AppState>());
+    send_bridge_command(&app, cmd).await
+}
+
+async fn handle_recording_hotkey(app: tauri::AppHandle, shortcut: String) -> Result<(), String> {
+    let app_state = app.state::<AppState>();
+
+    if is_push_to_talk(&app_state) {
+        return handle_push_to_talk_press(app.clone(), shortcut).await;
+    }
+
+    // 边沿检测：只在从未按下 -> 按下 的边沿触发
+    {
+        let mut down = app_state.hotkey_down.lock().unwrap();
+        if *down {
+            return Ok(());
+        }
+        *down = true;
+    }
+
+    // 去抖：间隔内忽略重复触发（默认 200ms，可配置）
+    let debounce_ms = { *app_state.hotkey_debounce_ms.lock().unwrap() };
+    let debounce = {
+        let mut last = app_state.last_toggle.lock().unwrap();
+        let now = Instant::now();
+        let within = if let Some(prev) = *last {
+            now.duration_since(prev) < Duration::from_millis(debounce_ms)
+        } else {
+            false
+        };
+        *last = Some(now);
+        within
+    };
+
+    if debounce {
+        let mut down = app_state.hotkey_down.lock().unwrap();
+        *down = false;
+        return Ok(());
+    }
+
+    info!("全局快捷键 {} 被按下", shortcut);
+
+    let currently = { *app_state.is_recording.lock().unwrap() };
+    if !currently && ensure_recording_start_allowed(&app).is_err() {
+        let mut down = app_state.hotkey_down.lock().unwrap();
+        *down = false;
+        return Ok(());
+    }
+    let cmd_name = if currently { stop_or_cancel_command(&app_state) } else { "start" }.to_string();
+    debug!("[tauri] 快捷键路径：当前 is_recording={}，准备发送 {} 指令", currently, cmd_name);
+
+    let bridge_state = app.state::<BridgeState>();
+    {
+        let mut guard = bridge_state.stdin.lock().await;
+        if let Some(stdin) = guard.as_mut() {
+            let mut payload = serde_json::json!({"cmd": cmd_name}).to_string() + "\n";
+            if !currently {
+                payload = capture_output_target(&app_state).to_string() + "\n" + &payload;
+                *app_state.recording_started_at.lock().unwrap() = Some(Instant::now());
+            }
+            stdin
+                .write_all(payload.as_bytes())
+                .await
+                .map_err(|e| format!("[tauri] 快捷键路径写入 {} 失败: {}", cmd_name, e))?;
+            stdin
+                .flush()
+                .await
+                .map_err(|e| format!("[tauri] 快捷键路径刷新 {} 失败: {}", cmd_name, e))?;
+            debug!("[tauri] 快捷键路径已发送 {} 指令", cmd_name);
+        } else {
+            let mut down = app_state.hotkey_down.lock().unwrap();
+            *down = false;
+            return Err(format!("[tauri] 快捷键路径发送 {} 失败：stdin 不可用", cmd_name));
+        }
+    }
+
+    if let Some(window) = app.get_webview_window("widget") {
+        let _ = window.emit("global-shortcut-pressed", shortcut.clone());
+    }
+
+    {
+        let mut down = app_state.hotkey_down.lock().unwrap();
+        *down = false;
+    }
+
+    Ok(())
+}
+
+// 心跳看门狗：随每个子进程启动，子进程退出时由守护循环 abort
+// 检测到卡死时通过 hung 通知守护循环结束子进程
+async fn run_bridge_watchdog(
+    stdin_arc: Arc<tokio::sync::Mutex<Option<ChildStdin>>>,
+    last_pong: Arc<Mutex<Option<Instant>>>,
+    hung: Arc<tokio::sync::Notify>,
+) {
+    loop {
+        tokio::time::sleep(BRIDGE_PING_INTERVAL).await;
+        // 桥接尚未就绪（模型加载中）时不检测
+        if last_pong.lock().map(|g| g.is_none()).unwrap_or(true) {
+            continue;
+        }
+
+        let sent_at = Instant::now();
+        {
+            let mut guard = stdin_arc.lock().await;
+            let Some(stdin) = guard.as_mut() else { break };
+            let payload = serde_json::json!({"cmd": "ping"}).to_string() + "\n";
+            if let Err(err) = stdin.write_all(payload.as_bytes()).await {
+                warn!("[tauri] 心跳写入 ping 失败: {}", err);
+                break;
+            }
+            if let Err(err) = stdin.flush().await {
+                warn!("[tauri] 心跳刷新 ping 失败: {}", err);
+                break;
+            }
+        }
+
+        tokio::time::sleep(BRIDGE_PONG_TIMEOUT).await;
+        let responded = last_pong
+            .lock()
+            .map(|g| g.map(|t| t >= sent_at).unwrap_or(false))
+            .unwrap_or(true);
+        if !responded {
+            warn!("[tauri] {} 秒内未收到 pong，判定桥接进程卡死", BRIDGE_PONG_TIMEOUT.as_secs());
+            hung.notify_one();
+            break;
+        }
+    }
+}
+
+// 第 attempts 次启动失败/退出后的重启退避秒数（每次 +2 秒，封顶于配置的上限）
+fn bridge_restart_delay_secs(attempts: u32) -> u64 {
+    let backoff_cap = std::cmp::max(1, load_ui_settings().bridge_backoff_cap_secs);
+    std::cmp::min(backoff_cap, 2 * (attempts as u64))
+}
+
+// 手动重启桥接进程：发送 shutdown 后由守护循环负责拉起
+#[tauri::command]
+async fn restart_bridge(app: tauri::AppHandle, bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
+    if !bridge.should_restart.load(Ordering::SeqCst) {
+        return Err("桥接守护已停止，无法重启".to_string());
+    }
+    bridge.manual_restart.store(true, Ordering::SeqCst);
+    let _ = app.emit("bridge-restarting", ());
+
+    let stdin_arc = bridge.stdin.clone();
+    let mut guard = stdin_arc.lock().await;
+    if let Some(stdin) = guard.as_mut() {
+        info!("[tauri] 手动重启：发送 shutdown 指令给桥接进程");
+        let payload = serde_json::json!({"cmd": "shutdown"}).to_string() + "\n";
+        if let Err(err) = stdin.write_all(payload.as_bytes()).await {
+            warn!("[tauri] 手动重启写入 shutdown 失败: {}", err);
+        } else if let Err(err) = stdin.flush().await {
+            warn!("[tauri] 手动重启刷新 shutdown 失败: {}", err);
+        }
+        // 关闭 stdin，确保桥接进程读到 EOF 后退出
+        *guard = None;
+        drop(guard);
+        tokio::time::sleep(Duration::from_millis(200)).await;
+    } else {
+        // 桥接进程未运行（可能处于退避等待），直接唤醒守护循环
+        warn!("[tauri] 手动重启：stdin 不可用，唤醒守护循环");
+        drop(guard);
+        bridge.restart_notify.notify_one();
+    }
+    Ok(())
+}
+
+// 查询桥接进程状态：running 以 stdin 句柄是否可用为准
+#[tauri::command]
+async fn get_bridge_status(bridge: tauri::State<'_, BridgeState>) -> Result<BridgeStatus, String> {
+    let running = { bridge.stdin.lock().await.is_some() };
+    let info = bridge
+        .process_info
+        .lock()
+        .map_err(|e| format!("获取桥接进程状态失败: {}", e))?
+        .clone();
+    Ok(BridgeStatus {
+        running,
+        pid: info.pid,
+        restart_attempts: info.restart_attempts,
+        last_exit_code: info.last_exit_code,
+        gave_up: info.gave_up,
+        enabled: !bridge.disabled.load(Ordering::SeqCst),
+    })
+}
+
+// 最近一次桥接错误（未发生或已清除时为 None）
+#[tauri::command]
+fn get_last_bridge_error(bridge: tauri::State<'_, BridgeState>) -> Result<Option<BridgeErrorRecord>, String> {
+    let guard = bridge.last_error.lock().map_err(|e| format!("获取桥接错误失败: {}", e))?;
+    Ok(guard.clone())
+}
+
+// 用户关闭“最近问题”提示后清除
+#[tauri::command]
+fn clear_bridge_error(bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
+    let mut guard = bridge.last_error.lock().map_err(|e| format!("清除桥接错误失败: {}", e))?;
+    *guard = None;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct VersionInfo {
+    app: String,
+    // 桥接未运行或未应答时为 None；旧版桥接不支持查询时为 "unknown"
+    bridge: Option<String>,
+}
+
+// Tauri命令：获取应用与桥接版本（用于“关于”面板与问题反馈）
+#[tauri::command]
+async fn get_versions(app: tauri::AppHandle) -> Result<VersionInfo, String> {
+    let app_version = app.package_info().version.to_string();
+    let (cached, ready) = {
+        let bridge = app.state::<BridgeState>();
+        let cached = bridge.version.lock().unwrap().clone();
+        let ready = bridge.last_pong.lock().map(|g| g.is_some()).unwrap_or(false);
+        (cached, ready)
+    };
+    if cached.is_some() || !ready {
+        return Ok(VersionInfo { app: app_version, bridge: cached });
+    }
+
+    let bridge_version = match bridge_request(&app, serde_json::json!({"cmd": "version"})).await {
+        Ok(reply) => match reply.get("event").and_then(|v| v.as_str()) {
+            Some("version") => reply.get("version").and_then(|v| v.as_str()).map(|v| v.to_string()),
+            Some("invalid_command") => Some("unknown".to_string()),
+            _ => None,
+        },
+        Err(e) => {
+            warn!("[tauri] 查询桥接版本失败: {}", e);
+            None
+        }
+    };
+    if let Some(version) = bridge_version.as_ref() {
+        *app.state::<BridgeState>().version.lock().unwrap() = Some(version.clone());
+    }
+    Ok(VersionInfo { app: app_version, bridge: bridge_version })
+}
+
+// 停用桥接进程：停止守护重启并发送 shutdown，释放麦克风等资源；应用保持运行
+#[tauri::command]
+async fn stop_bridge(bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
+    bridge.disabled.store(true, Ordering::SeqCst);
+    bridge.should_restart.store(false, Ordering::SeqCst);
+
+    let mut guard = bridge.stdin.lock().await;
+    if let Some(mut stdin) = guard.take() {
+        info!("[tauri] 用户停用桥接进程，发送 shutdown 指令");
+        let payload = serde_json::json!({"cmd": "shutdown"}).to_string() + "\n";
+        if let Err(err) = stdin.write_all(payload.as_bytes()).await {
+            warn!("[tauri] 停用桥接写入 shutdown 失败: {}", err);
+        } else if let Err(err) = stdin.flush().await {
+            warn!("[tauri] 停用桥接刷新 shutdown 失败: {}", err);
+        }
+    } else {
+        // 桥接未运行（退避等待或已放弃重试），唤醒守护循环使其进入停用等待
+        info!("[tauri] 用户停用桥接进程：当前未运行");
+        bridge.restart_notify.notify_one();
+    }
+    Ok(())
+}
+
+// 重新启用桥接进程：恢复守护重启并立即拉起；未停用时返回 false
+#[tauri::command]
+fn start_bridge(bridge: tauri::State<'_, BridgeState>) -> Result<bool, String> {
+    if !bridge.disabled.swap(false, Ordering::SeqCst) {
+        return Ok(false);
+    }
+    info!("[tauri] 用户重新启用桥接进程");
+    bridge.should_restart.store(true, Ordering::SeqCst);
+    bridge.restart_notify.notify_one();
+    Ok(true)
+}
+
+fn ensure_bridge_enabled(bridge: &BridgeState) -> Result<(), String> {
+    if bridge.disabled.load(Ordering::SeqCst) {
+        return Err("桥接进程已停用，请先在设置中启动桥接进程".to_string());
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize)]
+struct BridgeRestartPolicy {
+    max_restart_attempts: Option<u32>,
+    backoff_cap_secs: u64,
+}
+
+#[tauri::command]
+fn get_bridge_restart_policy() -> BridgeRestartPolicy {
+    let settings = load_ui_settings();
+    BridgeRestartPolicy {
+        max_restart_attempts: settings.bridge_max_restart_attempts,
+        backoff_cap_secs: settings.bridge_backoff_cap_secs,
+    }
+}
+
+#[tauri::command]
+fn set_bridge_restart_policy(payload: BridgeRestartPolicy) -> Result<bool, String> {
+    if payload.max_restart_attempts == Some(0) {
+        return Err("最大重启次数必须大于 0（不限制请传 null）".to_string());
+    }
+    if payload.backoff_cap_secs == 0 || payload.backoff_cap_secs > 3600 {
+        return Err(format!("退避上限超出范围：{} 秒（允许 1-3600 秒）", payload.backoff_cap_secs));
+    }
+    let mut settings = load_ui_settings();
+    settings.bridge_max_restart_attempts = payload.max_restart_attempts;
+    settings.bridge_backoff_cap_secs = payload.backoff_cap_secs;
+    save_ui_settings(&settings)?;
+    Ok(true)
+}
+
+// 达到最大重启次数后，重置计数并恢复守护循环
+#[tauri::command]
+fn resume_bridge_restarts(bridge: tauri::State<'_, BridgeState>) -> Result<bool, String> {
+    let gave_up = {
+        let mut info = bridge.process_info.lock().map_err(|e| format!("获取桥接进程状态失败: {}", e))?;
+        let gave_up = info.gave_up;
+        info.gave_up = false;
+        info.restart_attempts = 0;
+        gave_up
+    };
+    if gave_up {
+        info!("[tauri] 用户恢复桥接进程重启");
+        bridge.restart_notify.notify_one();
+    }
+    Ok(gave_up)
+}
+
+// 自启动：获取当前状态
+#[tauri::command]
+fn get_autostart_enabled(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(app.autolaunch().is_enabled().map_err(|e| e.to_string())?)
+}
+
+// 开机自启注册时附带的参数：存在时启动后只保留托盘
+const START_MINIMIZED_ARG: &str = "--minimized";
+// 开机自启延迟（秒）：默认值与上限
+const DEFAULT_AUTOSTART_DELAY_SECS: u64 = 5;
+const MAX_AUTOSTART_DELAY_SECS: u64 = 120;
+
+fn launched_minimized() -> bool {
+    std::env::args().any(|arg| arg == START_MINIMIZED_ARG)
+}
+
+// Tauri命令：获取“启动时最小化到托盘”偏好
+#[tauri::command]
+fn get_start_minimized() -> bool {
+    load_ui_settings().start_minimized
+}
+
+// Tauri命令：设置“启动时最小化到托盘”偏好
+#[tauri::command]
+fn set_start_minimized(enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.start_minimized = enabled;
+    save_ui_settings(&settings)
+}
+
+// Tauri命令：获取开机自启延迟（秒）
+#[tauri::command]
+fn get_autostart_delay_secs() -> u64 {
+    load_ui_settings().autostart_delay_secs.min(MAX_AUTOSTART_DELAY_SECS)
+}
+
+// Tauri命令：设置开机自启延迟（秒），超出上限时报错
+#[tauri::command]
+fn set_autostart_delay_secs(secs: u64) -> Result<(), String> {
+    if secs > MAX_AUTOSTART_DELAY_SECS {
+        return Err(format!("自启延迟不能超过 {} 秒", MAX_AUTOSTART_DELAY_SECS));
+    }
+    let mut settings = load_ui_settings();
+    settings.autostart_delay_secs = secs;
+    save_ui_settings(&settings)
+}
+
+// 自启动：设置状态
+#[tauri::command]
+fn set_autostart_enabled(app: tauri::AppHandle, enabled: bool) -> Result<bool, String> {
+    let api = app.autolaunch();
+    if enabled {
+        api.enable().map_err(|e| e.to_string())?;
+    } else {
+        api.disable().map_err(|e| e.to_string())?;
+    }
+    Ok(true)
+}
+
+// -----------------------------
+// 配置：替换词典 读/写（postprocess.json）
+// -----------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct PostprocessConfig {
+    #[serde(default = "default_case_insensitive")]
+    case_insensitive: bool,
+    #[serde(default)]
+    replace_map: IndexMap<String, ReplaceEntry>,
+    // 词典规模限制（旧文件缺少时使用默认值）
+    #[serde(default)]
+    limits: PostprocessLimits,
+}
+
+fn default_case_insensitive() -> bool { true }
+
+// 可配置的词典限制；读取时通过 effective() 限制在硬上限内，避免匹配性能退化
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+struct PostprocessLimits {
+    #[serde(default = "default_max_replace_entries")]
+    max_entries: usize,
+    // 字面替换键/值的最大字符数（正则条目另有 REGEX_ENTRY_MAX_CHARS 下限）
+    #[serde(default = "default_max_term_length")]
+    max_term_length: usize,
+}
+
+fn default_max_replace_entries() -> usize { MAX_REPLACE_ENTRIES }
+fn default_max_term_length() -> usize { LITERAL_ENTRY_MAX_CHARS }
+
+impl Default for PostprocessLimits {
+    fn default() -> Self {
+        PostprocessLimits { max_entries: default_max_replace_entries(), max_term_length: default_max_term_length() }
+    }
+}
+
+impl PostprocessLimits {
+    fn effective(self) -> Self {
+        PostprocessLimits {
+            max_entries: self.max_entries.clamp(1, HARD_MAX_REPLACE_ENTRIES),
+            max_term_length: self.max_term_length.clamp(1, HARD_MAX_TERM_LENGTH),
+        }
+    }
+
+    fn max_chars(&self, kind: ReplaceKind) -> usize {
+        match kind {
+            ReplaceKind::Literal => self.max_term_length,
+            ReplaceKind::Regex => self.max_term_length.max(REGEX_ENTRY_MAX_CHARS),
+        }
+    }
+}
+
+// 替换条目类型：literal（按字面匹配）/ regex（键为正则表达式）
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+enum ReplaceKind {
+    #[default]
+    Literal,
+    Regex,
+}
+
+// 替换条目：启用的字面替换在磁盘上仍保存为字符串（兼容旧格式），
+// 正则或停用的条目保存为 {"value": ..., "type": "regex", "enabled": false}
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(from = "ReplaceEntryRepr", into = "ReplaceEntryRepr")]
+struct ReplaceEntry {
+    value: String,
+    kind: ReplaceKind,
+    // 停用的条目保留在词典中，但 Python 侧匹配时跳过
+    enabled: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ReplaceEntryRepr {
+    Plain(String),
+    Rule {
+        value: String,
+        #[serde(default, rename = "type")]
+        kind: ReplaceKind,
+        #[serde(default = "default_entry_enabled")]
+        enabled: bool,
+    },
+}
+
+fn default_entry_enabled() -> bool { true }
+
+impl From<ReplaceEntryRepr> for ReplaceEntry {
+    fn from(repr: ReplaceEntryRepr) -> Self {
+        match repr {
+            ReplaceEntryRepr::Plain(value) => ReplaceEntry { value, kind: ReplaceKind::Literal, enabled: true },
+            ReplaceEntryRepr::Rule { value, kind, enabled } => ReplaceEntry { value, kind, enabled },
+        }
+    }
+}
+
+impl From<ReplaceEntry> for ReplaceEntryRepr {
+    fn from(entry: ReplaceEntry) -> Self {
+        if entry.kind == ReplaceKind::Literal && entry.enabled {
+            ReplaceEntryRepr::Plain(entry.value)
+        } else {
+            ReplaceEntryRepr::Rule { value: entry.value, kind: entry.kind, enabled: entry.enabled }
+        }
+    }
+}
+
+impl ReplaceEntry {
+    // 宽松解析：字符串或 {"value", "type", "enabled"} 对象，其余 JSON 值统一字符串化为字面替换
+    fn from_value(v: Value) -> Self {
+        match v {
+            Value::String(s) => ReplaceEntry { value: s, kind: ReplaceKind::Literal, enabled: true },
+            Value::Object(obj) => {
+                let value = match obj.get("value") {
+                    Some(Value::String(s)) => s.clone(),
+                    Some(other) => other.to_string(),
+                    None => String::new(),
+                };
+                let kind = match obj.get("type").and_then(|t| t.as_str()) {
+                    Some(t) if t.eq_ignore_ascii_case("regex") => ReplaceKind::Regex,
+                    _ => ReplaceKind::Literal,
+                };
+                let enabled = obj.get("enabled").and_then(|e| e.as_bool()).unwrap_or(true);
+                ReplaceEntry { value, kind, enabled }
+            }
+            other => ReplaceEntry { value: other.to_string(), kind: ReplaceKind::Literal, enabled: true },
+        }
+    }
+}
+
+// 条目长度上限默认值：字面替换 16 字符；正则表达式较长，放宽到 128 字符
+const LITERAL_ENTRY_MAX_CHARS: usize = 16;
+const REGEX_ENTRY_MAX_CHARS: usize = 128;
+// 替换词典最多条目数（默认值）
+const MAX_REPLACE_ENTRIES: usize = 200;
+// 可配置限制的硬上限
+const HARD_MAX_REPLACE_ENTRIES: usize = 5000;
+const HARD_MAX_TERM_LENGTH: usize = 256;
+
+#[derive(Deserialize, Debug)]
+struct SavePostprocessPayload {
+    #[serde(default)]
+    case_insensitive: Option<bool>,
+    // 允许字符串或 {"value", "type", "enabled"} 对象；其他 JSON 值统一字符串化
+    #[serde(default)]
+    replace_map: IndexMap<String, Value>,
+    // 未提供时沿用当前配置中的限制
+    #[serde(default)]
+    limits: Option<PostprocessLimits>,
+    // replace：整体覆盖（默认）；merge：在现有词典基础上追加/覆盖
+    #[serde(default)]
+    mode: SavePostprocessMode,
+}
+
+#[derive(Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum SavePostprocessMode {
+    #[default]
+    Replace,
+    Merge,
+}
+
+// 配置文件的根目录：与桥接使用同一个项目根目录；未找到（如安装包）时使用当前工作目录
+fn find_project_root_for_config() -> PathBuf {
+    find_project_root().unwrap_or_else(|| std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")))
+}
+
+// 配置目录重定位：环境变量 SK_CONFIG_DIR 优先，其次为用户目录下的引导文件（set_config_directory 写入）。
+// 启用后界面设置、统计、历史与替换词典全部位于该目录，避免配置分散在两处
+const CONFIG_DIR_ENV: &str = "SK_CONFIG_DIR";
+
+// 引导文件：Windows 为 %APPDATA%\speak-keyboard\config_dir.txt，其余平台为 $XDG_CONFIG_HOME（或 ~/.config）下同名路径
+fn config_dir_bootstrap_path() -> Option<PathBuf> {
+    let base = std::env::var_os("APPDATA")
+        .or_else(|| std::env::var_os("XDG_CONFIG_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("speak-keyboard").join("config_dir.txt"))
+}
+
+// 当前生效的配置目录覆盖及其来源（env / file）；未设置时为 None
+fn config_dir_override() -> Option<(PathBuf, &'static str)> {
+    if let Some(dir) = std::env::var_os(CONFIG_DIR_ENV).filter(|v| !v.is_empty()) {
+        return Some((PathBuf::from(dir), "env"));
+    }
+    let content = fs::read_to_string(config_dir_bootstrap_path()?).ok()?;
+    let dir = content.trim();
+    (!dir.is_empty()).then(|| (PathBuf::from(dir), "file"))
+}
+
+// 未重定位时的默认配置目录（项目根目录下 speak-keyboard-tauri/config）
+fn default_tauri_config_dir() -> PathBuf {
+    find_project_root_for_config().join("speak-keyboard-tauri").join("config")
+}
+
+fn resolve_tauri_config_path(file_name: &str) -> PathBuf {
+    match config_dir_override() {
+        Some((dir, _)) => dir.join(file_name),
+        None => default_tauri_config_dir().join(file_name),
+    }
+}
+
+// 在系统文件管理器中打开配置目录（不存在时先创建）
+fn open_config_folder(app: &tauri::AppHandle) -> Result<(), String> {
+    let dir = config_dir_override().map(|(dir, _)| dir).unwrap_or_else(default_tauri_config_dir);
+    if !dir.exists() {
+        fs::create_dir_all(&dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    app.opener()
+        .open_path(dir.to_string_lossy(), None::<&str>)
+        .map_err(|e| format!("打开配置目录失败: {}", e))
+}
+
+fn resolve_postprocess_path() -> PathBuf {
+    match config_dir_override() {
+        Some((dir, _)) => dir.join("postprocess.json"),
+        None => find_project_root_for_config().join("config").join("postprocess.json"),
+    }
+}
+
+#[derive(Serialize)]
+struct ConfigDirectoryInfo {
+    // 界面设置等文件所在目录
+    dir: String,
+    // default / env（SK_CONFIG_DIR）/ file（应用内设置）
+    source: &'static str,
+}
+
+// Tauri命令：获取当前配置目录及其来源
+#[tauri::command]
+fn get_config_directory() -> ConfigDirectoryInfo {
+    match config_dir_override() {
+        Some((dir, source)) => ConfigDirectoryInfo { dir: dir.to_string_lossy().to_string(), source },
+        None => ConfigDirectoryInfo { dir: default_tauri_config_dir().to_string_lossy().to_string(), source: "default" },
+    }
+}
+
+// 将旧配置复制到新目录：目标已存在的同名文件保留不动，返回复制的文件数
+// 日志（app.log.*、bridge.log*）与锁文件只属于当前目录，不随配置迁移
+fn is_migratable_config_file(name: &str) -> bool {
+    !name.starts_with("postprocess.json")
+        && !name.starts_with(LOG_FILE_NAME)
+        && !name.starts_with(BRIDGE_LOG_FILE_NAME)
+        && !name.ends_with(".lock")
+}
+
+fn migrate_config_files(files: &[PathBuf], target: &std::path::Path) -> Result<usize, String> {
+    let mut copied = 0;
+    for source in files.iter().filter(|p| p.is_file()) {
+        let Some(name) = source.file_name() else { continue };
+        let dest = target.join(name);
+        if dest.exists() {
+            continue;
+        }
+        fs::copy(source, &dest).map_err(|e| format!("复制 {} 失败: {}", source.display(), e))?;
+        copied += 1;
+    }
+    Ok(copied)
+}
+
+// Tauri命令：重定位配置目录（传空字符串恢复默认位置）。校验新目录可写，复制现有配置文件后持久化，
+// 并重启桥接使替换词典路径同步生效；原目录中的文件保留不删除。返回复制的文件数
+#[tauri::command]
+async fn set_config_directory(app: tauri::AppHandle, path: String) -> Result<usize, String> {
+    if let Some((_, "env")) = config_dir_override() {
+        return Err(format!("配置目录由环境变量 {} 指定，无法在应用内修改", CONFIG_DIR_ENV));
+    }
+    let bootstrap = config_dir_bootstrap_path().ok_or_else(|| "无法确定用户配置目录，不能保存配置目录设置".to_string())?;
+    let path = path.trim();
+    let target = if path.is_empty() { default_tauri_config_dir() } else { PathBuf::from(path) };
+    if !target.is_absolute() {
+        return Err(format!("配置目录必须是绝对路径: {}", target.display()));
+    }
+    // 恢复默认时替换词典回到项目根目录下的 config
+    let postprocess_target = if path.is_empty() { find_project_root_for_config().join("config") } else { target.clone() };
+    ensure_dir_writable(&target)?;
+    ensure_dir_writable(&postprocess_target)?;
+
+    let copied = {
+        let state = app.state::<AppState>();
+        // 迁移期间阻止统计与历史写入，避免新旧目录内容不一致
+        let _usage_guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+        let _history_guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+
+        let current_dir = resolve_tauri_config_path("");
+        let current_postprocess = resolve_postprocess_path();
+        let files: Vec<PathBuf> = match fs::read_dir(&current_dir) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.file_name().map(|n| is_migratable_config_file(&n.to_string_lossy())).unwrap_or(false))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let postprocess_files = [current_postprocess.clone(), current_postprocess.with_extension("json.bak")];
+        let mut copied = 0;
+        if current_dir != target {
+            copied += migrate_config_files(&files, &target)?;
+        }
+        if current_postprocess.parent() != Some(postprocess_target.as_path()) {
+            copied += migrate_config_files(&postprocess_files, &postprocess_target)?;
+        }
+
+        if let Some(dir) = bootstrap.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("创建目录 {} 失败: {}", dir.display(), e))?;
+        }
+        if path.is_empty() {
+            match fs::remove_file(&bootstrap) {
+                Ok(_) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(format!("删除配置目录设置失败: {}", e)),
+            }
+        } else {
+            fs::write(&bootstrap, target.to_string_lossy().as_bytes())
+                .map_err(|e| format!("保存配置目录设置失败: {}", e))?;
+        }
+        copied
+    };
+    // 日志写入端随配置目录切换，保证 get_recent_logs 读取的就是正在写入的文件
+    app.state::<LogState>().file.switch_dir(&target);
+    info!("[tauri] 配置目录已切换为 {}（复制 {} 个文件）", target.display(), copied);
+
+    if let Err(e) = start_postprocess_watcher(&app) {
+        warn!("[tauri] {}", e);
+    }
+    // 新目录中的界面设置可能使用不同的提示语言
+    set_current_locale(&load_ui_settings().locale);
+    let _ = app.emit("config-directory-changed", get_config_directory());
+    if !app.state::<BridgeState>().disabled.load(Ordering::SeqCst) {
+        restart_bridge(app.clone(), app.state()).await?;
+    }
+    Ok(copied)
+}
+
+#[derive(Serialize)]
+struct ConfigPathCheck {
+    // tauri_config（界面设置、统计、历史）/ postprocess（替换词典）
+    name: &'static str,
+    dir: String,
+    writable: bool,
+    // 失败时的系统错误
+    error: Option<String>,
+}
+
+// Tauri命令：检查各配置目录是否可写（安装在 Program Files 等只读位置时首次保存才会失败，引导流程可据此提前提示）
+#[tauri::command]
+fn check_config_writable() -> Vec<ConfigPathCheck> {
+    let postprocess = resolve_postprocess_path();
+    let locations = [
+        ("tauri_config", resolve_tauri_config_path("")),
+        ("postprocess", postprocess.parent().map(|p| p.to_path_buf()).unwrap_or(postprocess)),
+    ];
+    locations
+        .into_iter()
+        .map(|(name, dir)| {
+            let result = ensure_dir_writable(&dir);
+            if let Err(e) = &result {
+                warn!("[tauri] 配置目录不可写 {}: {}", dir.display(), e);
+            }
+            ConfigPathCheck {
+                name,
+                dir: dir.to_string_lossy().to_string(),
+                writable: result.is_ok(),
+                error: result.err(),
+            }
+        })
+        .collect()
+}
+
+fn read_postprocess_config_from_disk() -> Result<PostprocessConfig, String> {
+    let path = resolve_postprocess_path();
+    if !path.exists() {
+        return Ok(PostprocessConfig { case_insensitive: true, replace_map: IndexMap::new(), limits: PostprocessLimits::default() });
+    }
+    let mut file = fs::File::open(&path).map_err(|e| tf("config_open_failed", &[&e]))?;
+    let mut buf = String::new();
+    file.read_to_string(&mut buf).map_err(|e| tf("config_read_failed", &[&e]))?;
+
+    // 优先严格反序列化为 PostprocessConfig（可保留键的插入顺序）
+    match serde_json::from_str::<PostprocessConfig>(&buf) {
+        Ok(cfg) => return Ok(cfg),
+        Err(_e) => {
+            // 兼容旧格式或非字符串值：宽松解析再清洗
+            let val: Value = serde_json::from_str(&buf).map_err(|e| tf("config_parse_failed", &[&e]))?;
+            let case_insensitive = val.get("case_insensitive").and_then(|v| v.as_bool()).unwrap_or(true);
+            let limits = val
+                .get("limits")
+                .and_then(|v| serde_json::from_value::<PostprocessLimits>(v.clone()).ok())
+                .unwrap_or_default();
+            let mut map: IndexMap<String, ReplaceEntry> = IndexMap::new();
+            if let Some(obj) = val.get("replace_map").and_then(|v| v.as_object()) {
+                for (k, v) in obj.iter() {
+                    map.insert(k.clone(), ReplaceEntry::from_value(v.clone()));
+                }
+            }
+            return Ok(PostprocessConfig { case_insensitive, replace_map: map, limits });
+        }
+    }
+}
+
+// 单个条目的校验失败原因，供前端定位到具体行与字段
+#[derive(Serialize, Debug, Clone)]
+struct EntryRejection {
+    // 原始键（未 trim）
+    key: String,
+    // key / value
+    field: &'static str,
+    // too_long / empty / bad_regex
+    code: &'static str,
+    message: String,
+}
+
+impl EntryRejection {
+    fn new(key: &str, field: &'static str, code: &'static str, message: String) -> Self {
+        EntryRejection { key: key.to_string(), field, code, message }
+    }
+}
+
+#[derive(Serialize, Debug)]
+struct SavePostprocessResult {
+    entries: usize,
+    // 最终写入的配置（merge 模式下便于前端直接刷新）
+    config: PostprocessConfig,
+}
+
+// 保存失败：errors 仅包含被拒绝的条目；整体性错误（如条目数超限、写入失败）只有 message
+#[derive(Serialize, Debug)]
+struct SavePostprocessError {
+    message: String,
+    errors: Vec<EntryRejection>,
+}
+
+impl From<String> for SavePostprocessError {
+    fn from(message: String) -> Self {
+        SavePostprocessError { message, errors: Vec::new() }
+    }
+}
+
+// 清洗单个条目：trim、去空、长度限制（按 limits，正则至少 128）、正则可编译
+// 返回 Ok(None) 表示键和值均为空（空行），应直接跳过
+fn clean_replace_entry(key: &str, entry: ReplaceEntry, case_insensitive: bool, limits: &PostprocessLimits) -> Result<Option<(String, ReplaceEntry)>, EntryRejection> {
+    let key_trim = key.trim();
+    let val_trimmed = entry.value.trim();
+
+    if key_trim.is_empty() && val_trimmed.is_empty() { return Ok(None); }
+    if key_trim.is_empty() { return Err(EntryRejection::new(key, "key", "empty", t("entry_key_empty"))); }
+    if val_trimmed.is_empty() { return Err(EntryRejection::new(key, "value", "empty", tf("entry_value_empty", &[&key_trim]))); }
+    let max_chars = limits.max_chars(entry.kind);
+    if key_trim.chars().count() > max_chars {
+        return Err(EntryRejection::new(key, "key", "too_long", tf("entry_key_too_long", &[&max_chars, &key_trim])));
+    }
+    if val_trimmed.chars().count() > max_chars {
+        return Err(EntryRejection::new(key, "value", "too_long", tf("entry_value_too_long", &[&max_chars, &val_trimmed])));
+    }
+    if entry.kind == ReplaceKind::Regex {
+        if let Err(e) = regex::RegexBuilder::new(key_trim).case_insensitive(case_insensitive).build() {
+            return Err(EntryRejection::new(key, "key", "bad_regex", tf("entry_bad_regex", &[&key_trim, &e])));
+        }
+        if let Some(syntax) = python_unsupported_regex_syntax(key_trim) {
+            return Err(EntryRejection::new(key, "key", "bad_regex", tf("entry_regex_unsupported", &[&key_trim, &syntax])));
+        }
+    }
+
+    Ok(Some((key_trim.to_string(), ReplaceEntry { value: val_trimmed.to_string(), kind: entry.kind, enabled: entry.enabled })))
+}
+
+// 正则由 Rust regex 校验、由 Python re 执行：找出只有 Rust 支持的语法，
+// 如 \p{Han}、\x{4e00}、\z、[[:alpha:]]、(?<name>...)、(?U)
+fn python_unsupported_regex_syntax(pattern: &str) -> Option<&'static str> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => {
+                match (chars.get(i + 1), chars.get(i + 2)) {
+                    (Some('p') | Some('P'), _) => return Some("\\p{...}"),
+                    (Some('x') | Some('u'), Some('{')) => return Some("\\x{...}"),
+                    (Some('z'), _) => return Some("\\z"),
+                    _ => {}
+                }
+                i += 2;
+                continue;
+            }
+            '[' if chars.get(i + 1) == Some(&'[') && chars.get(i + 2) == Some(&':') => return Some("[[:...:]]"),
+            '(' if chars.get(i + 1) == Some(&'?') => {
+                if chars.get(i + 2) == Some(&'<') && !matches!(chars.get(i + 3), Some('=') | Some('!')) {
+                    return Some("(?<name>...)");
+                }
+                let mut flags = chars[i + 2..].iter().take_while(|c| c.is_ascii_alphabetic() || **c == '-');
+                if flags.any(|c| *c == 'U' || *c == 'R') {
+                    return Some("(?U)");
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+// 查找与 key 重复的已有键（大小写不敏感时按 lowercase 比较）
+fn find_duplicate_key(map: &IndexMap<String, ReplaceEntry>, key: &str, case_insensitive: bool) -> Option<String> {
+    if case_insensitive {
+        let key_norm = key.to_lowercase();
+        map.keys().find(|k| k.to_lowercase() == key_norm).cloned()
+    } else if map.contains_key(key) {
+        Some(key.to_string())
+    } else {
+        None
+    }
+}
+
+// 插入条目：重复键以后出现的为准（移到末尾），保存原始大小写键
+fn insert_replace_entry(map: &mut IndexMap<String, ReplaceEntry>, key: String, entry: ReplaceEntry, case_insensitive: bool) {
+    if let Some(old_key) = find_duplicate_key(map, &key, case_insensitive) {
+        let _ = map.shift_remove(&old_key);
+    }
+    map.insert(key, entry);
+}
+
+fn validate_and_clean_payload(payload: SavePostprocessPayload, current: PostprocessConfig) -> Result<PostprocessConfig, SavePostprocessError> {
+    let limits = payload.limits.unwrap_or(current.limits).effective();
+    // merge 模式从现有词典出发，未指定大小写策略时沿用现有配置
+    let (case_insensitive, mut cleaned) = match payload.mode {
+        SavePostprocessMode::Replace => (payload.case_insensitive.unwrap_or(true), IndexMap::new()),
+        SavePostprocessMode::Merge => (payload.case_insensitive.unwrap_or(current.case_insensitive), current.replace_map),
+    };
+
+    let mut errors: Vec<EntryRejection> = Vec::new();
+    for (k, v) in payload.replace_map.into_iter() {
+        match clean_replace_entry(&k, ReplaceEntry::from_value(v), case_insensitive, &limits) {
+            Ok(Some((key, entry))) => insert_replace_entry(&mut cleaned, key, entry, case_insensitive),
+            Ok(None) => {}
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(SavePostprocessError {
+            message: tf("entries_rejected", &[&errors.len()]),
+            errors,
+        });
+    }
+
+    // 条目数限制（默认最多 200 条）
+    if cleaned.len() > limits.max_entries {
+        return Err(tf("too_many_entries", &[&cleaned.len(), &limits.max_entries]).into());
+    }
+
+    // 如果不区分大小写，为了与 Python 侧匹配，需要把保存的键恢复为原样大小写。
+    // 这里选择将键以当前 cleaned 的键直接保存（已转为 lower），Python 侧基于 re.IGNORECASE 进行匹配，大小写无关。
+
+    Ok(PostprocessConfig { case_insensitive, replace_map: cleaned, limits })
+}
+
+fn resolve_postprocess_backup_path() -> PathBuf {
+    resolve_postprocess_path().with_extension("json.bak")
+}
+
+fn write_postprocess_config_to_disk(app: &tauri::AppHandle, cfg: &PostprocessConfig) -> Result<(), String> {
+    let path = resolve_postprocess_path();
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| tf("config_dir_create_failed", &[&e]))?; }
+
+    // 覆盖前备份现有文件（尽力而为，失败不影响保存）
+    if path.exists() {
+        let backup_path = resolve_postprocess_backup_path();
+        if let Err(e) = fs::copy(&path, &backup_path) {
+            warn!("备份 postprocess.json 失败，跳过备份: {}", e);
+        }
+    }
+
+    let data = serde_json::to_string_pretty(cfg).map_err(|e| tf("config_serialize_failed", &[&e]))?;
+    // 记录本次写入内容，文件监听据此忽略应用自身的写入
+    if let Ok(mut guard) = app.state::<AppState>().postprocess_last_written.lock() {
+        *guard = Some(data.clone());
+    }
+    write_json_file_verified::<PostprocessConfig>(&path, &data, Some(&resolve_postprocess_backup_path()))
+}
+
+// 临时文件写入后原子替换，再回读校验能否解析为目标结构：
+// 防止磁盘写满或杀毒软件干扰留下无法读取的文件。校验失败时若有备份则从备份恢复
+fn write_json_file_verified<T: serde::de::DeserializeOwned>(
+    path: &std::path::Path,
+    data: &str,
+    backup_path: Option<&std::path::Path>,
+) -> Result<(), String> {
+    let tmp_path = path.with_extension("json.tmp");
+    {
+        let mut f = fs::File::create(&tmp_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
+        f.write_all(data.as_bytes()).map_err(|e| format!("写入临时文件失败: {}", e))?;
+        f.sync_all().ok();
+    }
+    // 尝试原子替换
+    if fs::rename(&tmp_path, path).is_err() {
+        // Windows 上若目标存在可能失败：先删除再重命名
+        let _ = fs::remove_file(path);
+        fs::rename(&tmp_path, path).map_err(|e| format!("替换文件失败: {}", e))?;
+    }
+
+    let verify_err = match fs::read_to_string(path) {
+        Ok(content) => serde_json::from_str::<T>(&content).err().map(|e| e.to_string()),
+        Err(e) => Some(e.to_string()),
+    };
+    let Some(reason) = verify_err else { return Ok(()) };
+    warn!("写入后校验 {} 失败: {}", path.display(), reason);
+    match backup_path.filter(|b| b.exists()) {
+        Some(backup) => match fs::copy(backup, path) {
+            Ok(_) => Err(format!("写入后校验失败，已从备份恢复: {}", reason)),
+            Err(e) => Err(format!("写入后校验失败（{}），且从备份恢复失败: {}", reason, e)),
+        },
+        None => Err(format!("写入后校验失败: {}", reason)),
+    }
+}
+
+#[derive(Serialize)]
+struct CsvImportRejection {
+    // CSV 中的行号（从 1 开始，含表头）
+    row: usize,
+    reason: String,
+}
+
+#[derive(Serialize)]
+struct CsvImportSummary {
+    accepted: usize,
+    rejected: usize,
+    rejections: Vec<CsvImportRejection>,
+}
+
+// 导出替换词典为两列 CSV（key,value）；条目类型与启用状态不导出
+#[tauri::command]
+fn export_postprocess_csv(path: String) -> Result<usize, String> {
+    let cfg = read_postprocess_config_from_disk()?;
+    let path = PathBuf::from(path);
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+        }
+    }
+
+    let mut writer = csv::Writer::from_path(&path).map_err(|e| format!("创建 CSV 文件失败: {}", e))?;
+    writer.write_record(["key", "value"]).map_err(|e| format!("写入 CSV 失败: {}", e))?;
+    for (key, entry) in cfg.replace_map.iter() {
+        writer.write_record([key.as_str(), entry.value.as_str()]).map_err(|e| format!("写入 CSV 失败: {}", e))?;
+    }
+    writer.flush().map_err(|e| format!("写入 CSV 失败: {}", e))?;
+    Ok(cfg.replace_map.len())
+}
+
+// 从两列 CSV（key,value）导入替换词典：逐行清洗，合并到现有词典；
+// 单行错误不影响其他行，返回接受/拒绝统计
+#[tauri::command]
+fn import_postprocess_csv(app: tauri::AppHandle, path: String) -> Result<CsvImportSummary, String> {
+    let mut cfg = read_postprocess_config_from_disk()?;
+    let case_insensitive = cfg.case_insensitive;
+    let limits = cfg.limits.effective();
+
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_path(&path)
+        .map_err(|e| format!("打开 CSV 文件失败: {}", e))?;
+
+    let mut accepted = 0usize;
+    let mut rejections: Vec<CsvImportRejection> = Vec::new();
+    for (idx, record) in reader.records().enumerate() {
+        let row = idx + 1;
+        let record = match record {
+            Ok(r) => r,
+            Err(e) => {
+                rejections.push(CsvImportRejection { row, reason: format!("CSV 解析失败: {}", e) });
+                continue;
+            }
+        };
+        let key = record.get(0).unwrap_or("");
+        let value = record.get(1);
+        // 跳过表头
+        if row == 1 && key.trim().eq_ignore_ascii_case("key") && value.map(|v| v.trim().eq_ignore_ascii_case("value")).unwrap_or(false) {
+            continue;
+        }
+        let Some(value) = value else {
+            rejections.push(CsvImportRejection { row, reason: "列数不足，需要 key,value 两列".to_string() });
+            continue;
+        };
+
+        let entry = ReplaceEntry { value: value.to_string(), kind: ReplaceKind::Literal, enabled: true };
+        match clean_replace_entry(key, entry, case_insensitive, &limits) {
+            Ok(Some((key, entry))) => {
+                let is_new = find_duplicate_key(&cfg.replace_map, &key, case_insensitive).is_none();
+                if is_new && cfg.replace_map.len() >= limits.max_entries {
+                    rejections.push(CsvImportRejection { row, reason: format!("超出替换词典上限（最多 {} 条）", limits.max_entries) });
+                    continue;
+                }
+                insert_replace_entry(&mut cfg.replace_map, key, entry, case_insensitive);
+                accepted += 1;
+            }
+            Ok(None) => rejections.push(CsvImportRejection { row, reason: "键和值均为空".to_string() }),
+            Err(e) => rejections.push(CsvImportRejection { row, reason: e.message }),
+        }
+    }
+
+    if accepted > 0 {
+        write_postprocess_config_to_disk(&app, &cfg)?;
+    }
+    Ok(CsvImportSummary { accepted, rejected: rejections.len(), rejections })
+}
+
+#[derive(Serialize)]
+struct PreviewRuleHit {
+    key: String,
+    count: usize,
+}
+
+#[derive(Serialize)]
+struct PostprocessPreview {
+    text: String,
+    total_substitutions: usize,
+    rules: Vec<PreviewRuleHit>,
+}
+
+// 按与 Python 侧相同的规则对文本应用替换词典：
+// - 按 replace_map 的插入顺序逐条应用，后面的规则作用于前面规则的输出；
+// - 同一规则内从左到右替换所有不重叠的匹配；
+// - case_insensitive 时字面规则忽略大小写匹配，正则规则附加忽略大小写标志；
+// - 停用的规则跳过。经由正则替换的规则与 Python re.sub 一样展开 \1、\g<name> 等引用。
+fn apply_postprocess_rules(text: &str, cfg: &PostprocessConfig) -> Result<PostprocessPreview, String> {
+    let mut value = text.to_string();
+    let mut rules: Vec<PreviewRuleHit> = Vec::new();
+    let mut total = 0usize;
+
+    for (key, entry) in cfg.replace_map.iter() {
+        if !entry.enabled || key.is_empty() {
+            continue;
+        }
+        let count = if entry.kind == ReplaceKind::Literal && !cfg.case_insensitive {
+            let count = value.matches(key.as_str()).count();
+            if count > 0 {
+                value = value.replace(key.as_str(), &entry.value);
+            }
+            count
+        } else {
+            let pattern = match entry.kind {
+                ReplaceKind::Literal => regex::escape(key),
+                ReplaceKind::Regex => key.clone(),
+            };
+            let re = regex::RegexBuilder::new(&pattern)
+                .case_insensitive(cfg.case_insensitive)
+                .build()
+                .map_err(|e| format!("正则表达式无效（键: {}）: {}", key, e))?;
+            let count = re.find_iter(&value).count();
+            if count > 0 {
+                value = re.replace_all(&value, python_replacement_to_rust(&entry.value).as_str()).into_owned();
+            }
+            count
+        };
+        total += count;
+        rules.push(PreviewRuleHit { key: key.clone(), count });
+    }
+
+    Ok(PostprocessPreview { text: value, total_substitutions: total, rules })
+}
+
+// 将 Python re.sub 的替换模板转换为 regex crate 的语法：\1 / \g<1> / \g<name> -> ${1} / ${name}，
+// \\、\n、\t 等转义按 Python 规则展开，字面 $ 写作 $$；无法识别的转义原样保留
+fn python_replacement_to_rust(template: &str) -> String {
+    let chars: Vec<char> = template.chars().collect();
+    let mut out = String::with_capacity(template.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '$' {
+            out.push_str("$$");
+            i += 1;
+            continue;
+        }
+        if c != '\\' || i + 1 >= chars.len() {
+            out.push(c);
+            i += 1;
+            continue;
+        }
+        let next = chars[i + 1];
+        match next {
+            '1'..='9' => {
+                // Python 最多读取两位数字作为分组编号
+                let mut end = i + 2;
+                if end < chars.len() && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                let group: String = chars[i + 1..end].iter().collect();
+                out.push_str(&format!("${{{}}}", group));
+                i = end;
+            }
+            'g' if chars.get(i + 2) == Some(&'<') => {
+                match chars[i + 3..].iter().position(|c| *c == '>') {
+                    Some(len) => {
+                        let name: String = chars[i + 3..i + 3 + len].iter().collect();
+                        out.push_str(&format!("${{{}}}", name));
+                        i += 4 + len;
+                    }
+                    None => {
+                        out.push_str("\\g");
+                        i += 2;
+                    }
+                }
+            }
+            '\\' => { out.push('\\'); i += 2; }
+            'n' => { out.push('\n'); i += 2; }
+            't' => { out.push('\t'); i += 2; }
+            'r' => { out.push('\r'); i += 2; }
+            'f' => { out.push('\u{0c}'); i += 2; }
+            'v' => { out.push('\u{0b}'); i += 2; }
+            'a' => { out.push('\u{07}'); i += 2; }
+            _ => {
+                out.push(c);
+                i += 1;
+            }
+        }
+    }
+    out
+}
+
+// 预览替换效果：不读写磁盘，也不影响桥接进程正在使用的配置
+#[tauri::command]
+fn preview_postprocess(text: String, config: PostprocessConfig) -> Result<PostprocessPreview, String> {
+    apply_postprocess_rules(&text, &config)
+}
+
+// 从 postprocess.json.bak 恢复：当前文件会在写入时成为新的备份（即两者互换）
+#[tauri::command]
+fn restore_postprocess_backup(app: tauri::AppHandle) -> Result<PostprocessConfig, String> {
+    let backup_path = resolve_postprocess_backup_path();
+    if !backup_path.exists() {
+        return Err("没有可恢复的备份".to_string());
+    }
+    let content = fs::read_to_string(&backup_path).map_err(|e| format!("读取备份失败: {}", e))?;
+    let restored: PostprocessConfig = serde_json::from_str(&content).map_err(|e| format!("备份文件解析失败: {}", e))?;
+    write_postprocess_config_to_disk(&app, &restored)?;
+    Ok(restored)
+}
+
+// 读取配置（limits 为实际生效的限制，便于界面展示）
+#[tauri::command]
+fn get_postprocess_config() -> Result<PostprocessConfig, String> {
+    let mut cfg = read_postprocess_config_from_disk()?;
+    cfg.limits = cfg.limits.effective();
+    Ok(cfg)
+}
+
+// 保存配置
+#[tauri::command]
+fn save_postprocess_config(app: tauri::AppHandle, payload: SavePostprocessPayload) -> Result<SavePostprocessResult, SavePostprocessError> {
+    let current = if payload.mode == SavePostprocessMode::Merge {
+        read_postprocess_config_from_disk()?
+    } else {
+        // 覆盖模式仅需沿用现有限制，读取失败时使用默认值
+        read_postprocess_config_from_disk().unwrap_or_else(|_| PostprocessConfig {
+            case_insensitive: true,
+            replace_map: IndexMap::new(),
+            limits: PostprocessLimits::default(),
+        })
+    };
+    let cfg = validate_and_clean_payload(payload, current)?;
+    write_postprocess_config_to_disk(&app, &cfg)?;
+    Ok(SavePostprocessResult { entries: cfg.replace_map.len(), config: cfg })
+}
+
+// 监听 postprocess.json 所在目录：外部编辑后（去抖）重新加载并通知桥接与前端
+fn start_postprocess_watcher(app: &tauri::AppHandle) -> Result<(), String> {
+    use notify::Watcher;
+
+    let path = resolve_postprocess_path();
+    let dir = path.parent().map(PathBuf::from).ok_or_else(|| "无法确定配置目录".to_string())?;
+    fs::create_dir_all(&dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+
+    let app_handle = app.clone();
+    let mut watcher = notify::recommended_watcher(move |res: notify::Result<notify::Event>| {
+        let Ok(event) = res else { return };
+        if event.kind.is_access() {
+            return;
+        }
+        let touches_config = event
+            .paths
+            .iter()
+            .any(|p| p.file_name().map(|n| n == "postprocess.json").unwrap_or(false));
+        if touches_config {
+            schedule_postprocess_reload(&app_handle);
+        }
+    })
+    .map_err(|e| format!("创建配置文件监听失败: {}", e))?;
+    watcher
+        .watch(&dir, notify::RecursiveMode::NonRecursive)
+        .map_err(|e| format!("监听配置目录 {} 失败: {}", dir.display(), e))?;
+
+    *app.state::<AppState>().config_watcher.lock().unwrap() = Some(watcher);
+    info!("[tauri] 已开始监听配置目录: {}", dir.display());
+    Ok(())
+}
+
+// 去抖：编辑器保存时常连续触发多次事件，仅处理最后一次
+fn schedule_postprocess_reload(app: &tauri::AppHandle) {
+    let seq = {
+        let app_state = app.state::<AppState>();
+        let mut guard = app_state.postprocess_reload_seq.lock().unwrap();
+        *guard = guard.wrapping_add(1);
+        *guard
+    };
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(POSTPROCESS_RELOAD_DELAY).await;
+        let latest = { *app_handle.state::<AppState>().postprocess_reload_seq.lock().unwrap() };
+        if latest != seq {
+            return;
+        }
+        reload_postprocess_config(&app_handle).await;
+    });
+}
+
+async fn reload_postprocess_config(app: &tauri::AppHandle) {
+    // 内容与应用最近一次写入一致时视为自身写入，跳过
+    let content = fs::read_to_string(resolve_postprocess_path()).ok();
+    {
+        let app_state = app.state::<AppState>();
+        let last_written = app_state.postprocess_last_written.lock().unwrap();
+        if content.is_some() && *last_written == content {
+            return;
+        }
+    }
+
+    let cfg = match read_postprocess_config_from_disk() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            warn!("[tauri] 重新加载 postprocess.json 失败: {}", e);
+            return;
+        }
+    };
+    info!("[tauri] 检测到 postprocess.json 外部修改，已重新加载（{} 条规则）", cfg.replace_map.len());
+    if let Err(e) = send_bridge_command(app, "reload_postprocess").await {
+        info!("[tauri] {}", e);
+    }
+    let _ = app.emit("postprocess-reloaded", cfg);
+}
+
+// -----------------------------
+// 使用统计：读/写（usage_stats.json）
+// -----------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct UsageTotals {
+    #[serde(default)]
+    time_saved_sec: f64,
+    #[serde(default)]
+    total_chars: u64,
+    #[serde(default)]
+    corrections: u64,
+    // 录音次数与累计录音时长（秒），用于计算平均时长
+    #[serde(default)]
+    recordings_count: u64,
+    #[serde(default)]
+    total_duration_sec: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct UsageToday {
+    date: String,
+    #[serde(default)]
+    time_saved_sec: f64,
+    #[serde(default)]
+    total_chars: u64,
+    #[serde(default)]
+    corrections: u64,
+    #[serde(default)]
+    recordings_count: u64,
+    #[serde(default)]
+    total_duration_sec: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct UsageStatsFile {
+    totals: UsageTotals,
+    today: UsageToday,
+    // 历史每日统计（按日期升序，最多保留 USAGE_HISTORY_MAX_DAYS 天；旧文件缺失该字段时为空）
+    #[serde(default)]
+    history: Vec<UsageToday>,
+}
+
+// 历史统计保留天数
+const USAGE_HISTORY_MAX_DAYS: i64 = 90;
+
+fn resolve_usage_stats_path() -> PathBuf {
+    resolve_tauri_config_path("usage_stats.json")
+}
+
+fn current_date_string() -> String {
+    Local::now().format("%Y-%m-%d").to_string()
+}
+
+fn default_usage_stats() -> UsageStatsFile {
+    UsageStatsFile {
+        totals: UsageTotals::default(),
+        today: UsageToday { date: current_date_string(), ..Default::default() },
+        history: Vec::new(),
+    }
+}
+
+fn read_usage_stats_from_disk() -> Result<UsageStatsFile, String> {
+    let path = resolve_usage_stats_path();
+    if !path.exists() {
+        return Ok(default_usage_stats());
+    }
+    match fs::read_to_string(&path) {
+        Ok(content) => match serde_json::from_str::<UsageStatsFile>(&content) {
+            Ok(mut stats) => {
+                // 基本健壮性：字段可能缺失
+                if stats.today.date.trim().is_empty() {
+                    stats.today.date = current_date_string();
+                }
+                Ok(stats)
+            }
+            Err(e) => {
+                warn!("读取 usage_stats.json 解析失败: {}，将使用默认值", e);
+                Ok(default_usage_stats())
+            }
+        },
+        Err(e) => {
+            warn!("读取 usage_stats.json 失败: {}，将使用默认值", e);
+            Ok(default_usage_stats())
+        }
+    }
+}
+
+// 跨进程文件锁（usage_stats.json.lock 上的建议锁）：多个实例同时读改写统计时避免丢失更新。
+// 进程内仍先获取 AppState.usage_lock，文件锁随守卫析构释放（含提前返回的错误路径）
+struct UsageStatsFileLock {
+    file: fs::File,
+}
+
+impl UsageStatsFileLock {
+    fn acquire() -> Result<Self, String> {
+        let path = resolve_usage_stats_path().with_extension("json.lock");
+        if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?; }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .map_err(|e| format!("打开统计锁文件失败: {}", e))?;
+        file.lock_exclusive().map_err(|e| format!("获取统计文件锁失败: {}", e))?;
+        Ok(UsageStatsFileLock { file })
+    }
+}
+
+impl Drop for UsageStatsFileLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+fn write_usage_stats_to_disk(stats: &UsageStatsFile) -> Result<(), String> {
+    let path = resolve_usage_stats_path();
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?; }
+    let data = serde_json::to_string_pretty(stats).map_err(|e| format!("序列化统计失败: {}", e))?;
+    // 统计文件没有备份：校验失败只能报错，下次读取时回退为默认值
+    write_json_file_verified::<UsageStatsFile>(&path, &data, None)
+}
+
+// 将已写入的文件强制落盘；与常规写入不同，sync_all 失败会返回错误。
+// Windows 上 FlushFileBuffers 需要写权限，因此以可写方式打开；Unix 上再同步所在目录以持久化 rename
+fn sync_file_to_disk(path: &std::path::Path) -> Result<(), String> {
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .open(path)
+        .map_err(|e| format!("打开 {} 失败: {}", path.display(), e))?;
+    file.sync_all().map_err(|e| format!("同步 {} 到磁盘失败: {}", path.display(), e))?;
+    #[cfg(unix)]
+    if let Some(dir) = path.parent() {
+        fs::File::open(dir)
+            .and_then(|d| d.sync_all())
+            .map_err(|e| format!("同步目录 {} 失败: {}", dir.display(), e))?;
+    }
+    Ok(())
+}
+
+// Tauri命令：立即重写统计文件并确认已落盘（共享/自助终端机器在计划重启前使用）
+#[tauri::command]
+fn flush_usage_stats(app: tauri::AppHandle) -> Result<UsageStatsSnapshot, String> {
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let _file_lock = UsageStatsFileLock::acquire()?;
+
+    let mut stats = read_usage_stats_from_disk()?;
+    rollover_today_if_needed(&mut stats);
+    write_usage_stats_to_disk(&stats)?;
+    sync_file_to_disk(&resolve_usage_stats_path())?;
+    info!("[tauri] 统计已写入并同步到磁盘");
+    Ok(UsageStatsSnapshot::from(&stats))
+}
+
+fn parse_stats_date(date: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d").ok()
+}
+
+// 将旧的 today 归档到 history，并裁剪超出保留天数的记录
+fn archive_today_to_history(stats: &mut UsageStatsFile) {
+    if parse_stats_date(&stats.today.date).is_some() {
+        stats.history.retain(|d| d.date != stats.today.date);
+        stats.history.push(stats.today.clone());
+        stats.history.sort_by(|a, b| a.date.cmp(&b.date));
+    }
+    let cutoff = Local::now().date_naive() - chrono::Duration::days(USAGE_HISTORY_MAX_DAYS);
+    stats.history.retain(|d| parse_stats_date(&d.date).map(|date| date > cutoff).unwrap_or(false));
+}
+
+fn rollover_today_if_needed(stats: &mut UsageStatsFile) -> bool {
+    let today = current_date_string();
+    if stats.today.date != today {
+        archive_today_to_history(stats);
+        stats.today.date = today;
+        stats.today.time_saved_sec = 0.0;
+        stats.today.total_chars = 0;
+        stats.today.corrections = 0;
+        stats.today.recordings_count = 0;
+        stats.today.total_duration_sec = 0.0;
+        true
+    } else {
+        false
+    }
+}
+
+#[derive(Serialize, Clone)]
+struct UsageStatsSnapshot {
+    today_sec: f64,
+    total_sec: f64,
+    today_chars: u64,
+    total_chars: u64,
+    today_corrections: u64,
+    total_corrections: u64,
+    today_recordings: u64,
+    total_recordings: u64,
+    today_duration_sec: f64,
+    total_duration_sec: f64,
+    // 平均录音时长（秒）；无录音时为 0
+    today_avg_duration_sec: f64,
+    total_avg_duration_sec: f64,
+    // 纠错率：每 100 字的纠错次数；无字数时为 0
+    today_correction_rate: f64,
+    total_correction_rate: f64,
+}
+
+fn average_duration(total_sec: f64, count: u64) -> f64 {
+    if count == 0 { 0.0 } else { total_sec / count as f64 }
+}
+
+fn correction_rate(corrections: u64, chars: u64) -> f64 {
+    if chars == 0 { 0.0 } else { corrections as f64 * 100.0 / chars as f64 }
+}
+
+impl From<&UsageStatsFile> for UsageStatsSnapshot {
+    fn from(stats: &UsageStatsFile) -> Self {
+        UsageStatsSnapshot {
+            today_sec: stats.today.time_saved_sec,
+            total_sec: stats.totals.time_saved_sec,
+            today_chars: stats.today.total_chars,
+            total_chars: stats.totals.total_chars,
+            today_corrections: stats.today.corrections,
+            total_corrections: stats.totals.corrections,
+            today_recordings: stats.today.recordings_count,
+            total_recordings: stats.totals.recordings_count,
+            today_duration_sec: stats.today.total_duration_sec,
+            total_duration_sec: stats.totals.total_duration_sec,
+            today_avg_duration_sec: average_duration(stats.today.total_duration_sec, stats.today.recordings_count),
+            total_avg_duration_sec: average_duration(stats.totals.total_duration_sec, stats.totals.recordings_count),
+            today_correction_rate: correction_rate(stats.today.corrections, stats.today.total_chars),
+            total_correction_rate: correction_rate(stats.totals.corrections, stats.totals.total_chars),
+        }
+    }
+}
+
+// 一次转写结果带来的统计增量
+#[derive(Debug, Default, Clone, Copy)]
+struct UsageIncrement {
+    duration_sec: f64,
+    saved_sec: f64,
+    chars: u64,
+    corrections: u64,
+}
+
+// 从 transcription_result 中取出本次计入统计的修正次数。
+// 桥接分别提供 dict_corrections（词典替换）与 model_corrections（模型自身修正）时按设置求和；
+// 旧版桥接只提供 corrections，原样计入
+fn corrections_from_result(val: &Value, count_dictionary: bool) -> u64 {
+    let count = |key: &str| val.get(key).and_then(|v| v.as_i64()).map(|v| v.max(0) as u64);
+    let dict = count("dict_corrections");
+    let model = count("model_corrections");
+    if dict.is_none() && model.is_none() {
+        return count("corrections").unwrap_or(0);
+    }
+    let dict = if count_dictionary { dict.unwrap_or(0) } else { 0 };
+    dict.saturating_add(model.unwrap_or(0))
+}
+
+// 累加一次录音：录音次数 +1、录音时长与节省时间、字数与自动修正次数。
+// 一次结果只做一次读改写，减少连续听写时的磁盘写入
+fn accumulate_transcription(app: &tauri::AppHandle, inc: UsageIncrement) -> Result<UsageStatsSnapshot, String> {
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let _file_lock = UsageStatsFileLock::acquire()?;
+
+    let stats = read_usage_stats_from_disk()?;
+    apply_usage_increment(stats, inc, write_usage_stats_to_disk)
+}
+
+fn apply_usage_increment(
+    mut stats: UsageStatsFile,
+    inc: UsageIncrement,
+    write: impl FnOnce(&UsageStatsFile) -> Result<(), String>,
+) -> Result<UsageStatsSnapshot, String> {
+    let _changed = rollover_today_if_needed(&mut stats);
+    let saved = if inc.saved_sec.is_finite() && inc.saved_sec > 0.0 { inc.saved_sec } else { 0.0 };
+    stats.today.time_saved_sec += saved;
+    stats.totals.time_saved_sec += saved;
+    let dur = if inc.duration_sec.is_finite() && inc.duration_sec > 0.0 { inc.duration_sec } else { 0.0 };
+    stats.today.recordings_count = stats.today.recordings_count.saturating_add(1);
+    stats.totals.recordings_count = stats.totals.recordings_count.saturating_add(1);
+    stats.today.total_duration_sec += dur;
+    stats.totals.total_duration_sec += dur;
+    stats.today.total_chars = stats.today.total_chars.saturating_add(inc.chars);
+    stats.totals.total_chars = stats.totals.total_chars.saturating_add(inc.chars);
+    stats.today.corrections = stats.today.corrections.saturating_add(inc.corrections);
+    stats.totals.corrections = stats.totals.corrections.saturating_add(inc.corrections);
+    write(&stats)?;
+    Ok(UsageStatsSnapshot::from(&stats))
+}
+
+// 重置统计：scope 为 today（仅今天）、totals（仅累计）或 all（全部，含历史）
+#[tauri::command]
+fn reset_usage_stats(app: tauri::AppHandle, scope: String) -> Result<UsageStatsSnapshot, String> {
+    let scope = scope.trim().to_ascii_lowercase();
+    if !matches!(scope.as_str(), "today" | "totals" | "all") {
+        return Err(format!("不支持的重置范围: {}（可选 today / totals / all）", scope));
+    }
+
+    let snapshot = {
+        let state = app.state::<AppState>();
+        let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+        let _file_lock = UsageStatsFileLock::acquire()?;
+
+        // 先完成跨日归档，避免把过期的 today 写回磁盘
+        let mut stats = read_usage_stats_from_disk()?;
+        rollover_today_if_needed(&mut stats);
+        match scope.as_str() {
+            "today" => stats.today = default_usage_stats().today,
+            "totals" => stats.totals = default_usage_stats().totals,
+            _ => stats = default_usage_stats(),
+        }
+        write_usage_stats_to_disk(&stats)?;
+        UsageStatsSnapshot::from(&stats)
+    };
+
+    info!("[tauri] 已重置使用统计，范围: {}", scope);
+    emit_usage_stats(&app, &snapshot);
+    Ok(snapshot)
+}
+
+// 千位分隔：1234 -> "1,234"
+fn format_thousands(value: u64) -> String {
+    let digits = value.to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, ch) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(ch);
+    }
+    out
+}
+
+// 节省时间：秒 -> "45秒" / "12分30秒" / "1小时5分"
+fn format_saved_duration(seconds: f64) -> String {
+    let total = if seconds.is_finite() && seconds > 0.0 { seconds.round() as u64 } else { 0 };
+    let (hours, minutes, secs) = (total / 3600, total % 3600 / 60, total % 60);
+    if hours > 0 {
+        format!("{}小时{}分", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}分{}秒", minutes, secs)
+    } else {
+        format!("{}秒", secs)
+    }
+}
+
+// 托盘提示：显示今日字数与节省时间；托盘已销毁（如退出过程中）时直接跳过
+fn update_tray_tooltip(app: &tauri::AppHandle, snapshot: &UsageStatsSnapshot) {
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let tooltip = format!(
+        "speak keyboard\n今日：{} 字 · 节省 {}",
+        format_thousands(snapshot.today_chars),
+        format_saved_duration(snapshot.today_sec)
+    );
+    if let Err(e) = tray.set_tooltip(Some(tooltip)) {
+        warn!("更新托盘提示失败: {}", e);
+    }
+}
+
+#[derive(Default)]
+struct StatsEmitState {
+    last_emit: Option<Instant>,
+    // 间隔内到达、等待尾沿发送的最新快照
+    pending: Option<UsageStatsSnapshot>,
+    // 是否已安排尾沿发送任务
+    scheduled: bool,
+}
+
+// 广播最新统计：托盘提示立即更新；stats-updated 按 stats_emit_interval_ms 节流，
+// 间隔内的多次更新合并为一次，并在间隔结束时发送最新快照
+fn emit_usage_stats(app: &tauri::AppHandle, snapshot: &UsageStatsSnapshot) {
+    update_tray_tooltip(app, snapshot);
+    let interval = Duration::from_millis(load_ui_settings().stats_emit_interval_ms.min(MAX_STATS_EMIT_INTERVAL_MS));
+
+    let state = app.state::<AppState>();
+    let mut emit = state.stats_emit.lock().unwrap();
+    let elapsed = emit.last_emit.map(|t| t.elapsed());
+    if !emit.scheduled && elapsed.map(|e| e >= interval).unwrap_or(true) {
+        emit.last_emit = Some(Instant::now());
+        drop(emit);
+        let _ = app.emit("stats-updated", snapshot);
+        return;
+    }
+    emit.pending = Some(snapshot.clone());
+    if emit.scheduled {
+        return;
+    }
+    emit.scheduled = true;
+    let delay = interval.saturating_sub(elapsed.unwrap_or_default());
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(delay).await;
+        let pending = {
+            let state = app.state::<AppState>();
+            let mut emit = state.stats_emit.lock().unwrap();
+            emit.scheduled = false;
+            emit.last_emit = Some(Instant::now());
+            emit.pending.take()
+        };
+        if let Some(snapshot) = pending {
+            let _ = app.emit("stats-updated", snapshot);
+        }
+    });
+}
+
+// Tauri命令：获取 stats-updated 事件最小间隔（毫秒）
+#[tauri::command]
+fn get_stats_emit_interval() -> u64 {
+    load_ui_settings().stats_emit_interval_ms
+}
+
+// Tauri命令：设置 stats-updated 事件最小间隔（0 ~ MAX_STATS_EMIT_INTERVAL_MS 毫秒，0 表示每次更新都立即发送）
+#[tauri::command]
+fn set_stats_emit_interval(interval_ms: u64) -> Result<(), String> {
+    if interval_ms > MAX_STATS_EMIT_INTERVAL_MS {
+        return Err(format!("统计事件间隔不能超过 {} 毫秒", MAX_STATS_EMIT_INTERVAL_MS));
+    }
+    let mut settings = load_ui_settings();
+    settings.stats_emit_interval_ms = interval_ms;
+    save_ui_settings(&settings)
+}
+
+#[derive(Serialize)]
+struct UsageHistory {
+    days: u32,
+    time_saved_sec: f64,
+    total_chars: u64,
+    corrections: u64,
+    // 窗口内每日数据（按日期升序，含今天；无记录的日期补零）
+    entries: Vec<UsageToday>,
+}
+
+// 获取最近 days 天（含今天）的汇总与每日明细
+#[tauri::command]
+fn get_usage_history(app: tauri::AppHandle, days: u32) -> Result<UsageHistory, String> {
+    if days == 0 || days as i64 > USAGE_HISTORY_MAX_DAYS {
+        return Err(format!("天数超出范围：{}（允许 1-{}）", days, USAGE_HISTORY_MAX_DAYS));
+    }
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let _file_lock = UsageStatsFileLock::acquire()?;
+
+    let mut stats = read_usage_stats_from_disk()?;
+    if rollover_today_if_needed(&mut stats) { write_usage_stats_to_disk(&stats)?; }
+
+    let today = Local::now().date_naive();
+    let mut entries: Vec<UsageToday> = Vec::with_capacity(days as usize);
+    for offset in (0..days as i64).rev() {
+        let date = (today - chrono::Duration::days(offset)).format("%Y-%m-%d").to_string();
+        let entry = if date == stats.today.date {
+            stats.today.clone()
+        } else {
+            stats.history.iter().find(|d| d.date == date).cloned().unwrap_or(UsageToday {
+                date,
+                ..Default::default()
+            })
+        };
+        entries.push(entry);
+    }
+
+    Ok(UsageHistory {
+        days,
+        time_saved_sec: entries.iter().map(|d| d.time_saved_sec).sum(),
+        total_chars: entries.iter().map(|d| d.total_chars).sum(),
+        corrections: entries.iter().map(|d| d.corrections).sum(),
+        entries,
+    })
+}
+
+// 导出每日统计为 CSV（历史 + 今天，按日期升序），返回写入的数据行数
+#[tauri::command]
+fn export_usage_stats_csv(app: tauri::AppHandle, path: String) -> Result<usize, String> {
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let _file_lock = UsageStatsFileLock::acquire()?;
+
+    let mut stats = read_usage_stats_from_disk()?;
+    if rollover_today_if_needed(&mut stats) { write_usage_stats_to_disk(&stats)?; }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer
+        .write_record(["date", "time_saved_sec", "total_chars", "corrections"])
+        .map_err(|e| format!("写入 CSV 失败: {}", e))?;
+    let mut rows = 0usize;
+    for day in stats.history.iter().chain(std::iter::once(&stats.today)) {
+        writer
+            .write_record([
+                day.date.clone(),
+                format!("{:.3}", day.time_saved_sec),
+                day.total_chars.to_string(),
+                day.corrections.to_string(),
+            ])
+            .map_err(|e| format!("写入 CSV 失败: {}", e))?;
+        rows += 1;
+    }
+    let data = writer.into_inner().map_err(|e| format!("写入 CSV 失败: {}", e))?;
+
+    let path = PathBuf::from(path);
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+        }
+    }
+    let tmp_path = path.with_extension("csv.tmp");
+    {
+        let mut f = fs::File::create(&tmp_path).map_err(|e| format!("创建临时文件失败: {}", e))?;
+        f.write_all(&data).map_err(|e| format!("写入临时文件失败: {}", e))?;
+        f.sync_all().ok();
+    }
+    match fs::rename(&tmp_path, &path) {
+        Ok(_) => Ok(rows),
+        Err(_) => {
+            let _ = fs::remove_file(&path);
+            fs::rename(&tmp_path, &path).map_err(|e| format!("替换导出文件失败: {}", e))?;
+            Ok(rows)
+        }
+    }
+}
+
+#[tauri::command]
+fn get_time_saved_multiplier() -> f64 {
+    effective_time_saved_multiplier(&load_ui_settings())
+}
+
+// -----------------------------
+// 倍率自动估算：记录最近若干次录音的字数与时长（typing_samples.json），
+// 按说话速度相对参考打字速度的比值估算节省时间倍率
+// -----------------------------
+
+// 参考打字速度（字/秒），约每分钟 90 字
+const REFERENCE_TYPING_CHARS_PER_SEC: f64 = 1.5;
+// 滚动窗口大小与开始估算所需的最少样本数
+const TYPING_SAMPLES_MAX: usize = 50;
+const TYPING_SAMPLES_MIN: usize = 5;
+// 过短的录音误差大，不作为样本
+const TYPING_SAMPLE_MIN_DURATION_SEC: f64 = 0.5;
+// 估算结果的允许范围，避免异常样本导致倍率失控
+const MIN_AUTO_TIME_MULTIPLIER: f64 = 1.0;
+const MAX_AUTO_TIME_MULTIPLIER: f64 = 6.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+struct TypingSample {
+    chars: u64,
+    duration_sec: f64,
+}
+
+fn resolve_typing_samples_path() -> PathBuf {
+    resolve_usage_stats_path().with_file_name("typing_samples.json")
+}
+
+fn read_typing_samples() -> Vec<TypingSample> {
+    fs::read_to_string(resolve_typing_samples_path())
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+fn write_typing_samples(samples: &[TypingSample]) -> Result<(), String> {
+    let path = resolve_typing_samples_path();
+    if let Some(dir) = path.parent() { fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?; }
+    let data = serde_json::to_string(samples).map_err(|e| format!("序列化样本失败: {}", e))?;
+    fs::write(&path, data).map_err(|e| format!("写入样本文件失败: {}", e))
+}
+
+// 由样本估算倍率；样本不足时返回 None
+fn estimate_time_multiplier(samples: &[TypingSample]) -> Option<f64> {
+    if samples.len() < TYPING_SAMPLES_MIN {
+        return None;
+    }
+    let chars: u64 = samples.iter().map(|s| s.chars).sum();
+    let duration: f64 = samples.iter().map(|s| s.duration_sec).sum();
+    if duration <= 0.0 {
+        return None;
+    }
+    let speaking_chars_per_sec = chars as f64 / duration;
+    Some((speaking_chars_per_sec / REFERENCE_TYPING_CHARS_PER_SEC).clamp(MIN_AUTO_TIME_MULTIPLIER, MAX_AUTO_TIME_MULTIPLIER))
+}
+
+// 记录一次录音样本（保留最近 TYPING_SAMPLES_MAX 条），返回更新后的估算倍率
+fn record_typing_sample(app: &tauri::AppHandle, chars: u64, duration_sec: f64) -> Option<f64> {
+    let state = app.state::<AppState>();
+    let _guard = state.typing_samples_lock.lock().ok()?;
+    let mut samples = read_typing_samples();
+    if chars > 0 && duration_sec.is_finite() && duration_sec >= TYPING_SAMPLE_MIN_DURATION_SEC {
+        samples.push(TypingSample { chars, duration_sec });
+        if samples.len() > TYPING_SAMPLES_MAX {
+            let excess = samples.len() - TYPING_SAMPLES_MAX;
+            samples.drain(..excess);
+        }
+        if let Err(e) = write_typing_samples(&samples) {
+            warn!("[tauri] 记录打字速度样本失败: {}", e);
+        }
+    }
+    estimate_time_multiplier(&samples)
+}
+
+#[derive(Serialize)]
+struct TimeMultiplierEstimate {
+    auto: bool,
+    // 样本不足时为 None
+    estimated: Option<f64>,
+    samples: usize,
+    // 当前实际使用的倍率
+    effective: f64,
+}
+
+// Tauri命令：获取自动估算的倍率及当前生效倍率
+#[tauri::command]
+fn get_estimated_time_multiplier() -> TimeMultiplierEstimate {
+    let settings = load_ui_settings();
+    let samples = read_typing_samples();
+    let estimated = estimate_time_multiplier(&samples);
+    let effective = match estimated {
+        Some(m) if settings.auto_time_multiplier => m,
+        _ => effective_time_saved_multiplier(&settings),
+    };
+    TimeMultiplierEstimate { auto: settings.auto_time_multiplier, estimated, samples: samples.len(), effective }
+}
+
+// Tauri命令：获取词典替换是否计入修正次数
+#[tauri::command]
+fn get_count_dictionary_as_corrections() -> bool {
+    load_ui_settings().count_dictionary_as_corrections
+}
+
+// Tauri命令：设置词典替换是否计入修正次数（仅影响之后的转写）
+#[tauri::command]
+fn set_count_dictionary_as_corrections(enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.count_dictionary_as_corrections = enabled;
+    save_ui_settings(&settings)
+}
+
+#[tauri::command]
+fn set_auto_time_multiplier(enabled: bool) -> Result<bool, String> {
+    let mut settings = load_ui_settings();
+    settings.auto_time_multiplier = enabled;
+    save_ui_settings(&settings)?;
+    Ok(true)
+}
+
+#[tauri::command]
+fn set_time_saved_multiplier(multiplier: f64) -> Result<bool, String> {
+    if !is_valid_time_saved_multiplier(multiplier) {
+        return Err(format!("节省时间倍率无效：{}（允许大于 0 且不超过 {}）", multiplier, MAX_TIME_SAVED_MULTIPLIER));
+    }
+    let mut settings = load_ui_settings();
+    settings.time_saved_multiplier = multiplier;
+    save_ui_settings(&settings)?;
+    Ok(true)
+}
+
+// 读取当前统计（跨天时先归零今日数据并写回）
+fn current_usage_stats(app: &tauri::AppHandle) -> Result<UsageStatsSnapshot, String> {
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let _file_lock = UsageStatsFileLock::acquire()?;
+
+    let mut stats = read_usage_stats_from_disk()?;
+    let changed = rollover_today_if_needed(&mut stats);
+    if changed { write_usage_stats_to_disk(&stats)?; }
+    Ok(UsageStatsSnapshot::from(&stats))
+}
+
+#[tauri::command]
+fn get_usage_stats(app: tauri::AppHandle) -> Result<UsageStatsSnapshot, String> {
+    current_usage_stats(&app)
+}
+
+// Tauri命令：向所有窗口重新广播一次完整统计（窗口重新获得焦点时用于同步）
+#[tauri::command]
+fn broadcast_usage_stats(app: tauri::AppHandle) -> Result<(), String> {
+    let snapshot = current_usage_stats(&app)?;
+    emit_usage_stats(&app, &snapshot);
+    Ok(())
+}
+
+// -----------------------------
+// 配置打包：将 ui_settings / postprocess / usage_stats 导出为单个 JSON，便于排查问题与迁移
+// -----------------------------
+
+#[derive(Serialize, Deserialize)]
+struct ConfigBundle {
+    #[serde(default)]
+    app_version: String,
+    // 缺少的部分在导入时保持不变
+    #[serde(default)]
+    ui_settings: Option<Value>,
+    #[serde(default)]
+    postprocess: Option<Value>,
+    #[serde(default)]
+    usage_stats: Option<Value>,
+}
+
+#[derive(Serialize)]
+struct ConfigBundleImportSummary {
+    ui_settings: bool,
+    postprocess: bool,
+    usage_stats: bool,
+}
+
+// Tauri命令：导出当前生效的全部配置到一个 JSON 文件
+#[tauri::command]
+fn export_config_bundle(app: tauri::AppHandle, path: String) -> Result<(), String> {
+    let usage_stats = {
+        let state = app.state::<AppState>();
+        let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+        let _file_lock = UsageStatsFileLock::acquire()?;
+        read_usage_stats_from_disk()?
+    };
+    // 本地 HTTP 接口令牌不随配置包导出，避免分享配置时泄露
+    let mut ui_settings = load_ui_settings();
+    ui_settings.http_api_token = None;
+    let bundle = ConfigBundle {
+        app_version: app.package_info().version.to_string(),
+        ui_settings: Some(serde_json::to_value(ui_settings).map_err(|e| format!("序列化界面设置失败: {}", e))?),
+        postprocess: Some(serde_json::to_value(read_postprocess_config_from_disk()?).map_err(|e| format!("序列化替换词典失败: {}", e))?),
+        usage_stats: Some(serde_json::to_value(usage_stats).map_err(|e| format!("序列化统计失败: {}", e))?),
+    };
+    let data = serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化配置失败: {}", e))?;
+
+    let path = PathBuf::from(path);
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+        }
+    }
+    fs::write(&path, data).map_err(|e| format!("写入配置文件失败: {}", e))
+}
+
+// Tauri命令：导入配置包。先校验全部部分，任一部分无效则不写入任何文件；写入中途失败时恢复已写入的部分；
+// 部分界面设置（如快捷键、日志级别）需重启后生效
+#[tauri::command]
+fn import_config_bundle(app: tauri::AppHandle, path: String) -> Result<ConfigBundleImportSummary, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取配置文件失败: {}", e))?;
+    let bundle: ConfigBundle = serde_json::from_str(&content).map_err(|e| format!("配置文件解析失败: {}", e))?;
+
+    let ui_settings = match bundle.ui_settings {
+        Some(value) => {
+            let (value, _) = migrate_ui_settings(value);
+            Some(serde_json::from_value::<UiSettings>(value).map_err(|e| format!("ui_settings 无效: {}", e))?)
+        }
+        None => None,
+    };
+    let postprocess = match bundle.postprocess {
+        Some(value) => {
+            let payload: SavePostprocessPayload =
+                serde_json::from_value(value).map_err(|e| format!("postprocess 无效: {}", e))?;
+            let current = read_postprocess_config_from_disk()?;
+            Some(validate_and_clean_payload(payload, current).map_err(|e| format!("postprocess 无效: {}", e.message))?)
+        }
+        None => None,
+    };
+    let usage_stats = match bundle.usage_stats {
+        Some(value) => Some(serde_json::from_value::<UsageStatsFile>(value).map_err(|e| format!("usage_stats 无效: {}", e))?),
+        None => None,
+    };
+    if ui_settings.is_none() && postprocess.is_none() && usage_stats.is_none() {
+        return Err("配置文件中没有可导入的内容".to_string());
+    }
+
+    let summary = ConfigBundleImportSummary {
+        ui_settings: ui_settings.is_some(),
+        postprocess: postprocess.is_some(),
+        usage_stats: usage_stats.is_some(),
+    };
+    // 写入前保存原文件内容：后续某一部分写入失败时恢复已写入的部分，避免只导入一半
+    let previous_ui_settings = ui_settings.as_ref().map(|_| FileSnapshot::take(resolve_ui_settings_path())).transpose()?;
+    let previous_postprocess = postprocess.as_ref().map(|_| FileSnapshot::take(resolve_postprocess_path())).transpose()?;
+    let rollback = || {
+        for snapshot in previous_ui_settings.iter().chain(previous_postprocess.iter()) {
+            snapshot.restore();
+        }
+        set_current_locale(&load_ui_settings().locale);
+    };
+
+    if let Some(mut settings) = ui_settings {
+        // 配置包不含令牌：保留本机已有的 HTTP 接口令牌
+        settings.http_api_token = load_ui_settings().http_api_token;
+        save_ui_settings(&settings)?;
+        set_current_locale(&settings.locale);
+    }
+    if let Some(cfg) = postprocess {
+        if let Err(e) = write_postprocess_config_to_disk(&app, &cfg) {
+            rollback();
+            return Err(e);
+        }
+    }
+    if let Some(stats) = usage_stats {
+        match write_imported_usage_stats(&app, stats) {
+            Ok(snapshot) => emit_usage_stats(&app, &snapshot),
+            Err(e) => {
+                rollback();
+                return Err(e);
+            }
+        }
+    }
+    Ok(summary)
+}
+
+fn write_imported_usage_stats(app: &tauri::AppHandle, mut stats: UsageStatsFile) -> Result<UsageStatsSnapshot, String> {
+    let state = app.state::<AppState>();
+    let _guard = state.usage_lock.lock().map_err(|e| format!("获取统计锁失败: {}", e))?;
+    let _file_lock = UsageStatsFileLock::acquire()?;
+    rollover_today_if_needed(&mut stats);
+    write_usage_stats_to_disk(&stats)?;
+    Ok(UsageStatsSnapshot::from(&stats))
+}
+
+// 文件写入前的原始内容（None 表示原本不存在），用于导入失败时回滚
+struct FileSnapshot {
+    path: PathBuf,
+    content: Option<String>,
+}
+
+impl FileSnapshot {
+    // 读不到原内容就无法回滚，此时直接放弃导入
+    fn take(path: PathBuf) -> Result<Self, String> {
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => Some(content),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(format!("读取 {} 失败: {}", path.display(), e)),
+        };
+        Ok(FileSnapshot { path, content })
+    }
+
+    // 尽力恢复：回滚本身失败时只记录日志，返回最初的写入错误
+    fn restore(&self) {
+        let result = match &self.content {
+            Some(content) => fs::write(&self.path, content),
+            None => match fs::remove_file(&self.path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                other => other,
+            },
+        };
+        if let Err(e) = result {
+            warn!("恢复 {} 失败: {}", self.path.display(), e);
+        }
+    }
+}
+
+// -----------------------------
+// 转写历史：每条 transcription_result 追加一行 JSON 到 config/history.jsonl，仅保留最近 history_limit 条
+// -----------------------------
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct TranscriptionHistoryEntry {
+    timestamp: String,
+    text: String,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    corrections: u64,
+}
+
+fn resolve_history_path() -> PathBuf {
+    resolve_tauri_config_path(HISTORY_FILE_NAME)
+}
+
+fn effective_history_limit(settings: &UiSettings) -> usize {
+    settings.history_limit.min(MAX_HISTORY_LIMIT)
+}
+
+// 读取全部历史（按时间升序）；无法解析的行（如写入中断的半行）直接跳过
+fn read_history_from_disk() -> Result<Vec<TranscriptionHistoryEntry>, String> {
+    let path = resolve_history_path();
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取 history.jsonl 失败: {}", e)),
+    };
+    Ok(content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+fn write_history_to_disk(entries: &[TranscriptionHistoryEntry]) -> Result<(), String> {
+    let path = resolve_history_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    let mut data = String::new();
+    for entry in entries {
+        let line = serde_json::to_string(entry).map_err(|e| format!("序列化转写历史失败: {}", e))?;
+        data.push_str(&line);
+        data.push('\n');
+    }
+    let tmp_path = path.with_extension("jsonl.tmp");
+    fs::write(&tmp_path, data).map_err(|e| format!("写入临时文件失败: {}", e))?;
+    match fs::rename(&tmp_path, &path) {
+        Ok(_) => Ok(()),
+        Err(_) => {
+            let _ = fs::remove_file(&path);
+            fs::rename(&tmp_path, &path).map_err(|e| format!("替换 history.jsonl 失败: {}", e))
+        }
+    }
+}
+
+// 追加一条历史；超出上限时重写文件，仅保留最近 limit 条
+fn append_transcription_history(app: &tauri::AppHandle, entry: TranscriptionHistoryEntry) -> Result<(), String> {
+    let limit = effective_history_limit(&load_ui_settings());
+    if limit == 0 {
+        return Ok(());
+    }
+    let state = app.state::<AppState>();
+    let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+
+    let path = resolve_history_path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+    let line = serde_json::to_string(&entry).map_err(|e| format!("序列化转写历史失败: {}", e))? + "\n";
+    {
+        let mut f = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| format!("打开 history.jsonl 失败: {}", e))?;
+        f.write_all(line.as_bytes()).map_err(|e| format!("写入 history.jsonl 失败: {}", e))?;
+    }
+
+    let entries = read_history_from_disk()?;
+    if entries.len() > limit {
+        write_history_to_disk(&entries[entries.len() - limit..])?;
+    }
+    Ok(())
+}
+
+// Tauri命令：获取最近的转写历史（最新在前），query 非空时按文本子串过滤（不区分大小写）
+#[tauri::command]
+fn get_transcription_history(
+    app: tauri::AppHandle,
+    limit: Option<usize>,
+    query: Option<String>,
+) -> Result<Vec<TranscriptionHistoryEntry>, String> {
+    let state = app.state::<AppState>();
+    let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+
+    let needle = query
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
+    let limit = limit.unwrap_or(MAX_HISTORY_LIMIT).min(MAX_HISTORY_LIMIT);
+    Ok(read_history_from_disk()?
+        .into_iter()
+        .rev()
+        .filter(|entry| needle.as_ref().map(|n| entry.text.to_lowercase().contains(n)).unwrap_or(true))
+        .take(limit)
+        .collect())
+}
+
+// 历史条目的本地日期（时间戳无法解析时为 None）
+fn history_entry_date(entry: &TranscriptionHistoryEntry) -> Option<chrono::NaiveDate> {
+    chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+        .ok()
+        .map(|t| t.with_timezone(&Local).date_naive())
+}
+
+// Tauri命令：导出转写历史为 JSONL；from/to（YYYY-MM-DD，含端点）可选，用于按日期筛选。返回导出条数
+#[tauri::command]
+fn export_history(
+    app: tauri::AppHandle,
+    path: String,
+    from: Option<String>,
+    to: Option<String>,
+) -> Result<usize, String> {
+    let parse_bound = |value: Option<String>| -> Result<Option<chrono::NaiveDate>, String> {
+        match value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty()) {
+            Some(v) => parse_stats_date(&v).map(Some).ok_or_else(|| format!("日期格式无效: {}（应为 YYYY-MM-DD）", v)),
+            None => Ok(None),
+        }
+    };
+    let (from, to) = (parse_bound(from)?, parse_bound(to)?);
+
+    let state = app.state::<AppState>();
+    let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+    let entries: Vec<TranscriptionHistoryEntry> = read_history_from_disk()?
+        .into_iter()
+        .filter(|entry| {
+            if from.is_none() && to.is_none() {
+                return true;
+            }
+            let Some(date) = history_entry_date(entry) else { return false };
+            from.map(|f| date >= f).unwrap_or(true) && to.map(|t| date <= t).unwrap_or(true)
+        })
+        .collect();
+
+    let mut data = String::new();
+    for entry in &entries {
+        let line = serde_json::to_string(entry).map_err(|e| format!("序列化转写历史失败: {}", e))?;
+        data.push_str(&line);
+        data.push('\n');
+    }
+    let path = PathBuf::from(path);
+    if let Some(dir) = path.parent() {
+        if !dir.as_os_str().is_empty() {
+            fs::create_dir_all(dir).map_err(|e| format!("创建导出目录失败: {}", e))?;
+        }
+    }
+    fs::write(&path, data).map_err(|e| format!("写入导出文件失败: {}", e))?;
+    info!("[tauri] 已导出 {} 条转写历史到 {}", entries.len(), path.display());
+    Ok(entries.len())
+}
+
+#[derive(Serialize, Debug, Default, PartialEq)]
+struct HistoryImportSummary {
+    imported: usize,
+    // 与现有历史（或文件内）timestamp + text 相同而跳过的条数
+    duplicates: usize,
+    // 无法解析为历史条目的行数
+    malformed: usize,
+    // 合并后超出 history_limit 被裁掉的最旧条数
+    dropped: usize,
+}
+
+// 合并导入的历史：按 timestamp + text 去重，按时间排序后仅保留最近 limit 条
+fn merge_history(
+    existing: Vec<TranscriptionHistoryEntry>,
+    incoming: Vec<TranscriptionHistoryEntry>,
+    limit: usize,
+    summary: &mut HistoryImportSummary,
+) -> Vec<TranscriptionHistoryEntry> {
+    let mut seen: std::collections::HashSet<(String, String)> =
+        existing.iter().map(|e| (e.timestamp.clone(), e.text.clone())).collect();
+    let mut merged = existing;
+    for entry in incoming {
+        if seen.insert((entry.timestamp.clone(), entry.text.clone())) {
+            merged.push(entry);
+            summary.imported += 1;
+        } else {
+            summary.duplicates += 1;
+        }
+    }
+    merged.sort_by_key(|e| chrono::DateTime::parse_from_rfc3339(&e.timestamp).ok());
+    if merged.len() > limit {
+        summary.dropped = merged.len() - limit;
+        merged.drain(..summary.dropped);
+    }
+    merged
+}
+
+// Tauri命令：从 JSONL 文件导入转写历史并与现有历史合并（整个过程持有历史锁，避免与新转写交错写入）
+#[tauri::command]
+fn import_history(app: tauri::AppHandle, path: String) -> Result<HistoryImportSummary, String> {
+    let content = fs::read_to_string(&path).map_err(|e| format!("读取导入文件失败: {}", e))?;
+    let mut summary = HistoryImportSummary::default();
+    let mut incoming = Vec::new();
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<TranscriptionHistoryEntry>(line) {
+            Ok(entry) => incoming.push(entry),
+            Err(_) => summary.malformed += 1,
+        }
+    }
+
+    let limit = effective_history_limit(&load_ui_settings());
+    let state = app.state::<AppState>();
+    let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+    let merged = merge_history(read_history_from_disk()?, incoming, limit, &mut summary);
+    if summary.imported > 0 || summary.dropped > 0 {
+        write_history_to_disk(&merged)?;
+    }
+    info!(
+        "[tauri] 导入转写历史：新增 {} 条，重复 {} 条，无效 {} 行，超出上限 {} 条",
+        summary.imported, summary.duplicates, summary.malformed, summary.dropped
+    );
+    Ok(summary)
+}
+
+// Tauri命令：通过桥接进程输出任意文本（与转写结果一样遵循当前输出方式）
+#[tauri::command]
+async fn type_text(app: tauri::AppHandle, text: String) -> Result<(), String> {
+    if text.is_empty() {
+        return Err("文本为空，无需输出".to_string());
+    }
+    let len = text.chars().count();
+    if len > MAX_TYPE_TEXT_CHARS {
+        return Err(format!("文本过长：{} 个字符（最多 {} 个）", len, MAX_TYPE_TEXT_CHARS));
+    }
+    send_bridge_payload(&app, serde_json::json!({"cmd": "type_text", "text": text})).await
+}
+
+// Tauri命令：复制最近一次转写结果到剪贴板，返回复制的字符数
+#[tauri::command]
+fn copy_last_transcription(app: tauri::AppHandle, state: State<AppState>) -> Result<usize, String> {
+    let cached = state.last_transcription.lock().unwrap().clone();
+    // 重启后内存中尚无结果时回退到转写历史
+    let text = match cached {
+        Some(text) => text,
+        None => {
+            let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+            read_history_from_disk()?
+                .pop()
+                .map(|entry| entry.text)
+                .ok_or_else(|| "暂无转写结果可复制".to_string())?
+        }
+    };
+    app.clipboard()
+        .write_text(text.clone())
+        .map_err(|e| format!("写入剪贴板失败: {}", e))?;
+    Ok(text.chars().count())
+}
+
+// Tauri命令：将最近一次转写结果重新输出到当前焦点窗口
+#[tauri::command]
+async fn retype_last(app: tauri::AppHandle) -> Result<(), String> {
+    let last = {
+        let state = app.state::<AppState>();
+        let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+        read_history_from_disk()?.pop()
+    };
+    let Some(entry) = last else {
+        return Err("暂无转写历史，无法重新输出（请确认 history_limit 大于 0）".to_string());
+    };
+    type_text(app, entry.text).await
+}
+
+// Tauri命令：清空转写历史
+#[tauri::command]
+fn clear_transcription_history(app: tauri::AppHandle) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let _guard = state.history_lock.lock().map_err(|e| format!("获取历史锁失败: {}", e))?;
+
+    match fs::remove_file(resolve_history_path()) {
+        Ok(_) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(format!("删除 history.jsonl 失败: {}", e)),
+    }
+    info!("[tauri] 已清空转写历史");
+    Ok(())
+}
+
+// Tauri命令：开始录音
+#[tauri::command]
+async fn start_recording(app: tauri::AppHandle, state: tauri::State<'_, AppState>, bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
+    ensure_bridge_enabled(&bridge)?;
+    ensure_recording_start_allowed(&app)?;
+    // 将 start 指令写入桥接进程
+    let stdin_arc = bridge.stdin.clone();
+    let mut guard = stdin_arc.lock().await;
+    if let Some(stdin) = guard.as_mut() {
+        debug!("[tauri] 准备发送 start 指令到桥接进程");
+        let payload = capture_output_target(&state).to_string() + "\n" + &serde_json::json!({"cmd": "start"}).to_string() + "\n";
+        if let Err(e) = stdin.write_all(payload.as_bytes()).await { warn!("[tauri] 写入 start 指令失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
+        if let Err(e) = stdin.flush().await { warn!("[tauri] 刷新 start 指令失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
+        debug!("[tauri] start 指令已写入，等待桥接事件更新状态");
+        // 状态将由事件回传更新
+        Ok(())
+    } else {
+        Err("桥接进程未就绪，无法开始录音".to_string())
+    }
+}
+
+// Tauri命令：停止录音
+#[tauri::command]
+async fn stop_recording(_state: tauri::State<'_, AppState>, bridge: tauri::State<'_, BridgeState>) -> Result<String, String> {
+    // 将 stop 指令写入桥接进程
+    let stdin_arc = bridge.stdin.clone();
+    let mut guard = stdin_arc.lock().await;
+    if let Some(stdin) = guard.as_mut() {
+        debug!("[tauri] 准备发送 stop 指令到桥接进程");
+        let payload = serde_json::json!({"cmd": "stop"}).to_string() + "\n";
+        if let Err(e) = stdin.write_all(payload.as_bytes()).await { warn!("[tauri] 写入 stop 指令失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
+        if let Err(e) = stdin.flush().await { warn!("[tauri] 刷新 stop 指令失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
+        debug!("[tauri] stop 指令已写入，等待桥接事件更新状态");
+        // 返回简单确认字符串，实际结果通过事件回传
+        Ok("ok".to_string())
+    } else {
+        Err("桥接进程未就绪，无法停止录音".to_string())
+    }
+}
+
+// 切换录音：以后端状态为准，避免前端状态不同步导致无法停止
+#[tauri::command]
+async fn toggle_recording(app: tauri::AppHandle, state: tauri::State<'_, AppState>, bridge: tauri::State<'_, BridgeState>) -> Result<(), String> {
+    ensure_bridge_enabled(&bridge)?;
+    let currently_recording = { *state.is_recording.lock().unwrap() };
+    debug!("[tauri] toggle_recording 调用：当前 is_recording={}，将发送{}", currently_recording, if currently_recording { "stop" } else { "start" });
+    if currently_recording {
+        let stdin_arc = bridge.stdin.clone();
+        let mut guard = stdin_arc.lock().await;
+        if let Some(stdin) = guard.as_mut() {
+            let payload = serde_json::json!({"cmd": "stop"}).to_string() + "\n";
+            if let Err(e) = stdin.write_all(payload.as_bytes()).await { warn!("[tauri] toggle_recording: 写入 stop 失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
+            if let Err(e) = stdin.flush().await { warn!("[tauri] toggle_recording: 刷新 stop 失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
+            debug!("[tauri] toggle_recording: stop 指令已写入（本地预切换为 false，最终以事件为准）");
+            // 预先切换为 false，最终以事件为准
+            {
+                let mut rec = state.is_recording.lock().unwrap();
+                *rec = false;
+            }
+            Ok(())
+        } else {
+            warn!("[tauri] toggle_recording: stdin 不可用，无法发送 stop");
+            Err("桥接进程未就绪，无法停止录音".to_string())
+        }
+    } else {
+        ensure_recording_start_allowed(&app)?;
+        let stdin_arc = bridge.stdin.clone();
+        let mut guard = stdin_arc.lock().await;
+        if let Some(stdin) = guard.as_mut() {
+            let payload = capture_output_target(&state).to_string() + "\n" + &serde_json::json!({"cmd": "start"}).to_string() + "\n";
+            if let Err(e) = stdin.write_all(payload.as_bytes()).await { warn!("[tauri] toggle_recording: 写入 start 失败: {}", e); return Err(format!("写入桥接进程失败: {}", e)); }
+            if let Err(e) = stdin.flush().await { warn!("[tauri] toggle_recording: 刷新 start 失败: {}", e); return Err(format!("刷新写入失败: {}", e)); }
+            debug!("[tauri] toggle_recording: start 指令已写入（本地预切换为 true，最终以事件为准）");
+            // 预先切换为 true，最终以事件为准
+            {
+                let mut rec = state.is_recording.lock().unwrap();
+                *rec = true;
+            }
+            Ok(())
+        } else {
+            warn!("[tauri] toggle_recording: stdin 不可用，无法发送 start");
+            Err("桥接进程未就绪，无法开始录音".to_string())
+        }
+    }
+}
+
+fn tray_toggle_label(recording: bool) -> &'static str {
+    if recording { "停止录音" } else { "开始录音" }
+}
+
+// 根据录音状态更新托盘：菜单项文字切换，录音中使用带红点的图标，空闲时恢复默认图标
+fn update_tray_recording_state(app: &tauri::AppHandle, recording: bool) {
+    if let Some(item) = app.state::<AppState>().tray_toggle_item.lock().unwrap().as_ref() {
+        if let Err(e) = item.set_text(tray_toggle_label(recording)) {
+            warn!("更新托盘菜单失败: {}", e);
+        }
+    }
+    let Some(tray) = app.tray_by_id(TRAY_ID) else {
+        return;
+    };
+    let recording_icon = if recording {
+        app.path()
+            .resolve(TRAY_RECORDING_ICON, BaseDirectory::Resource)
+            .ok()
+            .and_then(|path| match tauri::image::Image::from_path(&path) {
+                Ok(image) => Some(image),
+                Err(e) => {
+                    warn!("加载托盘录音图标失败 {}: {}", path.display(), e);
+                    None
+                }
+            })
+    } else {
+        None
+    };
+    let icon = recording_icon.or_else(|| app.default_window_icon().cloned());
+    if let Err(e) = tray.set_icon(icon) {
+        warn!("更新托盘图标失败: {}", e);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct AudioDevice {
+    id: String,
+    name: String,
+    #[serde(default)]
+    is_default: bool,
+}
+
+// 将已保存的桥接相关设置（录音设备、输出方式）重新下发给桥接；未设置的项保持桥接默认配置
+async fn apply_saved_bridge_settings(app: &tauri::AppHandle) {
+    let settings = load_ui_settings();
+    if let Some(device) = settings.audio_device {
+        let payload = serde_json::json!({"cmd": "set_device", "id": device});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用录音设备失败: {}", e);
+        }
+    }
+    if let Some(method) = settings.output_method {
+        let payload = serde_json::json!({"cmd": "set_output_method", "method": method});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用输出方式失败: {}", e);
+        }
+    }
+    if settings.trim_trailing != TRIM_TRAILING_NONE {
+        let payload = serde_json::json!({"cmd": "set_trim", "mode": settings.trim_trailing});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用结尾裁剪失败: {}", e);
+        }
+    }
+    if settings.type_delay_ms > 0 {
+        let payload = serde_json::json!({"cmd": "set_type_delay", "delay_ms": settings.type_delay_ms.min(MAX_TYPE_DELAY_MS)});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用键入间隔失败: {}", e);
+        }
+    }
+    // 静音检测参数暂不重新下发：当前桥接不支持 set_vad，待桥接实现后再恢复
+    if !settings.show_partials {
+        let payload = serde_json::json!({"cmd": "set_partials", "enabled": false});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用中间结果开关失败: {}", e);
+        }
+    }
+    if settings.voice_formatting {
+        let payload = serde_json::json!({"cmd": "set_voice_formatting", "enabled": true});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用语音格式指令失败: {}", e);
+        }
+    }
+    if settings.dataset_collection {
+        let dir = resolve_dataset_dir(settings.dataset_dir.as_deref());
+        let payload = serde_json::json!({"cmd": "set_dataset", "enabled": true, "dir": dir.to_string_lossy()});
+        if let Err(e) = send_bridge_payload(app, payload).await {
+            warn!("[tauri] 重新应用数据集采集失败: {}", e);
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct DatasetCollectionInfo {
+    enabled: bool,
+    // 实际使用的目录（绝对路径）
+    dir: String,
+}
+
+// 数据集目录：未设置时为项目根目录下的 dataset，相对路径同样相对于项目根目录
+fn resolve_dataset_dir(dir: Option<&str>) -> PathBuf {
+    let root = find_project_root_for_config();
+    match dir.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(d) => root.join(d),
+        None => root.join("dataset"),
+    }
+}
+
+// 校验目录可写：不存在时创建，并尝试写入/删除一个探测文件
+fn ensure_dir_writable(dir: &std::path::Path) -> Result<(), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("创建目录 {} 失败: {}", dir.display(), e))?;
+    let probe = dir.join(".write_test");
+    fs::write(&probe, b"ok").map_err(|e| format!("目录 {} 不可写: {}", dir.display(), e))?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+// Tauri命令：获取数据集采集设置
+#[tauri::command]
+fn get_dataset_collection() -> DatasetCollectionInfo {
+    let settings = load_ui_settings();
+    DatasetCollectionInfo {
+        enabled: settings.dataset_collection,
+        dir: resolve_dataset_dir(settings.dataset_dir.as_deref()).to_string_lossy().to_string(),
+    }
+}
+
+// Tauri命令：启用/停用数据集采集，持久化并通知桥接（桥接未就绪时待 bridge_ready 后下发）
+#[tauri::command]
+async fn set_dataset_collection(app: tauri::AppHandle, enabled: bool, dir: Option<String>) -> Result<DatasetCollectionInfo, String> {
+    let dir_setting = dir.map(|d| d.trim().to_string()).filter(|d| !d.is_empty());
+    let resolved = resolve_dataset_dir(dir_setting.as_deref());
+    if enabled {
+        ensure_dir_writable(&resolved)?;
+    }
+
+    let mut settings = load_ui_settings();
+    settings.dataset_collection = enabled;
+    settings.dataset_dir = dir_setting;
+    save_ui_settings(&settings)?;
+
+    let info = DatasetCollectionInfo { enabled, dir: resolved.to_string_lossy().to_string() };
+    let payload = serde_json::json!({"cmd": "set_dataset", "enabled": enabled, "dir": info.dir});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        info!("[tauri] {}", e);
+    }
+    Ok(info)
+}
+
+// Tauri命令：列出可用的录音输入设备（向桥接请求并等待 device_list 应答）
+#[tauri::command]
+async fn list_audio_devices(app: tauri::AppHandle) -> Result<Vec<AudioDevice>, String> {
+    let reply = bridge_request(&app, serde_json::json!({"cmd": "list_devices"})).await?;
+    if reply.get("event").and_then(|v| v.as_str()) != Some("device_list") {
+        let message = reply.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+        return Err(format!("获取设备列表失败: {}", message));
+    }
+    let devices = reply.get("devices").cloned().unwrap_or(Value::Array(Vec::new()));
+    serde_json::from_value(devices).map_err(|e| format!("设备列表格式无效: {}", e))
+}
+
+// Tauri命令：获取已保存的录音设备（None 表示系统默认）
+#[tauri::command]
+fn get_audio_device() -> Option<String> {
+    load_ui_settings().audio_device
+}
+
+// Tauri命令：选择录音输入设备（空字符串恢复系统默认）；设置会持久化，桥接重启后自动重新应用
+#[tauri::command]
+async fn set_audio_device(app: tauri::AppHandle, id: String) -> Result<(), String> {
+    let trimmed = id.trim();
+    let device = if trimmed.is_empty() { None } else { Some(trimmed.to_string()) };
+    let mut settings = load_ui_settings();
+    settings.audio_device = device.clone();
+    save_ui_settings(&settings)?;
+
+    let payload = serde_json::json!({"cmd": "set_device", "id": device});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        // 桥接未就绪时仅保存，待 bridge_ready 后自动下发
+        info!("[tauri] {}", e);
+    }
+    Ok(())
+}
+
+// Tauri命令：获取文本输出方式（未设置时返回桥接实际使用的默认值 auto）
+#[tauri::command]
+fn get_output_method() -> String {
+    load_ui_settings()
+        .output_method
+        .unwrap_or_else(|| OUTPUT_METHOD_AUTO.to_string())
+}
+
+// Tauri命令：设置文本输出方式（auto / type / clipboard，auto 恢复桥接配置默认值），持久化并通知桥接
+#[tauri::command]
+async fn set_output_method(app: tauri::AppHandle, method: String) -> Result<(), String> {
+    let method = method.trim().to_lowercase();
+    if method != OUTPUT_METHOD_AUTO && method != OUTPUT_METHOD_TYPE && method != OUTPUT_METHOD_CLIPBOARD {
+        return Err(format!(
+            "无效的输出方式: {}（可选 {} / {} / {}）",
+            method, OUTPUT_METHOD_AUTO, OUTPUT_METHOD_TYPE, OUTPUT_METHOD_CLIPBOARD
+        ));
+    }
+    let mut settings = load_ui_settings();
+    // auto 表示不覆盖桥接配置：清空保存值，桥接收到 auto 后恢复配置文件中的输出方式
+    settings.output_method = (method != OUTPUT_METHOD_AUTO).then(|| method.clone());
+    save_ui_settings(&settings)?;
+
+    let payload = serde_json::json!({"cmd": "set_output_method", "method": method});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        // 桥接未就绪时仅保存，待 bridge_ready 后自动下发
+        info!("[tauri] {}", e);
+    }
+    Ok(())
+}
+
+// Tauri命令：获取结尾裁剪模式
+#[tauri::command]
+fn get_trim_trailing() -> String {
+    load_ui_settings().trim_trailing
+}
+
+// Tauri命令：设置结尾裁剪模式（保存并下发给桥接，由桥接在输出前裁剪并上报裁剪后的文本）
+#[tauri::command]
+async fn set_trim_trailing(app: tauri::AppHandle, mode: String) -> Result<(), String> {
+    let mode = mode.trim().to_lowercase();
+    if mode != TRIM_TRAILING_NONE && mode != TRIM_TRAILING_WHITESPACE && mode != TRIM_TRAILING_PUNCTUATION {
+        return Err(format!(
+            "无效的裁剪模式: {}（可选 {} / {} / {}）",
+            mode, TRIM_TRAILING_NONE, TRIM_TRAILING_WHITESPACE, TRIM_TRAILING_PUNCTUATION
+        ));
+    }
+    let mut settings = load_ui_settings();
+    settings.trim_trailing = mode.clone();
+    save_ui_settings(&settings)?;
+
+    let payload = serde_json::json!({"cmd": "set_trim", "mode": mode});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        // 桥接未就绪时仅保存，待 bridge_ready 后自动下发
+        info!("[tauri] {}", e);
+    }
+    Ok(())
+}
+
+// Tauri命令：获取静音检测参数
+#[tauri::command]
+fn get_vad_settings() -> VadSettings {
+    load_ui_settings().vad
+}
+
+// 设置静音检测参数的结果：supported 为 false 表示桥接未应用这些参数（仅保存备用）
+#[derive(Debug, Clone, Copy, Serialize)]
+struct VadUpdateResult {
+    #[serde(flatten)]
+    vad: VadSettings,
+    supported: bool,
+}
+
+// Tauri命令：设置静音检测参数（校验范围后保存并下发给桥接，通知前端当前灵敏度）
+// 当前桥接尚未实现静音自动停止，会以 invalid_command（vad_unsupported）回复，参数仅保存备用
+#[tauri::command]
+async fn set_vad_settings(app: tauri::AppHandle, vad: VadSettings) -> Result<VadUpdateResult, String> {
+    vad.validate()?;
+    let mut settings = load_ui_settings();
+    settings.vad = vad;
+    save_ui_settings(&settings)?;
+
+    let supported = match bridge_request(&app, vad.bridge_payload()).await {
+        Ok(reply) => match reply.get("event").and_then(|v| v.as_str()) {
+            Some("invalid_command") => {
+                let message = reply.get("message").and_then(|v| v.as_str()).unwrap_or("");
+                info!("[tauri] 桥接未应用静音检测参数: {}", message);
+                false
+            }
+            _ => true,
+        },
+        Err(e) => {
+            // 桥接未就绪时无法确认是否生效，按不支持处理
+            info!("[tauri] {}", e);
+            false
+        }
+    };
+    let result = VadUpdateResult { vad, supported };
+    let _ = app.emit("vad-updated", result);
+    Ok(result)
+}
+
+// 当前识别器是否会产生中间识别结果（partial_transcription）
+const PARTIALS_SUPPORTED: bool = false;
+
+// Tauri命令：是否会显示中间识别结果（当前识别器不支持中间结果，始终为 false，前端据此不提供开关）
+#[tauri::command]
+fn get_show_partials() -> bool {
+    PARTIALS_SUPPORTED && load_ui_settings().show_partials
+}
+
+// Tauri命令：设置是否显示中间识别结果（仅保存偏好并下发给桥接；当前识别器不产生中间结果，暂不生效）
+#[tauri::command]
+async fn set_show_partials(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.show_partials = enabled;
+    save_ui_settings(&settings)?;
+
+    let payload = serde_json::json!({"cmd": "set_partials", "enabled": enabled});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        // 桥接未就绪时仅保存，待 bridge_ready 后自动下发
+        info!("[tauri] {}", e);
+    }
+    Ok(())
+}
+
+// Tauri命令：是否启用语音格式指令
+#[tauri::command]
+fn get_voice_formatting() -> bool {
+    load_ui_settings().voice_formatting
+}
+
+// Tauri命令：设置是否启用语音格式指令（保存并下发给桥接，下一次转写结果生效）
+#[tauri::command]
+async fn set_voice_formatting(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.voice_formatting = enabled;
+    save_ui_settings(&settings)?;
+
+    let payload = serde_json::json!({"cmd": "set_voice_formatting", "enabled": enabled});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        // 桥接未就绪时仅保存，待 bridge_ready 后自动下发
+        info!("[tauri] {}", e);
+    }
+    Ok(())
+}
+
+// Tauri命令：获取模拟键入的字符间隔（毫秒，0 表示尽快输出）
+#[tauri::command]
+fn get_type_delay() -> u64 {
+    load_ui_settings().type_delay_ms
+}
+
+// Tauri命令：设置模拟键入的字符间隔（0 ~ MAX_TYPE_DELAY_MS 毫秒，0 表示尽快输出），保存并下发给桥接
+#[tauri::command]
+async fn set_type_delay(app: tauri::AppHandle, delay_ms: u64) -> Result<(), String> {
+    if delay_ms > MAX_TYPE_DELAY_MS {
+        return Err(format!("键入间隔需在 0 ~ {} 毫秒之间", MAX_TYPE_DELAY_MS));
+    }
+    let mut settings = load_ui_settings();
+    settings.type_delay_ms = delay_ms;
+    save_ui_settings(&settings)?;
+
+    let payload = serde_json::json!({"cmd": "set_type_delay", "delay_ms": delay_ms});
+    if let Err(e) = send_bridge_payload(&app, payload).await {
+        // 桥接未就绪时仅保存，待 bridge_ready 后自动下发
+        info!("[tauri] {}", e);
+    }
+    Ok(())
+}
+
+// 播放录音开始/结束提示音（在独立线程中播放，失败仅记录日志）
+fn play_feedback_sound(app: &tauri::AppHandle, recording: bool) {
+    if !load_ui_settings().sound_feedback {
+        return;
+    }
+    let rel = if recording { SOUND_RECORD_START } else { SOUND_RECORD_STOP };
+    let path = match app.path().resolve(rel, BaseDirectory::Resource) {
+        Ok(path) => path,
+        Err(e) => {
+            warn!("解析提示音路径失败 {}: {}", rel, e);
+            return;
+        }
+    };
+    std::thread::spawn(move || {
+        let play = || -> Result<(), String> {
+            let (_stream, handle) = rodio::OutputStream::try_default().map_err(|e| e.to_string())?;
+            let sink = rodio::Sink::try_new(&handle).map_err(|e| e.to_string())?;
+            let file = fs::File::open(&path).map_err(|e| e.to_string())?;
+            let source = rodio::Decoder::new(std::io::BufReader::new(file)).map_err(|e| e.to_string())?;
+            sink.append(source);
+            sink.sleep_until_end();
+            Ok(())
+        };
+        if let Err(e) = play() {
+            warn!("播放提示音失败 {}: {}", path.display(), e);
+        }
+    });
+}
+
+// Tauri命令：获取是否启用提示音
+#[tauri::command]
+fn get_sound_feedback() -> bool {
+    load_ui_settings().sound_feedback
+}
+
+// Tauri命令：启用/关闭录音提示音
+#[tauri::command]
+fn set_sound_feedback(enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.sound_feedback = enabled;
+    save_ui_settings(&settings)
+}
+
+// Tauri命令：录音期间是否阻止系统休眠
+#[tauri::command]
+fn get_prevent_sleep() -> bool {
+    load_ui_settings().prevent_sleep
+}
+
+// Tauri命令：设置录音期间阻止休眠；录音进行中时立即生效
+#[tauri::command]
+fn set_prevent_sleep(app: tauri::AppHandle, state: State<'_, AppState>, enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.prevent_sleep = enabled;
+    save_ui_settings(&settings)?;
+    let recording = *state.is_recording.lock().unwrap();
+    update_sleep_inhibit(&app, recording);
+    Ok(())
+}
+
+// 防休眠请求：Windows 上 SetThreadExecutionState 按线程生效，
+// 因此由专用线程持有，释放（或句柄析构）时该线程恢复默认状态后退出
+struct SleepInhibitor {
+    release_tx: std::sync::mpsc::Sender<()>,
+}
+
+impl SleepInhibitor {
+    #[cfg(windows)]
+    fn acquire() -> Option<Self> {
+        use windows::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED};
+        let (release_tx, release_rx) = std::sync::mpsc::channel::<()>();
+        let (ready_tx, ready_rx) = std::sync::mpsc::channel::<bool>();
+        std::thread::spawn(move || {
+            let previous = unsafe { SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED) };
+            let _ = ready_tx.send(previous.0 != 0);
+            // 收到释放信号或发送端被丢弃时都会返回
+            let _ = release_rx.recv();
+            unsafe { SetThreadExecutionState(ES_CONTINUOUS) };
+        });
+        match ready_rx.recv() {
+            Ok(true) => Some(SleepInhibitor { release_tx }),
+            _ => {
+                warn!("[tauri] 设置防休眠失败");
+                None
+            }
+        }
+    }
+
+    #[cfg(not(windows))]
+    fn acquire() -> Option<Self> {
+        debug!("[tauri] 当前平台未实现防休眠，忽略");
+        None
+    }
+}
+
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        let _ = self.release_tx.send(());
+    }
+}
+
+// 按录音状态获取或释放防休眠：仅在录音中且启用 prevent_sleep 时持有，其余情况一律释放
+fn update_sleep_inhibit(app: &tauri::AppHandle, recording: bool) {
+    let state = app.state::<AppState>();
+    let mut guard = state.sleep_inhibitor.lock().unwrap();
+    let wanted = recording && load_ui_settings().prevent_sleep;
+    if wanted && guard.is_none() {
+        *guard = SleepInhibitor::acquire();
+        if guard.is_some() {
+            info!("[tauri] 录音中，已阻止系统休眠");
+        }
+    } else if !wanted && guard.take().is_some() {
+        info!("[tauri] 已恢复系统休眠");
+    }
+}
+
+// 录音开始时启动超时计时：到时仍在录音则自动发送 stop 并通知前端
+fn arm_max_recording_timer(app: &tauri::AppHandle) {
+    let generation = app.state::<AppState>().max_recording_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let secs = load_ui_settings().max_recording_secs;
+    if secs == 0 {
+        return;
+    }
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(secs.min(MAX_MAX_RECORDING_SECS))).await;
+        {
+            let state = app.state::<AppState>();
+            if state.max_recording_generation.load(Ordering::SeqCst) != generation || !*state.is_recording.lock().unwrap() {
+                return;
+            }
+        }
+        info!("[tauri] 录音超过 {} 秒，自动停止", secs);
+        if let Err(err) = send_bridge_command(&app, "stop").await {
+            warn!("[tauri] 录音超时自动停止失败: {}", err);
+            return;
+        }
+        let _ = app.emit("recording-auto-stopped", serde_json::json!({ "max_recording_secs": secs }));
+    });
+}
+
+// 作废尚未触发的录音超时计时（正常停止或桥接退出时）
+fn disarm_max_recording_timer(app: &tauri::AppHandle) {
+    app.state::<AppState>().max_recording_generation.fetch_add(1, Ordering::SeqCst);
+}
+
+// Tauri命令：获取单次录音最长时长（秒，0 表示不限制）
+#[tauri::command]
+fn get_max_recording_secs() -> u64 {
+    load_ui_settings().max_recording_secs
+}
+
+// Tauri命令：设置单次录音最长时长；录音进行中时从现在起重新计时
+#[tauri::command]
+fn set_max_recording_secs(app: tauri::AppHandle, state: State<'_, AppState>, secs: u64) -> Result<(), String> {
+    if secs != 0 && !(MIN_MAX_RECORDING_SECS..=MAX_MAX_RECORDING_SECS).contains(&secs) {
+        return Err(format!(
+            "录音最长时长无效：{} 秒（允许 {} ~ {} 秒，0 表示不限制）",
+            secs, MIN_MAX_RECORDING_SECS, MAX_MAX_RECORDING_SECS
+        ));
+    }
+    let mut settings = load_ui_settings();
+    settings.max_recording_secs = secs;
+    save_ui_settings(&settings)?;
+    if *state.is_recording.lock().unwrap() {
+        arm_max_recording_timer(&app);
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ModelInfo {
+    name: String,
+    #[serde(default)]
+    active: bool,
+}
+
+// Tauri命令：列出可用的转写模型（由桥接返回）
+#[tauri::command]
+async fn get_available_models(app: tauri::AppHandle) -> Result<Vec<ModelInfo>, String> {
+    let reply = bridge_request(&app, serde_json::json!({"cmd": "list_models"})).await?;
+    if reply.get("event").and_then(|v| v.as_str()) != Some("model_list") {
+        let message = reply.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+        return Err(format!("获取模型列表失败: {}", message));
+    }
+    let models = reply.get("models").cloned().unwrap_or(Value::Array(Vec::new()));
+    serde_json::from_value(models).map_err(|e| format!("模型列表格式无效: {}", e))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LanguageInfo {
+    code: String,
+    name: String,
+}
+
+// Tauri命令：列出当前模型支持的转写语言（按模型缓存，避免重复往返桥接）
+#[tauri::command]
+async fn list_languages(app: tauri::AppHandle) -> Result<Vec<LanguageInfo>, String> {
+    // 缓存以桥接回复中的模型为键：切换模型后重启期间旧桥接仍可能应答，其结果不能记到新模型名下。
+    // 未选择模型（桥接默认模型）时无法预知模型名，接受任意缓存
+    let wanted = load_ui_settings().model.trim().to_string();
+    let matches_wanted = |model: &str| wanted.is_empty() || model == wanted;
+    if let Some((cached_model, languages)) = app.state::<AppState>().language_cache.lock().unwrap().as_ref() {
+        if matches_wanted(cached_model) {
+            return Ok(languages.clone());
+        }
+    }
+
+    let reply = bridge_request(&app, serde_json::json!({"cmd": "list_languages"}))
+        .await
+        .map_err(|e| format!("获取语言列表失败（桥接无应答）: {}", e))?;
+    match reply.get("event").and_then(|v| v.as_str()) {
+        Some("language_list") => {}
+        Some("invalid_command") => return Err("当前桥接版本不支持查询语言列表，请更新桥接程序".to_string()),
+        _ => {
+            let message = reply.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+            return Err(format!("获取语言列表失败: {}", message));
+        }
+    }
+    let languages = reply.get("languages").cloned().unwrap_or(Value::Array(Vec::new()));
+    let languages: Vec<LanguageInfo> =
+        serde_json::from_value(languages).map_err(|e| format!("语言列表格式无效: {}", e))?;
+
+    let model = reply.get("model").and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    if matches_wanted(&model) {
+        *app.state::<AppState>().language_cache.lock().unwrap() = Some((model, languages.clone()));
+    }
+    Ok(languages)
+}
+
+// Tauri命令：获取已选择的转写模型（空字符串表示桥接默认模型）
+#[tauri::command]
+fn get_active_model() -> String {
+    load_ui_settings().model
+}
+
+// Tauri命令：切换转写模型（持久化后重启桥接以加载新模型）
+#[tauri::command]
+async fn set_active_model(app: tauri::AppHandle, name: String) -> Result<(), String> {
+    let name = name.trim().to_string();
+    let mut settings = load_ui_settings();
+    if settings.model == name {
+        return Ok(());
+    }
+    settings.model = name.clone();
+    save_ui_settings(&settings)?;
+    *app.state::<AppState>().language_cache.lock().unwrap() = None;
+    let _ = app.emit("model-changed", serde_json::json!({ "model": name }));
+
+    info!("[tauri] 转写模型已切换为 {}，重启桥接进程", if name.is_empty() { "默认模型" } else { &name });
+    restart_bridge(app.clone(), app.state()).await
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct TranscriptionBenchmark {
+    model: String,
+    // 模型加载耗时（桥接启动时测得）、本次推理耗时与两者之和（秒）
+    load_secs: f64,
+    inference_secs: f64,
+    total_secs: f64,
+    // 命令发出到收到结果的耗时（含进程间通信）
+    round_trip_secs: f64,
+    text: String,
+    timestamp: String,
+}
+
+// Tauri命令：用内置测试音频测量一次端到端转写耗时，结果保存供界面展示
+#[tauri::command]
+async fn benchmark_transcription(app: tauri::AppHandle) -> Result<TranscriptionBenchmark, String> {
+    if *app.state::<AppState>().is_recording.lock().unwrap() {
+        return Err("录音进行中，无法进行性能测试".to_string());
+    }
+    let clip = app
+        .path()
+        .resolve(BENCHMARK_CLIP, BaseDirectory::Resource)
+        .map_err(|e| format!("解析测试音频路径失败: {}", e))?;
+    if !clip.exists() {
+        return Err(format!("测试音频不存在: {}", clip.display()));
+    }
+
+    let started = Instant::now();
+    let reply = bridge_request_with_timeout(
+        &app,
+        serde_json::json!({"cmd": "benchmark", "path": clip.to_string_lossy()}),
+        BENCHMARK_TIMEOUT,
+    )
+    .await?;
+    let round_trip_secs = started.elapsed().as_secs_f64();
+    match reply.get("event").and_then(|v| v.as_str()) {
+        Some("benchmark_result") => {}
+        Some("invalid_command") => return Err("当前桥接版本不支持性能测试，请更新桥接程序".to_string()),
+        _ => {
+            let message = reply.get("message").and_then(|v| v.as_str()).unwrap_or("未知错误");
+            return Err(format!("性能测试失败: {}", message));
+        }
+    }
+
+    let secs = |key: &str| reply.get(key).and_then(|v| v.as_f64()).unwrap_or(0.0);
+    let result = TranscriptionBenchmark {
+        model: reply.get("model").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        load_secs: secs("load_seconds"),
+        inference_secs: secs("inference_seconds"),
+        total_secs: secs("total_seconds"),
+        round_trip_secs,
+        text: reply.get("text").and_then(|v| v.as_str()).unwrap_or_default().to_string(),
+        timestamp: Local::now().to_rfc3339(),
+    };
+    info!(
+        "[tauri] 性能测试完成（{}）：推理 {:.3}s，往返 {:.3}s",
+        result.model, result.inference_secs, result.round_trip_secs
+    );
+    *app.state::<AppState>().last_benchmark.lock().unwrap() = Some(result.clone());
+    Ok(result)
+}
+
+// Tauri命令：获取最近一次性能测试结果（尚未测试时为 None）
+#[tauri::command]
+fn get_last_benchmark(state: tauri::State<'_, AppState>) -> Option<TranscriptionBenchmark> {
+    state.last_benchmark.lock().unwrap().clone()
+}
+
+// Tauri命令：获取录音状态
+#[tauri::command]
+fn get_recording_state(state: tauri::State<'_, AppState>) -> bool {
+    *state.is_recording.lock().unwrap()
+}
+
+#[derive(Clone, Serialize)]
+struct RecordingSnapshot {
+    is_recording: bool,
+    is_paused: bool,
+}
+
+// Tauri命令：获取录音状态快照（含暂停状态）
+#[tauri::command]
+fn get_recording_snapshot(state: tauri::State<'_, AppState>) -> RecordingSnapshot {
+    RecordingSnapshot {
+        is_recording: *state.is_recording.lock().unwrap(),
+        is_paused: *state.is_paused.lock().unwrap(),
+    }
+}
+
+// Tauri命令：暂停录音（保留本次会话，暂停期间的音频被丢弃；状态以 recording_paused 事件为准）
+#[tauri::command]
+async fn pause_recording(app: tauri::AppHandle) -> Result<(), String> {
+    let recording = { *app.state::<AppState>().is_recording.lock().unwrap() };
+    if !recording {
+        return Err("当前未在录音，无法暂停".to_string());
+    }
+    send_bridge_command(&app, "pause").await
+}
+
+// Tauri命令：继续录音
+#[tauri::command]
+async fn resume_recording(app: tauri::AppHandle) -> Result<(), String> {
+    let paused = { *app.state::<AppState>().is_paused.lock().unwrap() };
+    if !paused {
+        return Err("当前未暂停，无需继续".to_string());
+    }
+    send_bridge_command(&app, "resume").await
+}
+
+fn set_paused_state(app: &tauri::AppHandle, paused: bool) {
+    let changed = {
+        let app_state = app.state::<AppState>();
+        let mut guard = app_state.is_paused.lock().unwrap();
+        let changed = *guard != paused;
+        *guard = paused;
+        changed
+    };
+    if changed {
+        let _ = app.emit("recording-paused", paused);
+    }
+}
+
+#[derive(Clone, Serialize)]
+struct RecordingHotkeyInfo {
+    current: String,
+}
+
+#[derive(Deserialize)]
+struct SetRecordingHotkeyPayload {
+    hotkey: String,
+}
+
+#[tauri::command]
+fn get_recording_hotkey(app: tauri::AppHandle, state: State<'_, AppState>) -> Result<RecordingHotkeyInfo, String> {
+    let current = {
+        let guard = state.recording_hotkey.lock().map_err(|e| format!("获取当前快捷键失败: {}", e))?;
+        guard.clone()
+    };
+
+    if current.is_empty() {
+        return Ok(RecordingHotkeyInfo {
+            current: DEFAULT_RECORDING_HOTKEY.to_string(),
+        });
+    }
+
+    // 确保已注册（处理第一次启动时未注册的情况）
+    if let Err(err) = register_recording_hotkey(&app, &current) {
+        warn!("当前快捷键注册失败 {}，将尝试回退默认值: {}", current, err);
+        register_recording_hotkey(&app, DEFAULT_RECORDING_HOTKEY)?;
+        return Ok(RecordingHotkeyInfo {
+            current: DEFAULT_RECORDING_HOTKEY.to_string(),
+        });
+    }
+
+    Ok(RecordingHotkeyInfo { current })
+}
+
+#[tauri::command]
+fn set_recording_hotkey(app: tauri::AppHandle, payload: SetRecordingHotkeyPayload, state: State<'_, AppState>) -> Result<bool, String> {
+    let new_hotkey = payload.hotkey.trim();
+    if new_hotkey.is_empty() {
+        return Err(t("hotkey_empty"));
+    }
+
+    // 单独的修饰键按双击触发（仅支持的平台）；单次按下不会触发录音
+    if DoubleTapModifier::parse(new_hotkey).is_some() && !DOUBLE_TAP_SUPPORTED {
+        return Err(t("hotkey_needs_key"));
+    }
+
+    // 与其他快捷键（开始/停止/取消）不能重复
+    {
+        let mut bindings = HotkeyBindings::from_state(&state);
+        bindings.recording = new_hotkey.to_string();
+        bindings.check_conflicts()?;
+    }
+
+    register_recording_hotkey(&app, new_hotkey)?;
+
+    // 写入配置
+    let mut settings = load_ui_settings();
+    settings.recording_hotkey = new_hotkey.to_string();
+    sync_active_hotkey_profile(&mut settings);
+    save_ui_settings(&settings)?;
+
+    // 更新状态
+    {
+        let mut guard = state.recording_hotkey.lock().map_err(|e| tf("hotkey_state_failed", &[&e]))?;
+        *guard = new_hotkey.to_string();
+    }
+    // 用户重新设置成功后，启动时的回退提示不再适用
+    if let Ok(mut guard) = state.hotkey_registration_error.lock() {
+        *guard = None;
+    }
+
+    // 广播给前端：快捷键已更新（优先发到 widget 窗口，找不到则广播全局）
+    debug!("[rust] 准备发送 recording-hotkey-updated 事件，新快捷键: {}", new_hotkey);
+    if let Some(win) = app.get_webview_window("widget") {
+        debug!("[rust] 找到widget窗口，向其发送事件");
+        match win.emit("recording-hotkey-updated", new_hotkey.to_string()) {
+            Ok(_) => debug!("[rust] 事件发送成功"),
+            Err(e) => warn!("[rust] 事件发送失败: {}", e),
+        }
+    } else {
+        debug!("[rust] 未找到widget窗口，尝试全局广播");
+        match app.emit("recording-hotkey-updated", new_hotkey.to_string()) {
+            Ok(_) => debug!("[rust] 全局事件发送成功"),
+            Err(e) => warn!("[rust] 全局事件发送失败: {}", e),
+        }
+    }
+
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_hotkey_mode(state: State<'_, AppState>) -> Result<String, String> {
+    let guard = state.hotkey_mode.lock().map_err(|e| format!("获取快捷键模式失败: {}", e))?;
+    if guard.is_empty() {
+        return Ok(default_hotkey_mode());
+    }
+    Ok(guard.clone())
+}
+
+#[tauri::command]
+fn set_hotkey_mode(app: tauri::AppHandle, mode: String, state: State<'_, AppState>) -> Result<bool, String> {
+    let mode = mode.trim().to_ascii_lowercase();
+    if mode != HOTKEY_MODE_TOGGLE && mode != HOTKEY_MODE_PUSH_TO_TALK {
+        return Err(format!("不支持的快捷键模式: {}（可选 toggle / push_to_talk）", mode));
+    }
+
+    let mut settings = load_ui_settings();
+    settings.hotkey_mode = mode.clone();
+    save_ui_settings(&settings)?;
+
+    {
+        let mut guard = state.hotkey_mode.lock().map_err(|e| format!("更新快捷键模式失败: {}", e))?;
+        *guard = mode.clone();
+    }
+    // 切换模式时清理按住说话的残留状态
+    if let Ok(mut active) = state.ptt_active.lock() {
+        *active = false;
+    }
+    if let Ok(mut down) = state.hotkey_down.lock() {
+        *down = false;
+    }
+
+    let _ = app.emit("hotkey-mode-updated", mode);
+    Ok(true)
+}
+
+#[derive(Clone, Serialize)]
+struct StartStopHotkeysInfo {
+    start: Option<String>,
+    stop: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SetStartStopHotkeysPayload {
+    #[serde(default)]
+    start: Option<String>,
+    #[serde(default)]
+    stop: Option<String>,
+}
+
+fn non_empty_hotkey(hotkey: Option<&str>) -> Option<String> {
+    hotkey.map(|k| k.trim()).filter(|k| !k.is_empty()).map(|k| k.to_string())
+}
+
+#[tauri::command]
+fn get_start_stop_hotkeys(state: State<'_, AppState>) -> Result<StartStopHotkeysInfo, String> {
+    let start = state.start_hotkey.lock().map_err(|e| format!("获取开始快捷键失败: {}", e))?.clone();
+    let stop = state.stop_hotkey.lock().map_err(|e| format!("获取停止快捷键失败: {}", e))?.clone();
+    Ok(StartStopHotkeysInfo { start, stop })
+}
+
+// Tauri命令：启动时录音快捷键注册失败的原因（已回退为默认快捷键）；None 表示配置的快捷键已生效
+#[tauri::command]
+fn get_hotkey_registration_error(state: State<'_, AppState>) -> Option<String> {
+    state.hotkey_registration_error.lock().ok().and_then(|g| g.clone())
+}
+
+// 设置独立的开始/停止快捷键（传空字符串或 null 表示停用）
+#[tauri::command]
+fn set_start_stop_hotkeys(app: tauri::AppHandle, payload: SetStartStopHotkeysPayload, state: State<'_, AppState>) -> Result<bool, String> {
+    let start = non_empty_hotkey(payload.start.as_deref());
+    let stop = non_empty_hotkey(payload.stop.as_deref());
+
+    let mut bindings = HotkeyBindings::from_state(&state);
+    bindings.start = start.clone();
+    bindings.stop = stop.clone();
+    apply_hotkey_bindings(&app, &bindings)?;
+
+    let mut settings = load_ui_settings();
+    settings.start_hotkey = start.clone();
+    settings.stop_hotkey = stop.clone();
+    sync_active_hotkey_profile(&mut settings);
+    save_ui_settings(&settings)?;
+
+    let _ = app.emit("start-stop-hotkeys-updated", StartStopHotkeysInfo { start, stop });
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_cancel_hotkey(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let guard = state.cancel_hotkey.lock().map_err(|e| format!("获取取消快捷键失败: {}", e))?;
+    Ok(guard.clone())
+}
+
+#[tauri::command]
+fn get_retype_hotkey(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let guard = state.retype_hotkey.lock().map_err(|e| format!("获取重新输出快捷键失败: {}", e))?;
+    Ok(guard.clone())
+}
+
+// 设置重新输出快捷键（传空字符串或 null 表示停用）
+#[tauri::command]
+fn set_retype_hotkey(app: tauri::AppHandle, hotkey: Option<String>, state: State<'_, AppState>) -> Result<bool, String> {
+    let retype = non_empty_hotkey(hotkey.as_deref());
+
+    let mut bindings = HotkeyBindings::from_state(&state);
+    bindings.retype = retype.clone();
+    apply_hotkey_bindings(&app, &bindings)?;
+
+    let mut settings = load_ui_settings();
+    settings.retype_hotkey = retype.clone();
+    save_ui_settings(&settings)?;
+
+    let _ = app.emit("retype-hotkey-updated", retype);
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_widget_toggle_hotkey(state: State<'_, AppState>) -> Result<Option<String>, String> {
+    let guard = state.widget_toggle_hotkey.lock().map_err(|e| format!("获取悬浮窗快捷键失败: {}", e))?;
+    Ok(guard.clone())
+}
+
+// 设置显示/隐藏悬浮窗快捷键（传空字符串或 null 表示停用）
+#[tauri::command]
+fn set_widget_toggle_hotkey(app: tauri::AppHandle, hotkey: Option<String>, state: State<'_, AppState>) -> Result<bool, String> {
+    let widget_toggle = non_empty_hotkey(hotkey.as_deref());
+
+    let mut bindings = HotkeyBindings::from_state(&state);
+    bindings.widget_toggle = widget_toggle.clone();
+    apply_hotkey_bindings(&app, &bindings)?;
+
+    let mut settings = load_ui_settings();
+    settings.widget_toggle_hotkey = widget_toggle.clone();
+    save_ui_settings(&settings)?;
+
+    let _ = app.emit("widget-toggle-hotkey-updated", widget_toggle);
+    Ok(true)
+}
+
+// 设置取消录音快捷键（传空字符串或 null 表示停用）
+#[tauri::command]
+fn set_cancel_hotkey(app: tauri::AppHandle, hotkey: Option<String>, state: State<'_, AppState>) -> Result<bool, String> {
+    let cancel = non_empty_hotkey(hotkey.as_deref());
+
+    let mut bindings = HotkeyBindings::from_state(&state);
+    bindings.cancel = cancel.clone();
+    apply_hotkey_bindings(&app, &bindings)?;
+
+    let mut settings = load_ui_settings();
+    settings.cancel_hotkey = cancel.clone();
+    sync_active_hotkey_profile(&mut settings);
+    save_ui_settings(&settings)?;
+
+    let _ = app.emit("cancel-hotkey-updated", cancel);
+    Ok(true)
+}
+
+// -----------------------------
+// 快捷键方案：按名称保存多组录音/开始/停止/取消快捷键，运行时切换
+// -----------------------------
+
+const DEFAULT_HOTKEY_PROFILE: &str = "default";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+struct HotkeyProfile {
+    name: String,
+    recording_hotkey: String,
+    #[serde(default)]
+    start_hotkey: Option<String>,
+    #[serde(default)]
+    stop_hotkey: Option<String>,
+    #[serde(default)]
+    cancel_hotkey: Option<String>,
+}
+
+impl HotkeyProfile {
+    fn from_settings(name: &str, settings: &UiSettings) -> Self {
+        HotkeyProfile {
+            name: name.to_string(),
+            recording_hotkey: settings.recording_hotkey.clone(),
+            start_hotkey: settings.start_hotkey.clone(),
+            stop_hotkey: settings.stop_hotkey.clone(),
+            cancel_hotkey: settings.cancel_hotkey.clone(),
+        }
+    }
+}
+
+fn active_hotkey_profile_name(settings: &UiSettings) -> String {
+    settings
+        .active_hotkey_profile
+        .clone()
+        .filter(|name| settings.hotkey_profiles.iter().any(|p| &p.name == name))
+        .unwrap_or_else(|| DEFAULT_HOTKEY_PROFILE.to_string())
+}
+
+// 将顶层快捷键写回当前激活的方案（缺少默认方案时一并创建）
+fn sync_active_hotkey_profile(settings: &mut UiSettings) {
+    let active = active_hotkey_profile_name(settings);
+    let profile = HotkeyProfile::from_settings(&active, settings);
+    match settings.hotkey_profiles.iter_mut().find(|p| p.name == active) {
+        Some(existing) => *existing = profile,
+        None => settings.hotkey_profiles.insert(0, profile),
+    }
+    settings.active_hotkey_profile = Some(active);
+}
+
+// 缺少默认方案时由当前顶层快捷键生成（不改动已有方案）
+fn ensure_default_hotkey_profile(settings: &mut UiSettings) {
+    if !settings.hotkey_profiles.iter().any(|p| p.name == DEFAULT_HOTKEY_PROFILE) {
+        let default = HotkeyProfile::from_settings(DEFAULT_HOTKEY_PROFILE, settings);
+        settings.hotkey_profiles.insert(0, default);
+    }
+}
+
+// 新增或替换方案，返回其是否为当前激活方案
+fn upsert_hotkey_profile(settings: &mut UiSettings, profile: HotkeyProfile) -> bool {
+    ensure_default_hotkey_profile(settings);
+    let is_active = active_hotkey_profile_name(settings) == profile.name;
+    match settings.hotkey_profiles.iter_mut().find(|p| p.name == profile.name) {
+        Some(existing) => *existing = profile,
+        None => settings.hotkey_profiles.push(profile),
+    }
+    is_active
+}
+
+// 删除方案，返回其是否为激活方案；删除激活方案时激活指针回退到默认方案
+fn remove_hotkey_profile(settings: &mut UiSettings, name: &str) -> Result<bool, String> {
+    ensure_default_hotkey_profile(settings);
+    let was_active = active_hotkey_profile_name(settings) == name;
+    let before = settings.hotkey_profiles.len();
+    settings.hotkey_profiles.retain(|p| p.name != name);
+    if settings.hotkey_profiles.len() == before {
+        return Err(format!("快捷键方案不存在: {}", name));
+    }
+    if was_active {
+        settings.active_hotkey_profile = Some(DEFAULT_HOTKEY_PROFILE.to_string());
+    }
+    Ok(was_active)
+}
+
+// 将方案中的快捷键写入顶层配置并设为激活方案
+fn apply_hotkey_profile_to_settings(settings: &mut UiSettings, profile: &HotkeyProfile) {
+    settings.recording_hotkey = profile.recording_hotkey.clone();
+    settings.start_hotkey = profile.start_hotkey.clone();
+    settings.stop_hotkey = profile.stop_hotkey.clone();
+    settings.cancel_hotkey = profile.cancel_hotkey.clone();
+    settings.active_hotkey_profile = Some(profile.name.clone());
+}
+
+#[derive(Serialize)]
+struct HotkeyProfileList {
+    active: String,
+    profiles: Vec<HotkeyProfile>,
+}
+
+// Tauri命令：列出全部快捷键方案（首次调用时由当前快捷键生成默认方案）
+#[tauri::command]
+fn list_hotkey_profiles() -> Result<HotkeyProfileList, String> {
+    let mut settings = load_ui_settings();
+    if !settings.hotkey_profiles.iter().any(|p| p.name == DEFAULT_HOTKEY_PROFILE) {
+        ensure_default_hotkey_profile(&mut settings);
+        save_ui_settings(&settings)?;
+    }
+    Ok(HotkeyProfileList {
+        active: active_hotkey_profile_name(&settings),
+        profiles: settings.hotkey_profiles,
+    })
+}
+
+// Tauri命令：新增或更新快捷键方案；更新的是当前激活方案时立即重新注册
+#[tauri::command]
+fn save_hotkey_profile(app: tauri::AppHandle, profile: HotkeyProfile) -> Result<bool, String> {
+    let name = profile.name.trim().to_string();
+    if name.is_empty() {
+        return Err("方案名称不能为空".to_string());
+    }
+    let profile = HotkeyProfile {
+        name,
+        recording_hotkey: profile.recording_hotkey.trim().to_string(),
+        start_hotkey: non_empty_hotkey(profile.start_hotkey.as_deref()),
+        stop_hotkey: non_empty_hotkey(profile.stop_hotkey.as_deref()),
+        cancel_hotkey: non_empty_hotkey(profile.cancel_hotkey.as_deref()),
+    };
+    if profile.recording_hotkey.is_empty() {
+        return Err("快捷键不能为空".to_string());
+    }
+    let bindings = hotkey_bindings_for_profile(&app, &profile);
+    bindings.check_conflicts()?;
+
+    let mut settings = load_ui_settings();
+    let is_active = upsert_hotkey_profile(&mut settings, profile.clone());
+    save_ui_settings(&settings)?;
+
+    if is_active {
+        activate_hotkey_profile(app, profile.name)?;
+    }
+    Ok(true)
+}
+
+// Tauri命令：删除快捷键方案（默认方案不可删除）；删除的是激活方案时回退到默认方案
+#[tauri::command]
+fn delete_hotkey_profile(app: tauri::AppHandle, name: String) -> Result<bool, String> {
+    if name == DEFAULT_HOTKEY_PROFILE {
+        return Err("默认方案不可删除".to_string());
+    }
+    let mut settings = load_ui_settings();
+    let was_active = remove_hotkey_profile(&mut settings, &name)?;
+    save_ui_settings(&settings)?;
+
+    if was_active {
+        info!("已删除激活的快捷键方案 {}，回退到默认方案", name);
+        activate_hotkey_profile(app, DEFAULT_HOTKEY_PROFILE.to_string())?;
+    }
+    Ok(true)
+}
+
+fn hotkey_bindings_for_profile(app: &tauri::AppHandle, profile: &HotkeyProfile) -> HotkeyBindings {
+    let mut bindings = HotkeyBindings::from_state(&app.state::<AppState>());
+    bindings.recording = profile.recording_hotkey.clone();
+    bindings.start = profile.start_hotkey.clone();
+    bindings.stop = profile.stop_hotkey.clone();
+    bindings.cancel = profile.cancel_hotkey.clone();
+    bindings
+}
+
+// Tauri命令：激活快捷键方案：重新注册全部快捷键并持久化激活指针
+#[tauri::command]
+fn activate_hotkey_profile(app: tauri::AppHandle, name: String) -> Result<bool, String> {
+    let mut settings = load_ui_settings();
+    ensure_default_hotkey_profile(&mut settings);
+    let profile = settings
+        .hotkey_profiles
+        .iter()
+        .find(|p| p.name == name)
+        .cloned()
+        .ok_or_else(|| format!("快捷键方案不存在: {}", name))?;
+
+    let bindings = hotkey_bindings_for_profile(&app, &profile);
+    apply_hotkey_bindings(&app, &bindings)?;
+    {
+        let state = app.state::<AppState>();
+        let mut guard = state.recording_hotkey.lock().map_err(|e| format!("更新快捷键状态失败: {}", e))?;
+        *guard = profile.recording_hotkey.clone();
+    }
+
+    apply_hotkey_profile_to_settings(&mut settings, &profile);
+    save_ui_settings(&settings)?;
+
+    info!("已切换到快捷键方案 {}", profile.name);
+    let _ = app.emit("recording-hotkey-updated", profile.recording_hotkey.clone());
+    let _ = app.emit("start-stop-hotkeys-updated", StartStopHotkeysInfo {
+        start: profile.start_hotkey.clone(),
+        stop: profile.stop_hotkey.clone(),
+    });
+    let _ = app.emit("cancel-hotkey-updated", profile.cancel_hotkey.clone());
+    let _ = app.emit("hotkey-profile-activated", profile.name);
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_hotkey_debounce(state: State<'_, AppState>) -> Result<u64, String> {
+    let guard = state.hotkey_debounce_ms.lock().map_err(|e| format!("获取去抖间隔失败: {}", e))?;
+    Ok(*guard)
+}
+
+#[tauri::command]
+fn set_hotkey_debounce(ms: u64, state: State<'_, AppState>) -> Result<bool, String> {
+    if ms > MAX_HOTKEY_DEBOUNCE_MS {
+        return Err(format!("去抖间隔超出范围：{}ms（允许 0-{}ms）", ms, MAX_HOTKEY_DEBOUNCE_MS));
+    }
+
+    let mut settings = load_ui_settings();
+    settings.hotkey_debounce_ms = ms;
+    save_ui_settings(&settings)?;
+
+    let mut guard = state.hotkey_debounce_ms.lock().map_err(|e| format!("更新去抖间隔失败: {}", e))?;
+    *guard = ms;
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_min_recording_ms(state: State<'_, AppState>) -> Result<u64, String> {
+    let guard = state.min_recording_ms.lock().map_err(|e| format!("获取最短录音时长失败: {}", e))?;
+    Ok(*guard)
+}
+
+#[tauri::command]
+fn set_min_recording_ms(ms: u64, state: State<'_, AppState>) -> Result<bool, String> {
+    if ms > MAX_MIN_RECORDING_MS {
+        return Err(format!("最短录音时长超出范围：{}ms（允许 0-{}ms）", ms, MAX_MIN_RECORDING_MS));
+    }
+
+    let mut settings = load_ui_settings();
+    settings.min_recording_ms = ms;
+    save_ui_settings(&settings)?;
+
+    let mut guard = state.min_recording_ms.lock().map_err(|e| format!("更新最短录音时长失败: {}", e))?;
+    *guard = ms;
+    Ok(true)
+}
+
+#[tauri::command]
+fn get_double_tap_window(state: State<'_, AppState>) -> Result<u64, String> {
+    let guard = state.double_tap_window_ms.lock().map_err(|e| format!("获取双击间隔失败: {}", e))?;
+    Ok(*guard)
+}
+
+#[tauri::command]
+fn set_double_tap_window(ms: u64, state: State<'_, AppState>) -> Result<bool, String> {
+    if !(MIN_DOUBLE_TAP_WINDOW_MS..=MAX_DOUBLE_TAP_WINDOW_MS).contains(&ms) {
+        return Err(format!(
+            "双击间隔超出范围：{}ms（允许 {}-{}ms）",
+            ms, MIN_DOUBLE_TAP_WINDOW_MS, MAX_DOUBLE_TAP_WINDOW_MS
+        ));
+    }
+
+    let mut settings = load_ui_settings();
+    settings.double_tap_window_ms = ms;
+    save_ui_settings(&settings)?;
+
+    let mut guard = state.double_tap_window_ms.lock().map_err(|e| format!("更新双击间隔失败: {}", e))?;
+    *guard = ms;
+    Ok(true)
+}
+
+#[derive(Clone, Serialize)]
+struct HotkeyProbeResult {
+    // available / parse_error / already_registered
+    status: String,
+    hotkey: String,
+    message: Option<String>,
+}
+
+impl HotkeyProbeResult {
+    fn new(status: &str, hotkey: &str, message: Option<String>) -> Self {
+        HotkeyProbeResult { status: status.to_string(), hotkey: hotkey.to_string(), message }
+    }
+}
+
+// 确认当前录音快捷键仍处于注册状态，否则按 AppState 中的配置重新注册
+fn ensure_hotkeys_registered(app: &tauri::AppHandle) {
+    let bindings = HotkeyBindings::from_state(&app.state::<AppState>());
+    // 双击修饰键不经过 global-shortcut 注册，探测不会影响它
+    if DoubleTapModifier::parse(&bindings.recording).is_some() {
+        return;
+    }
+    let still_registered = parse_hotkey(&bindings.recording)
+        .map(|shortcut| app.global_shortcut().is_registered(shortcut))
+        .unwrap_or(false);
+    if !still_registered {
+        info!("[tauri] 探测后录音快捷键 {} 未注册，尝试恢复", bindings.recording);
+        if let Err(err) = register_recording_hotkey(app, &bindings.recording) {
+            warn!("[tauri] 恢复录音快捷键失败: {}", err);
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct RegisteredShortcutInfo {
+    // recording / start / stop / cancel / retype / widget_toggle
+    purpose: &'static str,
+    hotkey: String,
+    registered: bool,
+    // 单独修饰键，按双击检测，不经过 global-shortcut 注册
+    double_tap: bool,
+    error: Option<String>,
+}
+
+// Tauri命令：列出当前配置的全部快捷键及其是否实际已注册，用于排查注册状态漂移
+#[tauri::command]
+fn list_registered_shortcuts(app: tauri::AppHandle) -> Vec<RegisteredShortcutInfo> {
+    let bindings = HotkeyBindings::from_state(&app.state::<AppState>());
+    let configured = [
+        ("recording", Some(bindings.recording)),
+        ("start", bindings.start),
+        ("stop", bindings.stop),
+        ("cancel", bindings.cancel),
+        ("retype", bindings.retype),
+        ("widget_toggle", bindings.widget_toggle),
+    ];
+    let gs = app.global_shortcut();
+    configured
+        .into_iter()
+        .filter_map(|(purpose, hotkey)| hotkey.map(|h| (purpose, h)))
+        .map(|(purpose, hotkey)| {
+            if purpose == "recording" && DoubleTapModifier::parse(&hotkey).is_some() {
+                return RegisteredShortcutInfo { purpose, hotkey, registered: DOUBLE_TAP_SUPPORTED, double_tap: true, error: None };
+            }
+            match parse_hotkey(&hotkey) {
+                Ok(shortcut) => RegisteredShortcutInfo {
+                    purpose,
+                    registered: gs.is_registered(shortcut),
+                    hotkey,
+                    double_tap: false,
+                    error: None,
+                },
+                Err(e) => RegisteredShortcutInfo { purpose, hotkey, registered: false, double_tap: false, error: Some(e) },
+            }
+        })
+        .collect()
+}
+
+// 探测快捷键是否可用：临时注册后立即注销，不影响当前已生效的快捷键
+#[tauri::command]
+fn probe_hotkey(app: tauri::AppHandle, hotkey: String) -> HotkeyProbeResult {
+    let hotkey = hotkey.trim().to_string();
+    if let Some(modifier) = DoubleTapModifier::parse(&hotkey) {
+        return if DOUBLE_TAP_SUPPORTED {
+            HotkeyProbeResult::new("available", &hotkey, Some(format!("双击 {} 触发", modifier.name())))
+        } else {
+            HotkeyProbeResult::new("parse_error", &hotkey, Some("当前平台不支持双击修饰键快捷键".to_string()))
+        };
+    }
+    let shortcut = match parse_hotkey(&hotkey) {
+        Ok(shortcut) => shortcut,
+        Err(err) => return HotkeyProbeResult::new("parse_error", &hotkey, Some(err)),
+    };
+
+    let gs = app.global_shortcut();
+    if gs.is_registered(shortcut) {
+        return HotkeyProbeResult::new("already_registered", &hotkey, Some("该快捷键已被本应用占用".to_string()));
+    }
+
+    let result = match gs.register(shortcut) {
+        Ok(_) => match gs.unregister(shortcut) {
+            Ok(_) => HotkeyProbeResult::new("available", &hotkey, None),
+            Err(err) => {
+                warn!("[tauri] 探测快捷键 {} 后注销失败: {}", hotkey, err);
+                HotkeyProbeResult::new("available", &hotkey, Some(format!("注销临时注册失败: {}", err)))
+            }
+        },
+        Err(err) => HotkeyProbeResult::new("already_registered", &hotkey, Some(format!("可能已被其他程序占用: {}", err))),
+    };
+
+    ensure_hotkeys_registered(&app);
+    result
+}
+
+// 捕获用户按下的下一个组合键并返回规范化的快捷键字符串（与注册时的格式一致）。
+// 捕获期间注销全部快捷键，避免误触发录音；结束后按当前配置重新注册
+#[tauri::command]
+async fn capture_next_hotkey(app: tauri::AppHandle, timeout_ms: Option<u64>) -> Result<String, String> {
+    let state = app.state::<AppState>();
+    if state.hotkey_capture_active.swap(true, Ordering::SeqCst) {
+        return Err("正在捕获快捷键".to_string());
+    }
+    let timeout = Duration::from_millis(
+        timeout_ms.unwrap_or(DEFAULT_HOTKEY_CAPTURE_TIMEOUT_MS).min(MAX_HOTKEY_CAPTURE_TIMEOUT_MS),
+    );
+
+    if let Err(e) = app.global_shortcut().unregister_all() {
+        warn!("[tauri] 捕获快捷键前注销失败: {}", e);
+    }
+    stop_double_tap_watcher(&app);
+
+    let captured = tauri::async_runtime::spawn_blocking(move || poll_next_key_combination(timeout))
+        .await
+        .map_err(|e| format!("捕获快捷键任务失败: {}", e))
+        .and_then(|r| r);
+
+    // 无论捕获结果如何都恢复当前配置的快捷键
+    let recording = state.recording_hotkey.lock().unwrap().clone();
+    if let Err(err) = register_recording_hotkey(&app, &recording) {
+        warn!("[tauri] 捕获结束后恢复快捷键 {} 失败: {}", recording, err);
+    }
+    state.hotkey_capture_active.store(false, Ordering::SeqCst);
+
+    match captured? {
+        Some(combo) => Ok(format!("{}", parse_hotkey(&combo)?)),
+        None => Err(format!("{} 秒内未检测到按键", timeout.as_secs_f64())),
+    }
+}
+
+// 保留占位：后续若需要在其他命令中获取当前热键可恢复此函数
+
+fn init_recording_hotkey(app: &tauri::AppHandle, state: &State<'_, AppState>) {
+    let settings = load_ui_settings();
+    // 双击间隔需在启动检测线程前写入
+    if let Ok(mut guard) = state.double_tap_window_ms.lock() {
+        *guard = settings.double_tap_window_ms.clamp(MIN_DOUBLE_TAP_WINDOW_MS, MAX_DOUBLE_TAP_WINDOW_MS);
+    }
+    let hotkey = if settings.recording_hotkey.trim().is_empty() {
+        DEFAULT_RECORDING_HOTKEY.to_string()
+    } else {
+        settings.recording_hotkey.clone()
+    };
+
+    // 独立的开始/停止快捷键需在注册录音快捷键前写入状态
+    if let Ok(mut guard) = state.start_hotkey.lock() {
+        *guard = non_empty_hotkey(settings.start_hotkey.as_deref());
+    }
+    if let Ok(mut guard) = state.stop_hotkey.lock() {
+        *guard = non_empty_hotkey(settings.stop_hotkey.as_deref());
+    }
+    if let Ok(mut guard) = state.cancel_hotkey.lock() {
+        *guard = non_empty_hotkey(settings.cancel_hotkey.as_deref());
+    }
+    if let Ok(mut guard) = state.retype_hotkey.lock() {
+        *guard = non_empty_hotkey(settings.retype_hotkey.as_deref());
+    }
+    if let Ok(mut guard) = state.widget_toggle_hotkey.lock() {
+        *guard = non_empty_hotkey(settings.widget_toggle_hotkey.as_deref());
+    }
+
+    if let Err(err) = register_recording_hotkey(app, &hotkey) {
+        warn!("初始化快捷键失败，逐个重新注册: {}", err);
+        // 先只注册录音快捷键，区分是录音快捷键本身还是某个辅助快捷键注册失败
+        let auxiliary: Vec<(&'static str, Option<String>)> = AUXILIARY_HOTKEY_PURPOSES
+            .iter()
+            .map(|purpose| (*purpose, auxiliary_hotkey_slot(state, purpose).lock().ok().and_then(|mut g| g.take())))
+            .collect();
+        let recording = match register_recording_hotkey(app, &hotkey) {
+            Ok(()) => hotkey.clone(),
+            Err(err) => {
+                warn!("初始化快捷键 {} 失败，将回退为默认值: {}", hotkey, err);
+                // 启动时前端可能尚未监听事件，原因同时保存供 get_hotkey_registration_error 查询
+                if let Ok(mut guard) = state.hotkey_registration_error.lock() {
+                    *guard = Some(err.clone());
+                }
+                let _ = app.emit("hotkey-fallback", serde_json::json!({
+                    "attempted": hotkey,
+                    "fallback": DEFAULT_RECORDING_HOTKEY,
+                    "error": err,
+                }));
+                if let Err(e) = register_recording_hotkey(app, DEFAULT_RECORDING_HOTKEY) {
+                    warn!("注册默认快捷键失败: {}", e);
+                }
+                DEFAULT_RECORDING_HOTKEY.to_string()
+            }
+        };
+        if let Ok(mut guard) = state.recording_hotkey.lock() {
+            *guard = recording;
+        }
+        // 逐个恢复辅助快捷键：注册失败的单独停用并逐条上报，其余保持可用
+        for (purpose, key) in auxiliary {
+            let Some(key) = key else { continue };
+            match register_auxiliary_hotkey(app, purpose, &key) {
+                Ok(()) => {
+                    if let Ok(mut guard) = auxiliary_hotkey_slot(state, purpose).lock() {
+                        *guard = Some(key);
+                    }
+                }
+                Err(e) => {
+                    warn!("初始化{}失败，已停用: {}", t(&format!("hotkey_purpose_{}", purpose)), e);
+                    let _ = app.emit("auxiliary-hotkey-failed", serde_json::json!({
+                        "purpose": purpose,
+                        "hotkey": key,
+                        "error": e,
+                    }));
+                }
+            }
+        }
+    } else if let Ok(mut guard) = state.recording_hotkey.lock() {
+        *guard = hotkey;
+    }
+
+    let mode = if settings.hotkey_mode == HOTKEY_MODE_PUSH_TO_TALK {
+        HOTKEY_MODE_PUSH_TO_TALK.to_string()
+    } else {
+        HOTKEY_MODE_TOGGLE.to_string()
+    };
+    if let Ok(mut guard) = state.hotkey_mode.lock() {
+        *guard = mode;
+    }
+
+    if let Ok(mut guard) = state.hotkey_debounce_ms.lock() {
+        *guard = if settings.hotkey_debounce_ms > MAX_HOTKEY_DEBOUNCE_MS {
+            DEFAULT_HOTKEY_DEBOUNCE_MS
+        } else {
+            settings.hotkey_debounce_ms
+        };
+    }
+    if let Ok(mut guard) = state.min_recording_ms.lock() {
+        *guard = settings.min_recording_ms.min(MAX_MIN_RECORDING_MS);
+    }
+}
+
+// -----------------------------
+// 窗口位置：保存/恢复（ui_settings.json）
+// -----------------------------
+
+// 移动/缩放结束后延迟保存，避免拖动过程中频繁写盘
+const WINDOW_GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+fn saved_window_geometry(settings: &UiSettings, label: &str) -> Option<WindowGeometry> {
+    match label {
+        "widget" => settings.widget_position,
+        SETTINGS_WINDOW_LABEL => settings.settings_window_rect,
+        _ => None,
+    }
+}
+
+fn store_window_geometry(settings: &mut UiSettings, label: &str, geometry: WindowGeometry) {
+    match label {
+        "widget" => settings.widget_position = Some(geometry),
+        SETTINGS_WINDOW_LABEL => settings.settings_window_rect = Some(geometry),
+        _ => {}
+    }
+}
+
+// 保存的窗口中心点所在的显示器（按工作区判断）；显示器已断开时为 None
+fn monitor_containing(window: &tauri::WebviewWindow, geometry: &WindowGeometry) -> Option<tauri::Monitor> {
+    let current = window.outer_size().unwrap_or_default();
+    let width = geometry.width.unwrap_or(current.width) as i32;
+    let height = geometry.height.unwrap_or(current.height) as i32;
+    let center = (geometry.x + width / 2, geometry.y + height / 2);
+
+    window.available_monitors().unwrap_or_default().into_iter().find(|m| {
+        let area = m.work_area();
+        center.0 >= area.position.x
+            && center.0 < area.position.x + area.size.width as i32
+            && center.1 >= area.position.y
+            && center.1 < area.position.y + area.size.height as i32
+    })
+}
+
+// 将保存的位置限制在当前可见显示器的工作区内，避免显示器断开后窗口跑到屏幕外
+fn clamp_to_visible_area(window: &tauri::WebviewWindow, geometry: &WindowGeometry) -> tauri::PhysicalPosition<i32> {
+    let current = window.outer_size().unwrap_or_default();
+    let width = geometry.width.unwrap_or(current.width) as i32;
+    let height = geometry.height.unwrap_or(current.height) as i32;
+
+    let monitor = monitor_containing(window, geometry).or_else(|| {
+        window
+            .primary_monitor()
+            .ok()
+            .flatten()
+            .or_else(|| window.available_monitors().ok().and_then(|m| m.into_iter().next()))
+    });
+    let Some(monitor) = monitor else {
+        return tauri::PhysicalPosition::new(geometry.x, geometry.y);
+    };
+
+    let area = monitor.work_area();
+    let max_x = area.position.x + (area.size.width as i32 - width).max(0);
+    let max_y = area.position.y + (area.size.height as i32 - height).max(0);
+    tauri::PhysicalPosition::new(
+        geometry.x.clamp(area.position.x, max_x),
+        geometry.y.clamp(area.position.y, max_y),
+    )
+}
+
+fn restore_window_geometry(window: &tauri::WebviewWindow) {
+    let settings = load_ui_settings();
+    let Some(geometry) = saved_window_geometry(&settings, window.label()) else { return };
+    // 设置窗口默认居中显示：保存时所在的显示器已断开则保持默认位置
+    if window.label() == SETTINGS_WINDOW_LABEL && monitor_containing(window, &geometry).is_none() {
+        info!("窗口 {} 保存时所在的显示器已不可用，跳过恢复", window.label());
+        return;
+    }
+    if let (Some(width), Some(height)) = (geometry.width, geometry.height) {
+        if let Err(e) = window.set_size(tauri::PhysicalSize::new(width, height)) {
+            warn!("恢复窗口 {} 尺寸失败: {}", window.label(), e);
+        }
+    }
+    let position = clamp_to_visible_area(window, &geometry);
+    match window.set_position(position) {
+        Ok(_) => info!("已恢复窗口 {} 位置: ({}, {})", window.label(), position.x, position.y),
+        Err(e) => warn!("恢复窗口 {} 位置失败: {}", window.label(), e),
+    }
+}
+
+// 悬浮窗默认位置：主显示器工作区右下角，距边缘的逻辑像素
+const WIDGET_DEFAULT_MARGIN: f64 = 24.0;
+
+// 计算悬浮窗默认位置（主显示器工作区右下角，避开任务栏）
+fn default_widget_position(window: &tauri::WebviewWindow) -> Result<tauri::PhysicalPosition<i32>, String> {
+    let monitor = window
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .or_else(|| window.available_monitors().ok().and_then(|m| m.into_iter().next()))
+        .ok_or_else(|| "未找到可用的显示器".to_string())?;
+    let area = monitor.work_area();
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let margin = (WIDGET_DEFAULT_MARGIN * monitor.scale_factor()).round() as i32;
+    let x = area.position.x + area.size.width as i32 - size.width as i32 - margin;
+    let y = area.position.y + area.size.height as i32 - size.height as i32 - margin;
+    // 窗口大于工作区时贴左上角
+    Ok(tauri::PhysicalPosition::new(x.max(area.position.x), y.max(area.position.y)))
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct WidgetAnchor {
+    // top_left / top_right / bottom_left / bottom_right / custom
+    corner: String,
+    // 距工作区边缘的逻辑像素
+    margin_x: i32,
+    margin_y: i32,
+}
+
+impl Default for WidgetAnchor {
+    fn default() -> Self {
+        WidgetAnchor {
+            corner: WIDGET_ANCHOR_CUSTOM.to_string(),
+            margin_x: WIDGET_DEFAULT_MARGIN as i32,
+            margin_y: WIDGET_DEFAULT_MARGIN as i32,
+        }
+    }
+}
+
+// 按锚定角计算窗口左上角坐标（物理像素）；边距限制在 0 与工作区剩余空间之间，保证窗口完整可见
+fn anchor_position(corner: &str, area: (i32, i32, i32, i32), size: (i32, i32), margin: (i32, i32)) -> (i32, i32) {
+    let (ax, ay, aw, ah) = area;
+    let mx = margin.0.clamp(0, (aw - size.0).max(0));
+    let my = margin.1.clamp(0, (ah - size.1).max(0));
+    let x = if corner.ends_with("left") { ax + mx } else { ax + aw - size.0 - mx };
+    let y = if corner.starts_with("top") { ay + my } else { ay + ah - size.1 - my };
+    (x.max(ax), y.max(ay))
+}
+
+// 锚定模式下将悬浮窗放到主显示器工作区的对应角；custom 时返回 false，由调用方恢复自由坐标
+fn apply_widget_anchor(window: &tauri::WebviewWindow, anchor: &WidgetAnchor) -> Result<bool, String> {
+    if anchor.corner == WIDGET_ANCHOR_CUSTOM {
+        return Ok(false);
+    }
+    let monitor = window
+        .primary_monitor()
+        .map_err(|e| e.to_string())?
+        .or_else(|| window.available_monitors().ok().and_then(|m| m.into_iter().next()))
+        .ok_or_else(|| "未找到可用的显示器".to_string())?;
+    let area = monitor.work_area();
+    let size = window.outer_size().map_err(|e| e.to_string())?;
+    let scale = monitor.scale_factor();
+    let (x, y) = anchor_position(
+        &anchor.corner,
+        (area.position.x, area.position.y, area.size.width as i32, area.size.height as i32),
+        (size.width as i32, size.height as i32),
+        ((anchor.margin_x as f64 * scale).round() as i32, (anchor.margin_y as f64 * scale).round() as i32),
+    );
+    window.set_position(tauri::PhysicalPosition::new(x, y)).map_err(|e| e.to_string())?;
+    debug!("[tauri] 悬浮窗已锚定到 {}: ({}, {})", anchor.corner, x, y);
+    Ok(true)
+}
+
+// 启动时放置悬浮窗：锚定优先，custom 或锚定失败时恢复保存的坐标
+fn place_widget_window(window: &tauri::WebviewWindow) {
+    let anchor = load_ui_settings().widget_anchor;
+    match apply_widget_anchor(window, &anchor) {
+        Ok(true) => {}
+        Ok(false) => restore_window_geometry(window),
+        Err(e) => {
+            warn!("[tauri] 应用悬浮窗锚定失败: {}", e);
+            restore_window_geometry(window);
+        }
+    }
+}
 
-    // 写入配置
+// 主显示器工作区变化（分辨率、任务栏位置、缩放）时按锚定重新放置悬浮窗
+fn start_widget_anchor_watcher(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut last_area = None;
+        loop {
+            tokio::time::sleep(WIDGET_ANCHOR_POLL_INTERVAL).await;
+            let Some(window) = app.get_webview_window("widget") else { continue };
+            let area = window.primary_monitor().ok().flatten().map(|m| {
+                let a = m.work_area();
+                (a.position.x, a.position.y, a.size.width, a.size.height, m.scale_factor().to_bits())
+            });
+            if area == last_area {
+                continue;
+            }
+            let changed = last_area.is_some();
+            last_area = area;
+            if !changed {
+                continue;
+            }
+            let anchor = load_ui_settings().widget_anchor;
+            if anchor.corner != WIDGET_ANCHOR_CUSTOM {
+                info!("[tauri] 主显示器工作区已变化，重新锚定悬浮窗");
+                if let Err(e) = apply_widget_anchor(&window, &anchor) {
+                    warn!("[tauri] 重新锚定悬浮窗失败: {}", e);
+                }
+            }
+        }
+    });
+}
+
+// Tauri命令：获取悬浮窗锚定设置
+#[tauri::command]
+fn get_widget_anchor() -> WidgetAnchor {
+    load_ui_settings().widget_anchor
+}
+
+// Tauri命令：设置悬浮窗锚定角与边距（逻辑像素）并立即应用；custom 时恢复自由拖动保存的坐标
+#[tauri::command]
+fn set_widget_anchor(app: tauri::AppHandle, corner: String, margin_x: i32, margin_y: i32) -> Result<WidgetAnchor, String> {
+    let corner = corner.trim().to_lowercase();
+    if corner != WIDGET_ANCHOR_CUSTOM && !WIDGET_ANCHOR_CORNERS.contains(&corner.as_str()) {
+        return Err(format!(
+            "无效的锚定位置: {}（可选 {} / {}）",
+            corner,
+            WIDGET_ANCHOR_CORNERS.join(" / "),
+            WIDGET_ANCHOR_CUSTOM
+        ));
+    }
+    let anchor = WidgetAnchor {
+        corner,
+        margin_x: margin_x.clamp(0, MAX_WIDGET_ANCHOR_MARGIN),
+        margin_y: margin_y.clamp(0, MAX_WIDGET_ANCHOR_MARGIN),
+    };
     let mut settings = load_ui_settings();
-    settings.recording_hotkey = new_hotkey.to_string();
+    settings.widget_anchor = anchor.clone();
     save_ui_settings(&settings)?;
 
-    // 更新状态
-    {
-        let mut guard = state.recording_hotkey.lock().map_err(|e| format!("更新快捷键状态失败: {}", e))?;
-        *guard = new_hotkey.to_string();
+    if let Some(window) = app.get_webview_window("widget") {
+        if !apply_widget_anchor(&window, &anchor)? {
+            restore_window_geometry(&window);
+        }
     }
+    Ok(anchor)
+}
 
-    // 广播给前端：快捷键已更新（优先发到 widget 窗口，找不到则广播全局）
-    println!("[rust] 准备发送 recording-hotkey-updated 事件，新快捷键: {}", new_hotkey);
-    if let Some(win) = app.get_webview_window("widget") {
-        println!("[rust] 找到widget窗口，向其发送事件");
-        match win.emit("recording-hotkey-updated", new_hotkey.to_string()) {
-            Ok(_) => println!("[rust] 事件发送成功"),
-            Err(e) => println!("[rust] 事件发送失败: {}", e),
+// Tauri命令：将悬浮窗移回默认位置并清除保存的坐标（窗口隐藏时同样生效）
+#[tauri::command]
+fn reset_widget_position(app: tauri::AppHandle) -> Result<(), String> {
+    let window = app
+        .get_webview_window("widget")
+        .ok_or_else(|| "窗口不存在: widget".to_string())?;
+    let position = default_widget_position(&window)?;
+    window.set_position(position).map_err(|e| e.to_string())?;
+    info!("已将悬浮窗重置到默认位置: ({}, {})", position.x, position.y);
+
+    let mut settings = load_ui_settings();
+    settings.widget_position = None;
+    save_ui_settings(&settings)
+}
+
+// 去抖保存：仅当延迟结束时没有新的移动/缩放事件才写入
+fn schedule_save_window_geometry(app: &tauri::AppHandle, label: &str) {
+    let seq = {
+        let app_state = app.state::<AppState>();
+        let mut map = app_state.window_geometry_seq.lock().unwrap();
+        let entry = map.entry(label.to_string()).or_insert(0);
+        *entry = entry.wrapping_add(1);
+        *entry
+    };
+    let app_handle = app.clone();
+    let label = label.to_string();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WINDOW_GEOMETRY_SAVE_DELAY).await;
+        let latest = {
+            let app_state = app_handle.state::<AppState>();
+            let map = app_state.window_geometry_seq.lock().unwrap();
+            map.get(&label).copied()
+        };
+        if latest != Some(seq) {
+            return;
+        }
+        let Some(window) = app_handle.get_webview_window(&label) else { return };
+        // 最小化时系统会报告无效坐标，跳过
+        if window.is_minimized().unwrap_or(false) {
+            return;
+        }
+        let (Ok(position), Ok(size)) = (window.outer_position(), window.outer_size()) else { return };
+        let mut settings = load_ui_settings();
+        store_window_geometry(&mut settings, &label, WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: Some(size.width),
+            height: Some(size.height),
+        });
+        if let Err(e) = save_ui_settings(&settings) {
+            warn!("保存窗口 {} 位置失败: {}", label, e);
+        }
+    });
+}
+
+// Tauri命令：获取窗口是否置顶
+#[tauri::command]
+fn get_always_on_top(app: tauri::AppHandle, label: &str) -> Result<bool, String> {
+    match app.get_webview_window(label) {
+        Some(window) => window.is_always_on_top().map_err(|e| e.to_string()),
+        None if label == "widget" => Ok(load_ui_settings().widget_always_on_top),
+        None => Err(format!("窗口不存在: {}", label)),
+    }
+}
+
+// Tauri命令：设置窗口置顶（悬浮窗的设置会持久化）
+#[tauri::command]
+fn set_always_on_top(app: tauri::AppHandle, label: String, enabled: bool) -> Result<bool, String> {
+    let window = app
+        .get_webview_window(&label)
+        .ok_or_else(|| format!("窗口不存在: {}", label))?;
+    // 仅切换置顶，不改动 skip_taskbar（由 show_window/minimize_window 负责）
+    window.set_always_on_top(enabled).map_err(|e| e.to_string())?;
+
+    if label == "widget" {
+        let mut settings = load_ui_settings();
+        settings.widget_always_on_top = enabled;
+        save_ui_settings(&settings)?;
+    }
+    Ok(true)
+}
+
+// 悬浮窗是否应穿透鼠标：仅在偏好开启、未录音且设置窗口未显示时穿透，
+// 保证用户始终可以通过打开设置窗口恢复与悬浮窗的交互
+fn widget_should_ignore_cursor(app: &tauri::AppHandle) -> bool {
+    if !load_ui_settings().widget_click_through {
+        return false;
+    }
+    if *app.state::<AppState>().is_recording.lock().unwrap() {
+        return false;
+    }
+    let settings_visible = app
+        .get_webview_window(SETTINGS_WINDOW_LABEL)
+        .and_then(|w| w.is_visible().ok())
+        .unwrap_or(false);
+    !settings_visible
+}
+
+// 按当前状态立即应用悬浮窗鼠标穿透
+fn refresh_widget_click_through(app: &tauri::AppHandle) {
+    if let Some(window) = app.get_webview_window("widget") {
+        let ignore = widget_should_ignore_cursor(app);
+        if let Err(e) = window.set_ignore_cursor_events(ignore) {
+            warn!("设置悬浮窗鼠标穿透失败: {}", e);
+        }
+    }
+}
+
+// 延迟应用（录音结束后使用）；到期时重新判断状态，期间开始录音则保持可点击
+fn schedule_widget_click_through(app: &tauri::AppHandle) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(WIDGET_CLICK_THROUGH_DELAY).await;
+        refresh_widget_click_through(&app);
+    });
+}
+
+fn widget_recording_only(settings: &UiSettings) -> bool {
+    settings.widget_visibility_mode == WIDGET_VISIBILITY_RECORDING_ONLY
+}
+
+// 录音结束后延迟隐藏悬浮窗的秒数：recording_only 模式未设置时立即隐藏，always 模式未设置时不隐藏
+fn widget_autohide_delay(settings: &UiSettings) -> Option<u64> {
+    let secs = settings.widget_autohide_secs.filter(|s| *s > 0);
+    if widget_recording_only(settings) {
+        Some(secs.unwrap_or(0))
+    } else {
+        secs
+    }
+}
+
+// 录音结束后安排自动隐藏悬浮窗；新的安排会使之前尚未触发的计时失效
+fn schedule_widget_autohide(app: &tauri::AppHandle) {
+    let generation = app.state::<AppState>().widget_autohide_generation.fetch_add(1, Ordering::SeqCst) + 1;
+    let Some(secs) = widget_autohide_delay(&load_ui_settings()) else { return };
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        tokio::time::sleep(Duration::from_secs(secs.min(MAX_WIDGET_AUTOHIDE_SECS))).await;
+        let state = app.state::<AppState>();
+        if state.widget_autohide_generation.load(Ordering::SeqCst) != generation || *state.is_recording.lock().unwrap() {
+            return;
+        }
+        if let Some(window) = app.get_webview_window("widget") {
+            debug!("[tauri] 录音结束 {} 秒，自动隐藏悬浮窗", secs);
+            let _ = window.hide();
+        }
+    });
+}
+
+// 取消尚未触发的自动隐藏（录音重新开始或用户手动显示悬浮窗时）
+fn cancel_widget_autohide(app: &tauri::AppHandle) {
+    app.state::<AppState>().widget_autohide_generation.fetch_add(1, Ordering::SeqCst);
+}
+
+// Tauri命令：获取录音结束后自动隐藏悬浮窗的延迟（秒，None 表示不自动隐藏；recording_only 模式下表示立即隐藏）
+#[tauri::command]
+fn get_widget_autohide() -> Option<u64> {
+    load_ui_settings().widget_autohide_secs
+}
+
+// Tauri命令：设置自动隐藏延迟；传入 None 或 0 关闭自动隐藏（recording_only 模式下为录音结束后立即隐藏）
+#[tauri::command]
+fn set_widget_autohide(app: tauri::AppHandle, secs: Option<u64>) -> Result<Option<u64>, String> {
+    let secs = secs.filter(|s| *s > 0);
+    if let Some(s) = secs {
+        if s > MAX_WIDGET_AUTOHIDE_SECS {
+            return Err(format!("自动隐藏延迟不能超过 {} 秒", MAX_WIDGET_AUTOHIDE_SECS));
+        }
+    }
+    let mut settings = load_ui_settings();
+    settings.widget_autohide_secs = secs;
+    save_ui_settings(&settings)?;
+    if secs.is_none() && !widget_recording_only(&settings) {
+        cancel_widget_autohide(&app);
+    }
+    Ok(secs)
+}
+
+// Tauri命令：获取悬浮窗显示方式
+#[tauri::command]
+fn get_widget_visibility_mode() -> String {
+    load_ui_settings().widget_visibility_mode
+}
+
+// Tauri命令：设置悬浮窗显示方式并立即应用：always 立即显示；recording_only 在未录音时立即隐藏
+#[tauri::command]
+fn set_widget_visibility_mode(app: tauri::AppHandle, state: State<'_, AppState>, mode: String) -> Result<(), String> {
+    let mode = mode.trim().to_lowercase();
+    if mode != WIDGET_VISIBILITY_ALWAYS && mode != WIDGET_VISIBILITY_RECORDING_ONLY {
+        return Err(format!(
+            "无效的显示方式: {}（可选 {} / {}）",
+            mode, WIDGET_VISIBILITY_ALWAYS, WIDGET_VISIBILITY_RECORDING_ONLY
+        ));
+    }
+    let mut settings = load_ui_settings();
+    settings.widget_visibility_mode = mode.clone();
+    save_ui_settings(&settings)?;
+
+    cancel_widget_autohide(&app);
+    let recording = *state.is_recording.lock().unwrap();
+    if let Some(window) = app.get_webview_window("widget") {
+        if mode == WIDGET_VISIBILITY_ALWAYS || recording {
+            let _ = window.show();
+        } else {
+            let _ = window.hide();
+        }
+    }
+    Ok(())
+}
+
+// Tauri命令：临时设置悬浮窗是否忽略鼠标事件（不持久化，下次状态变化时按偏好重新计算）
+#[tauri::command]
+fn set_widget_ignore_cursor(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let window = app
+        .get_webview_window("widget")
+        .ok_or_else(|| "窗口不存在: widget".to_string())?;
+    window.set_ignore_cursor_events(enabled).map_err(|e| e.to_string())
+}
+
+// Tauri命令：获取空闲时鼠标穿透偏好
+#[tauri::command]
+fn get_widget_click_through() -> bool {
+    load_ui_settings().widget_click_through
+}
+
+// Tauri命令：设置空闲时鼠标穿透偏好并立即应用
+#[tauri::command]
+fn set_widget_click_through(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    let mut settings = load_ui_settings();
+    settings.widget_click_through = enabled;
+    save_ui_settings(&settings)?;
+    refresh_widget_click_through(&app);
+    Ok(())
+}
+
+// 设置窗口整体不透明度（Windows 通过分层窗口实现）
+#[cfg(windows)]
+fn apply_window_opacity(window: &tauri::WebviewWindow, opacity: f64) -> Result<(), String> {
+    use windows::Win32::Foundation::{COLORREF, HWND};
+    use windows::Win32::UI::WindowsAndMessaging::{
+        GetWindowLongPtrW, SetLayeredWindowAttributes, SetWindowLongPtrW, GWL_EXSTYLE, LWA_ALPHA,
+        WS_EX_LAYERED,
+    };
+
+    let raw = window.hwnd().map_err(|e| e.to_string())?;
+    let hwnd = HWND(raw.0 as isize);
+    let alpha = (opacity.clamp(0.0, 1.0) * 255.0).round() as u8;
+    unsafe {
+        let ex_style = GetWindowLongPtrW(hwnd, GWL_EXSTYLE);
+        if ex_style & WS_EX_LAYERED.0 as isize == 0 {
+            SetWindowLongPtrW(hwnd, GWL_EXSTYLE, ex_style | WS_EX_LAYERED.0 as isize);
+        }
+        SetLayeredWindowAttributes(hwnd, COLORREF(0), alpha, LWA_ALPHA)
+            .map_err(|e| format!("设置窗口透明度失败: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(windows))]
+fn apply_window_opacity(_window: &tauri::WebviewWindow, _opacity: f64) -> Result<(), String> {
+    Err("当前平台不支持设置窗口透明度".to_string())
+}
+
+#[derive(Clone, Debug)]
+struct ForegroundWindow {
+    handle: isize,
+    title: String,
+    pid: u32,
+}
+
+// 获取当前前台窗口（Windows 通过 GetForegroundWindow 实现）
+#[cfg(windows)]
+fn foreground_window() -> Option<ForegroundWindow> {
+    use windows::Win32::UI::WindowsAndMessaging::{GetForegroundWindow, GetWindowTextW, GetWindowThreadProcessId};
+
+    unsafe {
+        let hwnd = GetForegroundWindow();
+        if hwnd.0 == 0 {
+            return None;
+        }
+        let mut buf = [0u16; 512];
+        let len = GetWindowTextW(hwnd, &mut buf).max(0) as usize;
+        let mut pid = 0u32;
+        GetWindowThreadProcessId(hwnd, Some(&mut pid));
+        Some(ForegroundWindow {
+            handle: hwnd.0,
+            title: String::from_utf16_lossy(&buf[..len]),
+            pid,
+        })
+    }
+}
+
+#[cfg(not(windows))]
+fn foreground_window() -> Option<ForegroundWindow> {
+    None
+}
+
+// 录音开始时记录输出目标：本应用自身的窗口（如点击悬浮窗按钮）不作为目标，输出时沿用当前焦点。
+// 返回需下发给桥接的 set_output_target 指令
+fn capture_output_target(state: &AppState) -> Value {
+    let target = foreground_window().filter(|w| w.pid != std::process::id());
+    let hwnd = target.as_ref().map(|w| w.handle);
+    if let Some(w) = target.as_ref() {
+        debug!("[tauri] 记录输出目标窗口: {}（hwnd={}）", w.title, w.handle);
+    }
+    *state.output_target.lock().unwrap() = target;
+    serde_json::json!({"cmd": "set_output_target", "hwnd": hwnd})
+}
+
+// 免打扰匹配：标题包含列表中任一项（不区分大小写）时返回命中的项
+fn match_blocked_title<'a>(title: &str, blocked: &'a [String]) -> Option<&'a str> {
+    let title = title.to_lowercase();
+    blocked
+        .iter()
+        .map(|item| item.trim())
+        .find(|item| !item.is_empty() && title.contains(&item.to_lowercase()))
+}
+
+// 即将发送 start 前的统一检查，所有开始录音的路径（快捷键、命令、托盘、深度链接、HTTP 接口）共用：
+// 前台窗口命中免打扰列表时通知前端并返回错误
+fn ensure_recording_start_allowed(app: &tauri::AppHandle) -> Result<(), String> {
+    let settings = load_ui_settings();
+    if settings.blocked_app_titles.is_empty() {
+        return Ok(());
+    }
+    let Some(window) = foreground_window().filter(|w| w.pid != std::process::id()) else { return Ok(()) };
+    let Some(pattern) = match_blocked_title(&window.title, &settings.blocked_app_titles) else { return Ok(()) };
+    info!("[tauri] 前台窗口 {} 命中免打扰项 {}，拒绝开始录音", window.title, pattern);
+    let _ = app.emit(
+        "recording-blocked",
+        serde_json::json!({ "title": window.title, "pattern": pattern }),
+    );
+    Err(format!("前台窗口命中免打扰项 {}，已拒绝开始录音", pattern))
+}
+
+// Tauri命令：获取免打扰应用列表（窗口标题关键字）
+#[tauri::command]
+fn get_blocked_app_titles() -> Vec<String> {
+    load_ui_settings().blocked_app_titles
+}
+
+// Tauri命令：添加免打扰关键字（忽略空白与重复项，不区分大小写），返回更新后的列表
+#[tauri::command]
+fn add_blocked_app_title(title: String) -> Result<Vec<String>, String> {
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err("免打扰关键字不能为空".to_string());
+    }
+    let mut settings = load_ui_settings();
+    let lowered = title.to_lowercase();
+    if !settings.blocked_app_titles.iter().any(|item| item.to_lowercase() == lowered) {
+        settings.blocked_app_titles.push(title);
+        save_ui_settings(&settings)?;
+    }
+    Ok(settings.blocked_app_titles)
+}
+
+// Tauri命令：移除免打扰关键字（不区分大小写），返回更新后的列表
+#[tauri::command]
+fn remove_blocked_app_title(title: String) -> Result<Vec<String>, String> {
+    let lowered = title.trim().to_lowercase();
+    let mut settings = load_ui_settings();
+    let before = settings.blocked_app_titles.len();
+    settings.blocked_app_titles.retain(|item| item.to_lowercase() != lowered);
+    if settings.blocked_app_titles.len() != before {
+        save_ui_settings(&settings)?;
+    }
+    Ok(settings.blocked_app_titles)
+}
+
+// Tauri命令：获取当前前台窗口标题（不支持的平台返回 None）
+#[tauri::command]
+fn get_foreground_window_title() -> Option<String> {
+    foreground_window().map(|w| w.title)
+}
+
+// Tauri命令：获取悬浮窗不透明度
+#[tauri::command]
+fn get_widget_opacity() -> f64 {
+    load_ui_settings().widget_opacity
+}
+
+// Tauri命令：设置悬浮窗不透明度（0.2 ~ 1.0），返回是否已实际生效
+#[tauri::command]
+fn set_widget_opacity(app: tauri::AppHandle, value: f64) -> Result<bool, String> {
+    if !value.is_finite() || !(MIN_WIDGET_OPACITY..=MAX_WIDGET_OPACITY).contains(&value) {
+        return Err(format!(
+            "悬浮窗不透明度无效：{}（允许范围 {} ~ {}）",
+            value, MIN_WIDGET_OPACITY, MAX_WIDGET_OPACITY
+        ));
+    }
+
+    let mut settings = load_ui_settings();
+    settings.widget_opacity = value;
+    save_ui_settings(&settings)?;
+
+    // 平台不支持或窗口不存在时仅保存设置，不视为错误
+    let applied = match app.get_webview_window("widget") {
+        Some(window) => match apply_window_opacity(&window, value) {
+            Ok(()) => true,
+            Err(e) => {
+                warn!("应用悬浮窗不透明度失败: {}", e);
+                false
+            }
+        },
+        None => false,
+    };
+    Ok(applied)
+}
+
+// 将主题应用到所有窗口的原生标题栏/控件；system 交还系统决定
+fn apply_window_theme(app: &tauri::AppHandle, theme: &str) {
+    let native = match theme {
+        THEME_LIGHT => Some(tauri::Theme::Light),
+        THEME_DARK => Some(tauri::Theme::Dark),
+        _ => None,
+    };
+    for (label, window) in app.webview_windows() {
+        if let Err(e) = window.set_theme(native) {
+            debug!("窗口 {} 设置主题失败（平台可能不支持）: {}", label, e);
+        }
+    }
+}
+
+// Tauri命令：获取界面主题
+#[tauri::command]
+fn get_theme() -> String {
+    load_ui_settings().theme
+}
+
+// Tauri命令：设置界面主题并通知所有窗口
+#[tauri::command]
+fn set_theme(app: tauri::AppHandle, theme: String) -> Result<(), String> {
+    let theme = theme.trim().to_lowercase();
+    if theme != THEME_SYSTEM && theme != THEME_LIGHT && theme != THEME_DARK {
+        return Err(format!(
+            "无效的主题: {}（可选 {} / {} / {}）",
+            theme, THEME_SYSTEM, THEME_LIGHT, THEME_DARK
+        ));
+    }
+    let mut settings = load_ui_settings();
+    settings.theme = theme.clone();
+    save_ui_settings(&settings)?;
+
+    apply_window_theme(&app, &theme);
+    let _ = app.emit("theme-changed", serde_json::json!({ "theme": theme }));
+    Ok(())
+}
+
+// 显示并聚焦悬浮窗（与托盘“显示主窗口”一致）
+fn show_widget_window(app: &tauri::AppHandle) {
+    cancel_widget_autohide(app);
+    if let Some(window) = app.get_webview_window("widget") {
+        let _ = window.set_skip_taskbar(true);
+        let _ = window.show();
+        let _ = window.set_focus();
+        let _ = window.unminimize();
+    }
+}
+
+// 设置窗口初始化：恢复上次位置与尺寸，移动/缩放后去抖保存，关闭时隐藏
+fn init_settings_window(window: &tauri::WebviewWindow) {
+    restore_window_geometry(window);
+    let app_handle = window.app_handle().clone();
+    window.on_window_event(move |event| {
+        if matches!(event, tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_)) {
+            schedule_save_window_geometry(&app_handle, SETTINGS_WINDOW_LABEL);
+        }
+    });
+    hide_settings_on_close(window);
+}
+
+// 设置窗口关闭时隐藏而非销毁，以保留前端状态
+fn hide_settings_on_close(window: &tauri::WebviewWindow) {
+    let window_clone = window.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::CloseRequested { api, .. } = event {
+            info!("设置窗口关闭请求，隐藏而不是销毁");
+            api.prevent_close();
+            let _ = window_clone.hide();
+            refresh_widget_click_through(window_clone.app_handle());
+        }
+    });
+}
+
+// 显示并聚焦设置窗口；窗口不存在时按 tauri.conf.json 中的定义懒创建
+fn open_settings_window(app: &tauri::AppHandle) -> Result<(), String> {
+    let window = match app.get_webview_window(SETTINGS_WINDOW_LABEL) {
+        Some(window) => window,
+        None => {
+            info!("设置窗口不存在，重新创建");
+            let builder = match app.config().app.windows.iter().find(|w| w.label == SETTINGS_WINDOW_LABEL) {
+                Some(config) => tauri::WebviewWindowBuilder::from_config(app, config)
+                    .map_err(|e| format!("创建设置窗口失败: {}", e))?,
+                // 配置中缺少定义时使用与默认配置一致的参数
+                None => tauri::WebviewWindowBuilder::new(app, SETTINGS_WINDOW_LABEL, tauri::WebviewUrl::default())
+                    .title("settings")
+                    .inner_size(500.0, 550.0)
+                    .resizable(true)
+                    .maximizable(false)
+                    .decorations(false)
+                    .center()
+                    .visible(false),
+            };
+            let window = builder.build().map_err(|e| format!("创建设置窗口失败: {}", e))?;
+            init_settings_window(&window);
+            apply_window_theme(app, &load_ui_settings().theme);
+            window
+        }
+    };
+
+    if window.is_minimized().unwrap_or(false) {
+        window.unminimize().map_err(|e| e.to_string())?;
+    }
+    if !window.is_visible().unwrap_or(false) {
+        window.show().map_err(|e| e.to_string())?;
+    }
+    window.set_focus().map_err(|e| e.to_string())?;
+    refresh_widget_click_through(app);
+    Ok(())
+}
+
+// Tauri命令：打开设置窗口（已可见时仅聚焦）
+#[tauri::command]
+fn open_settings(app: tauri::AppHandle) -> Result<(), String> {
+    open_settings_window(&app)
+}
+
+// 深度链接：speakkeyboard://toggle | start | stop | show
+fn handle_deep_link(app: &tauri::AppHandle, url: &tauri::Url) {
+    let action = url
+        .host_str()
+        .map(str::to_string)
+        .or_else(|| url.path().trim_matches('/').split('/').next().map(str::to_string))
+        .unwrap_or_default()
+        .to_lowercase();
+    info!("[tauri] 收到深度链接: {}（动作: {}）", url, action);
+
+    let app_handle = app.clone();
+    match action.as_str() {
+        "show" => show_widget_window(app),
+        "toggle" | "start" | "stop" => {
+            tauri::async_runtime::spawn(async move {
+                let result = match action.as_str() {
+                    "toggle" => toggle_recording(app_handle.clone(), app_handle.state(), app_handle.state()).await,
+                    "start" => start_recording(app_handle.clone(), app_handle.state(), app_handle.state()).await,
+                    _ => stop_recording(app_handle.state(), app_handle.state()).await.map(|_| ()),
+                };
+                if let Err(e) = result {
+                    warn!("[tauri] 深度链接 {} 执行失败: {}", action, e);
+                }
+            });
+        }
+        _ => warn!("[tauri] 未知的深度链接动作: {}", action),
+    }
+}
+
+// -----------------------------
+// 本地 HTTP 接口：POST /start、/stop、/toggle，GET /status
+// -----------------------------
+
+// 生成随机访问令牌（32 位十六进制）
+fn generate_http_api_token() -> String {
+    use std::hash::{BuildHasher, Hasher};
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    (0..2u64)
+        .map(|i| {
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_u64(i);
+            hasher.write_u128(nanos);
+            hasher.write_u32(std::process::id());
+            format!("{:016x}", hasher.finish())
+        })
+        .collect()
+}
+
+fn stop_http_api(app: &tauri::AppHandle) {
+    if let Some(task) = app.state::<AppState>().http_api_task.lock().unwrap().take() {
+        task.abort();
+        info!("[tauri] 本地 HTTP 接口已停止");
+    }
+}
+
+// 按当前设置（重新）启动本地 HTTP 接口；未启用时仅停止已有监听
+async fn start_http_api(app: &tauri::AppHandle) -> Result<(), String> {
+    stop_http_api(app);
+    let mut settings = load_ui_settings();
+    if !settings.http_api_enabled {
+        return Ok(());
+    }
+    let token = match settings.http_api_token.as_deref().map(str::trim) {
+        Some(token) if !token.is_empty() => token.to_string(),
+        _ => {
+            let token = generate_http_api_token();
+            settings.http_api_token = Some(token.clone());
+            save_ui_settings(&settings)?;
+            token
+        }
+    };
+
+    let addr = std::net::SocketAddr::from(([127, 0, 0, 1], settings.http_api_port));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("本地 HTTP 接口监听 {} 失败: {}", addr, e))?;
+    info!("[tauri] 本地 HTTP 接口已启动: http://{}", addr);
+
+    let app_handle = app.clone();
+    let task = tauri::async_runtime::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let app_handle = app_handle.clone();
+                    let token = token.clone();
+                    tauri::async_runtime::spawn(async move {
+                        if let Err(e) = handle_http_api_connection(&app_handle, stream, &token).await {
+                            warn!("[tauri] 处理 HTTP 请求失败: {}", e);
+                        }
+                    });
+                }
+                Err(e) => {
+                    warn!("[tauri] 接受 HTTP 连接失败: {}", e);
+                    tokio::time::sleep(Duration::from_millis(200)).await;
+                }
+            }
+        }
+    });
+    *app.state::<AppState>().http_api_task.lock().unwrap() = Some(task);
+    Ok(())
+}
+
+// 读取请求头（不处理请求体），返回 (方法, 路径, Authorization)
+async fn read_http_request_head(
+    stream: &mut tokio::net::TcpStream,
+) -> Result<(String, String, Option<String>), String> {
+    use tokio::io::AsyncReadExt;
+
+    let mut head = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    while !head.windows(4).any(|w| w == b"\r\n\r\n") {
+        if head.len() >= HTTP_API_MAX_REQUEST_BYTES {
+            return Err("请求头过大".to_string());
+        }
+        let n = tokio::time::timeout(HTTP_API_READ_TIMEOUT, stream.read(&mut chunk))
+            .await
+            .map_err(|_| "读取请求超时".to_string())?
+            .map_err(|e| e.to_string())?;
+        if n == 0 {
+            return Err("连接已关闭".to_string());
+        }
+        head.extend_from_slice(&chunk[..n]);
+    }
+
+    let text = String::from_utf8_lossy(&head);
+    let mut lines = text.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("").to_uppercase();
+    let target = request_line.next().unwrap_or("");
+    let path = target.split('?').next().unwrap_or("").to_string();
+    let authorization = lines
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .map(|(_, value)| value.trim().to_string());
+    Ok((method, path, authorization))
+}
+
+async fn handle_http_api_connection(
+    app: &tauri::AppHandle,
+    mut stream: tokio::net::TcpStream,
+    token: &str,
+) -> Result<(), String> {
+    let (status, body) = match read_http_request_head(&mut stream).await {
+        Err(e) => (400, serde_json::json!({"ok": false, "error": e})),
+        Ok((method, path, authorization)) => {
+            let provided = authorization
+                .as_deref()
+                .map(|value| value.strip_prefix("Bearer ").unwrap_or(value).trim());
+            if provided != Some(token) {
+                (401, serde_json::json!({"ok": false, "error": "unauthorized"}))
+            } else {
+                route_http_api_request(app, &method, &path).await
+            }
+        }
+    };
+
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Service Unavailable",
+    };
+    let body = body.to_string();
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+    stream.write_all(response.as_bytes()).await.map_err(|e| e.to_string())?;
+    stream.shutdown().await.map_err(|e| e.to_string())
+}
+
+// 路由：与前端命令共用同一套桥接写入逻辑
+async fn route_http_api_request(app: &tauri::AppHandle, method: &str, path: &str) -> (u16, Value) {
+    let result = match (method, path) {
+        ("POST", "/start") => start_recording(app.clone(), app.state(), app.state()).await,
+        ("POST", "/stop") => stop_recording(app.state(), app.state()).await.map(|_| ()),
+        ("POST", "/toggle") => toggle_recording(app.clone(), app.state(), app.state()).await,
+        ("GET", "/status") => {
+            let is_recording = *app.state::<AppState>().is_recording.lock().unwrap();
+            let is_paused = *app.state::<AppState>().is_paused.lock().unwrap();
+            let bridge_running = app.state::<BridgeState>().stdin.lock().await.is_some();
+            return (200, serde_json::json!({
+                "ok": true,
+                "is_recording": is_recording,
+                "is_paused": is_paused,
+                "bridge_running": bridge_running
+            }));
         }
-    } else {
-        println!("[rust] 未找到widget窗口，尝试全局广播");
-        match app.emit("recording-hotkey-updated", new_hotkey.to_string()) {
-            Ok(_) => println!("[rust] 全局事件发送成功"),
-            Err(e) => println!("[rust] 全局事件发送失败: {}", e),
+        (_, "/start" | "/stop" | "/toggle" | "/status") => {
+            return (405, serde_json::json!({"ok": false, "error": "method not allowed"}));
         }
+        _ => return (404, serde_json::json!({"ok": false, "error": "not found"})),
+    };
+    match result {
+        Ok(()) => (200, serde_json::json!({"ok": true})),
+        Err(e) => (503, serde_json::json!({"ok": false, "error": e})),
     }
+}
 
-    Ok(true)
+#[derive(Serialize)]
+struct HttpApiInfo {
+    enabled: bool,
+    port: u16,
+    token: Option<String>,
+    running: bool,
 }
 
-// 保留占位：后续若需要在其他命令中获取当前热键可恢复此函数
+#[derive(Deserialize)]
+struct HttpApiSettingsPayload {
+    enabled: bool,
+    port: Option<u16>,
+}
 
-fn init_recording_hotkey(app: &tauri::AppHandle, state: &State<'_, AppState>) {
+fn http_api_info(app: &tauri::AppHandle) -> HttpApiInfo {
     let settings = load_ui_settings();
-    let hotkey = if settings.recording_hotkey.trim().is_empty() {
-        DEFAULT_RECORDING_HOTKEY.to_string()
-    } else {
-        settings.recording_hotkey.clone()
-    };
+    let running = app.state::<AppState>().http_api_task.lock().unwrap().is_some();
+    HttpApiInfo {
+        enabled: settings.http_api_enabled,
+        port: settings.http_api_port,
+        token: settings.http_api_token,
+        running,
+    }
+}
 
-    if let Err(err) = register_recording_hotkey(app, &hotkey) {
-        println!("初始化快捷键 {} 失败，将回退为默认值: {}", hotkey, err);
-        if let Err(e) = register_recording_hotkey(app, DEFAULT_RECORDING_HOTKEY) {
-            println!("注册默认快捷键失败: {}", e);
+// Tauri命令：获取本地 HTTP 接口设置与运行状态
+#[tauri::command]
+fn get_http_api_settings(app: tauri::AppHandle) -> HttpApiInfo {
+    http_api_info(&app)
+}
+
+// Tauri命令：启用/关闭本地 HTTP 接口或修改端口（立即生效，无需重启应用）
+#[tauri::command]
+async fn set_http_api_settings(app: tauri::AppHandle, payload: HttpApiSettingsPayload) -> Result<HttpApiInfo, String> {
+    let mut settings = load_ui_settings();
+    if let Some(port) = payload.port {
+        if port == 0 {
+            return Err("端口号无效：0".to_string());
         }
+        settings.http_api_port = port;
     }
+    settings.http_api_enabled = payload.enabled;
+    save_ui_settings(&settings)?;
 
-    if let Ok(mut guard) = state.recording_hotkey.lock() {
-        *guard = hotkey;
-    }
+    start_http_api(&app).await?;
+    Ok(http_api_info(&app))
 }
 
 // Tauri命令：显示/隐藏窗口
@@ -804,10 +6940,13 @@ fn toggle_window_visibility(app: tauri::AppHandle, label: &str) -> Result<(), St
 // Tauri命令：隐藏窗口
 #[tauri::command]
 fn hide_window(app: tauri::AppHandle, label: &str) -> Result<(), String> {
-    println!("隐藏窗口: {}", label);
+    debug!("隐藏窗口: {}", label);
     if let Some(window) = app.get_webview_window(label) {
         window.hide().map_err(|e| e.to_string())?;
-        println!("窗口已隐藏");
+        debug!("窗口已隐藏");
+    }
+    if label == SETTINGS_WINDOW_LABEL {
+        refresh_widget_click_through(&app);
     }
     Ok(())
 }
@@ -815,9 +6954,9 @@ fn hide_window(app: tauri::AppHandle, label: &str) -> Result<(), String> {
 // Tauri命令：显示窗口
 #[tauri::command]
 fn show_window(app: tauri::AppHandle, label: &str) -> Result<(), String> {
-    println!("尝试显示窗口: {}", label);
+    debug!("尝试显示窗口: {}", label);
     if let Some(window) = app.get_webview_window(label) {
-        println!("窗口存在，当前可见性: {:?}", window.is_visible());
+        debug!("窗口存在，当前可见性: {:?}", window.is_visible());
         if label == "widget" {
             // 恢复悬浮窗显示时的任务栏策略：可见时不在任务栏
             let _ = window.set_skip_taskbar(true);
@@ -825,9 +6964,12 @@ fn show_window(app: tauri::AppHandle, label: &str) -> Result<(), String> {
         window.show().map_err(|e| e.to_string())?;
         window.set_focus().map_err(|e| e.to_string())?;
         window.unminimize().map_err(|e| e.to_string())?;
-        println!("窗口显示完成");
+        debug!("窗口显示完成");
     } else {
-        println!("窗口不存在: {}", label);
+        info!("窗口不存在: {}", label);
+    }
+    if label == SETTINGS_WINDOW_LABEL {
+        refresh_widget_click_through(&app);
     }
     Ok(())
 }
@@ -847,128 +6989,347 @@ fn minimize_window(app: tauri::AppHandle, label: &str) -> Result<(), String> {
     Ok(())
 }
 
+// 自动探测项目根目录（包含 app/bridge.py 的目录）。结果在首次调用时缓存，
+// 配置路径与桥接启动都以此为准，避免两处探测结果不一致
+fn find_project_root() -> Option<PathBuf> {
+    static PROJECT_ROOT: std::sync::OnceLock<Option<PathBuf>> = std::sync::OnceLock::new();
+    PROJECT_ROOT
+        .get_or_init(|| {
+            let mut dir = std::env::current_dir().ok()?;
+            for _ in 0..5 {
+                if dir.join("app").join("bridge.py").exists() {
+                    return Some(dir);
+                }
+                if !dir.pop() { break; }
+            }
+            None
+        })
+        .clone()
+}
+
+#[derive(Serialize)]
+struct RuntimePaths {
+    // 包含 app/bridge.py 的项目根目录；None 表示未找到（安装包运行时通常如此）
+    project_root: Option<String>,
+    // 配置文件实际使用的根目录（未找到项目根目录时为当前工作目录）
+    config_root: String,
+    // 界面设置、统计与历史所在目录
+    config_dir: String,
+    // default / env / file，见 get_config_directory
+    config_dir_source: &'static str,
+    postprocess_path: String,
+    usage_stats_path: String,
+    // 随安装包分发的 bridge 可执行文件（None 表示未找到，将回退到 Python）
+    packaged_bridge: Option<String>,
+    current_dir: Option<String>,
+}
+
+// Tauri命令：返回各路径的解析结果，用于排查配置与桥接来自不同目录的问题
+#[tauri::command]
+fn get_runtime_paths(app: tauri::AppHandle) -> RuntimePaths {
+    let config_dir = get_config_directory();
+    RuntimePaths {
+        project_root: find_project_root().map(|p| p.to_string_lossy().to_string()),
+        config_root: find_project_root_for_config().to_string_lossy().to_string(),
+        config_dir: config_dir.dir,
+        config_dir_source: config_dir.source,
+        postprocess_path: resolve_postprocess_path().to_string_lossy().to_string(),
+        usage_stats_path: resolve_usage_stats_path().to_string_lossy().to_string(),
+        packaged_bridge: find_packaged_bridge_executable(&app).map(|p| p.to_string_lossy().to_string()),
+        current_dir: std::env::current_dir().ok().map(|p| p.to_string_lossy().to_string()),
+    }
+}
+
+// 设置中的自定义 bridge 路径；文件已不存在时忽略并回退到自动探测
+fn bridge_override_executable(settings: &UiSettings) -> Option<PathBuf> {
+    let raw = settings.bridge_exe_override.as_deref().map(str::trim).filter(|p| !p.is_empty())?;
+    let path = PathBuf::from(raw);
+    if path.is_file() {
+        Some(path)
+    } else {
+        warn!("[tauri] 自定义 bridge 路径无效，回退到自动探测: {}", raw);
+        None
+    }
+}
+
+// Tauri命令：获取自定义 bridge 可执行文件路径（None 表示自动探测）
+#[tauri::command]
+fn get_bridge_override() -> Option<String> {
+    load_ui_settings().bridge_exe_override
+}
+
+// Tauri命令：设置自定义 bridge 路径（None 或空字符串恢复自动探测），保存后重启桥接使其生效
+#[tauri::command]
+async fn set_bridge_override(app: tauri::AppHandle, path: Option<String>) -> Result<Option<String>, String> {
+    let path = path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    if let Some(p) = path.as_deref() {
+        let candidate = std::path::Path::new(p);
+        if !candidate.exists() {
+            return Err(format!("路径不存在: {}", p));
+        }
+        if !candidate.is_file() {
+            return Err(format!("路径不是文件: {}", p));
+        }
+    }
+    let mut settings = load_ui_settings();
+    if settings.bridge_exe_override == path {
+        return Ok(path);
+    }
+    settings.bridge_exe_override = path.clone();
+    save_ui_settings(&settings)?;
+    info!("[tauri] 自定义 bridge 路径已更新: {:?}", path);
+
+    // 桥接已停用时仅保存，下次 start_bridge 启动时生效
+    if app.state::<BridgeState>().disabled.load(Ordering::SeqCst) {
+        return Ok(path);
+    }
+    restart_bridge(app.clone(), app.state()).await?;
+    Ok(path)
+}
+
+// 优先查找随安装包一起分发的 onedir 可执行文件（通过 Tauri 资源路径解析，安装/开发环境均兼容）
+fn find_packaged_bridge_executable(app: &tauri::AppHandle) -> Option<PathBuf> {
+    #[cfg(windows)]
+    let exe_name = "bridge.exe";
+    #[cfg(not(windows))]
+    let exe_name = "bridge";
+
+    let rel_candidates = [
+        format!("bin/bridge/{}", exe_name),
+        format!("bridge/{}", exe_name),
+        format!("{}", exe_name),
+    ];
+
+    for rel in rel_candidates.iter() {
+        if let Ok(path) = app.path().resolve(rel, BaseDirectory::Resource) {
+            if path.exists() && path.is_file() {
+                return Some(path);
+            }
+        }
+    }
+    None
+}
+
+// 选择 Python 解释器（优先 .venv/venv/env，其次环境变量 SK_PYTHON，最后回退到系统 python）
+fn find_python_executable(project_root: &std::path::Path) -> OsString {
+    if let Ok(val) = std::env::var("SK_PYTHON") {
+        if !val.trim().is_empty() {
+            return OsString::from(val);
+        }
+    }
+    #[cfg(windows)]
+    let candidates = [
+        project_root.join(".venv").join("Scripts").join("python.exe"),
+        project_root.join("venv").join("Scripts").join("python.exe"),
+        project_root.join("env").join("Scripts").join("python.exe"),
+    ];
+    #[cfg(not(windows))]
+    let candidates = [
+        project_root.join(".venv").join("bin").join("python3"),
+        project_root.join("venv").join("bin").join("python3"),
+        project_root.join("env").join("bin").join("python3"),
+    ];
+    for p in candidates.iter() {
+        if p.exists() && p.is_file() {
+            return OsString::from(p.as_os_str());
+        }
+    }
+    // 回退
+    OsString::from("python")
+}
+
+// 在 PATH 中查找可执行文件（解释器为裸命令名时用于诊断）
+fn find_in_path(name: &std::ffi::OsStr) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths).find_map(|dir| {
+        let candidate = dir.join(name);
+        #[cfg(windows)]
+        let candidate = if candidate.extension().is_none() { candidate.with_extension("exe") } else { candidate };
+        candidate.is_file().then_some(candidate)
+    })
+}
+
+#[derive(Serialize)]
+struct EnvironmentDiagnostics {
+    // 实际启动方式：override（设置中的自定义路径）、packaged（随安装包分发的 bridge 可执行文件）或 python
+    launcher: &'static str,
+    bridge_override: Option<String>,
+    bridge_executable: Option<String>,
+    python_executable: String,
+    // 解释器路径解析后的位置（裸命令名时按 PATH 查找）；None 表示未找到
+    python_resolved: Option<String>,
+    python_exists: bool,
+    project_root: Option<String>,
+    bridge_script_found: bool,
+    current_dir: Option<String>,
+}
+
+// Tauri命令：环境诊断报告（解释器/打包的 bridge、项目根目录），便于附在问题反馈中
+#[tauri::command]
+fn diagnose_environment(app: tauri::AppHandle) -> EnvironmentDiagnostics {
+    let project_root = find_project_root();
+    let root_for_python = project_root
+        .clone()
+        .or_else(|| std::env::current_dir().ok())
+        .unwrap_or_else(|| PathBuf::from("."));
+    let python = find_python_executable(&root_for_python);
+    let python_path = PathBuf::from(&python);
+    let python_resolved = if python_path.components().count() > 1 {
+        python_path.is_file().then_some(python_path)
+    } else {
+        find_in_path(&python)
+    };
+    let bridge_executable = find_packaged_bridge_executable(&app);
+    let settings = load_ui_settings();
+    let launcher = if bridge_override_executable(&settings).is_some() {
+        "override"
+    } else if bridge_executable.is_some() {
+        "packaged"
+    } else {
+        "python"
+    };
+    EnvironmentDiagnostics {
+        launcher,
+        bridge_override: settings.bridge_exe_override,
+        bridge_executable: bridge_executable.map(|p| p.display().to_string()),
+        python_executable: python.to_string_lossy().to_string(),
+        python_exists: python_resolved.is_some(),
+        python_resolved: python_resolved.map(|p| p.display().to_string()),
+        bridge_script_found: project_root.as_ref().is_some_and(|root| root.join("app").join("bridge.py").is_file()),
+        project_root: project_root.map(|p| p.display().to_string()),
+        current_dir: std::env::current_dir().ok().map(|p| p.display().to_string()),
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let log_state = init_logging();
+    set_current_locale(&load_ui_settings().locale);
     tauri::Builder::default()
+        // 单实例需最先注册：再次启动（含深度链接）时转发给已运行的实例后退出，深度链接由 deep-link 插件转发
+        .plugin(tauri_plugin_single_instance::init(|app, argv, _cwd| {
+            info!("[tauri] 检测到重复启动，参数: {:?}", argv);
+            // 携带深度链接的启动交由深度链接处理（其中 show 动作同样会显示窗口），普通启动则显示悬浮窗
+            let has_deep_link = argv.iter().any(|arg| arg.to_lowercase().starts_with("speakkeyboard://"));
+            if !has_deep_link {
+                show_widget_window(app);
+            }
+        }))
+        .plugin(tauri_plugin_deep_link::init())
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_clipboard_manager::init())
         .plugin(tauri_plugin_global_shortcut::Builder::new().build())
-        .plugin(tauri_plugin_autostart::init(tauri_plugin_autostart::MacosLauncher::LaunchAgent, Some(vec!["--flag1", "--flag2"])))
+        .plugin(tauri_plugin_autostart::init(tauri_plugin_autostart::MacosLauncher::LaunchAgent, Some(vec![START_MINIMIZED_ARG])))
         .manage(AppState::default())
+        .manage(log_state)
         .manage(BridgeState {
             stdin: Arc::new(tokio::sync::Mutex::new(None)),
             should_restart: Arc::new(AtomicBool::new(true)),
+            manual_restart: Arc::new(AtomicBool::new(false)),
+            restart_notify: Arc::new(tokio::sync::Notify::new()),
+            process_info: Arc::new(Mutex::new(BridgeProcessInfo::default())),
+            last_pong: Arc::new(Mutex::new(None)),
+            pending_requests: Arc::new(Mutex::new(std::collections::HashMap::new())),
+            shutdown_done: Arc::new(tokio::sync::Notify::new()),
+            disabled: Arc::new(AtomicBool::new(false)),
+            last_error: Arc::new(Mutex::new(None)),
+            version: Arc::new(Mutex::new(None)),
+            next_request_id: Arc::new(AtomicU64::new(1)),
         })
         .setup(|app| {
-            {
-                let state = app.state::<AppState>();
-                init_recording_hotkey(&app.app_handle(), &state);
-            }
             // 启动 Python 桥接进程（自动探测项目根目录）
-            fn find_project_root() -> Option<PathBuf> {
-                let mut dir = std::env::current_dir().ok()?;
-                for _ in 0..5 {
-                    if dir.join("app").join("bridge.py").exists() {
-                        return Some(dir);
-                    }
-                    if !dir.pop() { break; }
-                }
-                None
-            }
-
-            // 优先查找随安装包一起分发的 onedir 可执行文件（通过 Tauri 资源路径解析，安装/开发环境均兼容）
-            fn find_packaged_bridge_executable(app: &tauri::AppHandle) -> Option<PathBuf> {
-                #[cfg(windows)]
-                let exe_name = "bridge.exe";
-                #[cfg(not(windows))]
-                let exe_name = "bridge";
-
-                let rel_candidates = [
-                    format!("bin/bridge/{}", exe_name),
-                    format!("bridge/{}", exe_name),
-                    format!("{}", exe_name),
-                ];
-
-                for rel in rel_candidates.iter() {
-                    if let Ok(path) = app.path().resolve(rel, BaseDirectory::Resource) {
-                        if path.exists() && path.is_file() {
-                            return Some(path);
-                        }
-                    }
-                }
-                None
-            }
-
-            // 选择 Python 解释器（优先 .venv/venv/env，其次环境变量 SK_PYTHON，最后回退到系统 python）
-            fn find_python_executable(project_root: &std::path::Path) -> OsString {
-                if let Ok(val) = std::env::var("SK_PYTHON") {
-                    if !val.trim().is_empty() {
-                        return OsString::from(val);
-                    }
-                }
-                #[cfg(windows)]
-                let candidates = [
-                    project_root.join(".venv").join("Scripts").join("python.exe"),
-                    project_root.join("venv").join("Scripts").join("python.exe"),
-                    project_root.join("env").join("Scripts").join("python.exe"),
-                ];
-                #[cfg(not(windows))]
-                let candidates = [
-                    project_root.join(".venv").join("bin").join("python3"),
-                    project_root.join("venv").join("bin").join("python3"),
-                    project_root.join("env").join("bin").join("python3"),
-                ];
-                for p in candidates.iter() {
-                    if p.exists() && p.is_file() {
-                        return OsString::from(p.as_os_str());
-                    }
-                }
-                // 回退
-                OsString::from("python")
-            }
-
-            let project_root = find_project_root().unwrap_or_else(|| std::env::current_dir().unwrap());
-            println!("准备启动桥接进程，项目根目录: {:?}", project_root);
+            let project_root = find_project_root_for_config();
+            info!("准备启动桥接进程，项目根目录: {:?}", project_root);
 
             let py = find_python_executable(&project_root);
-            println!("将使用 Python 解释器（回退路径）: {:?}", py);
+            info!("将使用 Python 解释器（回退路径）: {:?}", py);
 
             let app_handle = app.handle().clone();
             let app_state = app.state::<AppState>();
             init_recording_hotkey(&app_handle, &app_state);
 
+            if let Err(e) = start_postprocess_watcher(&app_handle) {
+                warn!("[tauri] {}", e);
+            }
+
             // 循环守护：子进程退出后自动重启（带简单退避）
             let restart_flag = app.state::<BridgeState>().should_restart.clone();
+            let manual_restart = app.state::<BridgeState>().manual_restart.clone();
+            let restart_notify = app.state::<BridgeState>().restart_notify.clone();
+            let process_info = app.state::<BridgeState>().process_info.clone();
+            let last_pong = app.state::<BridgeState>().last_pong.clone();
+            let shutdown_done = app.state::<BridgeState>().shutdown_done.clone();
+            let bridge_disabled = app.state::<BridgeState>().disabled.clone();
+            let last_bridge_error = app.state::<BridgeState>().last_error.clone();
+            let bridge_version = app.state::<BridgeState>().version.clone();
+            // 仅开机自启时延迟首次启动，避免音频服务尚未就绪导致反复重启
+            let startup_delay = if launched_minimized() {
+                load_ui_settings().autostart_delay_secs.min(MAX_AUTOSTART_DELAY_SECS)
+            } else {
+                0
+            };
             tauri::async_runtime::spawn(async move {
+                if startup_delay > 0 {
+                    info!("[tauri] 开机自启：延迟 {} 秒后启动桥接进程", startup_delay);
+                    tokio::time::sleep(Duration::from_secs(startup_delay)).await;
+                }
                 let mut attempts: u32 = 0;
                 loop {
                     if !restart_flag.load(Ordering::SeqCst) {
-                        println!("[tauri] 收到停止重启信号，结束桥接守护循环");
-                        break;
+                        if !bridge_disabled.load(Ordering::SeqCst) {
+                            info!("[tauri] 收到停止重启信号，结束桥接守护循环");
+                            break;
+                        }
+                        // 用户停用：挂起等待 start_bridge
+                        info!("[tauri] 桥接进程已停用，等待重新启用");
+                        restart_notify.notified().await;
+                        manual_restart.store(false, Ordering::SeqCst);
+                        attempts = 0;
+                        continue;
                     }
                     attempts += 1;
-                    println!("[tauri] 尝试启动桥接进程（尝试次数 {}）", attempts);
+                    info!("[tauri] 尝试启动桥接进程（尝试次数 {}）", attempts);
+                    if let Ok(mut info) = process_info.lock() {
+                        info.restart_attempts = attempts;
+                    }
 
-                    // 优先使用随 Tauri 安装包分发的 onedir 可执行文件
-                    let mut cmd = if let Some(bridge_exe) = find_packaged_bridge_executable(&app_handle) {
-                        println!("[tauri] 检测到打包的 bridge 可执行文件: {:?}", bridge_exe);
+                    // 用户指定的 bridge 优先，其次随 Tauri 安装包分发的 onedir 可执行文件
+                    let settings = load_ui_settings();
+                    let mut cmd = if let Some(override_exe) = bridge_override_executable(&settings) {
+                        info!("[tauri] 使用自定义 bridge 可执行文件: {:?}", override_exe);
+                        let mut c = Command::new(&override_exe);
+                        if let Some(dir) = override_exe.parent() {
+                            c.current_dir(dir);
+                        }
+                        c
+                    } else if let Some(bridge_exe) = find_packaged_bridge_executable(&app_handle) {
+                        info!("[tauri] 检测到打包的 bridge 可执行文件: {:?}", bridge_exe);
                         let mut c = Command::new(&bridge_exe);
                         if let Some(dir) = bridge_exe.parent() {
                             c.current_dir(dir);
                         }
                         c
                     } else {
-                        println!("[tauri] 未检测到打包的 bridge，可回退到 Python 启动 app.bridge");
+                        info!("[tauri] 未检测到打包的 bridge，可回退到 Python 启动 app.bridge");
                         let mut c = Command::new(&py);
                         c.arg("-u").arg("-m").arg("app.bridge");
-                        // 可按需添加 --config / --save-dataset / --dataset-dir
-                        // c.arg("--save-dataset");
-                        // c.arg("--dataset-dir").arg("dataset");
+                        // 可按需添加 --config；数据集采集在 bridge_ready 后通过 set_dataset 指令下发
                         c.current_dir(&project_root);
                         c
                     };
 
+                    // 已选择的转写模型（模型在桥接启动时加载）
+                    let model = settings.model;
+                    if !model.trim().is_empty() {
+                        cmd.env("FUNASR_ASR_MODEL", model.trim());
+                    }
+                    // 配置目录已重定位时，桥接从同一目录读取替换词典
+                    if let Some((dir, _)) = config_dir_override() {
+                        cmd.env(CONFIG_DIR_ENV, dir);
+                    }
+
                     // Windows: 隐藏子进程控制台窗口
                     #[cfg(windows)]
                     {
@@ -982,7 +7343,16 @@ pub fn run() {
 
                     match cmd.spawn() {
                         Ok(mut child) => {
-                            println!("[tauri] 桥接进程已启动 (pid=?)，绑定stdin与事件通道");
+                            info!("[tauri] 桥接进程已启动 (pid={:?})，绑定stdin与事件通道", child.id());
+                            if let Ok(mut info) = process_info.lock() {
+                                info.pid = child.id();
+                            }
+                            if let Ok(mut pong) = last_pong.lock() {
+                                *pong = None;
+                            }
+                            if let Ok(mut version) = bridge_version.lock() {
+                                *version = None;
+                            }
                             // 绑定 stdin
                             {
                                 let stdin_arc = {
@@ -992,16 +7362,41 @@ pub fn run() {
                                 let mut guard = stdin_arc.lock().await;
                                 *guard = child.stdin.take();
                             }
+                            let _ = app_handle.emit("bridge-event", serde_json::json!({
+                                "event": "bridge_started",
+                                "attempt": attempts,
+                                "pid": child.id(),
+                                "timestamp": Local::now().to_rfc3339()
+                            }));
+
+                            // 启动心跳看门狗（与本次子进程生命周期绑定）
+                            let hung = Arc::new(tokio::sync::Notify::new());
+                            let watchdog = {
+                                let stdin_arc = app_handle.state::<BridgeState>().stdin.clone();
+                                tauri::async_runtime::spawn(run_bridge_watchdog(stdin_arc, last_pong.clone(), hung.clone()))
+                            };
 
                             // 读取 stdout，逐行解析并转发事件
                             if let Some(stdout) = child.stdout.take() {
                                 let mut reader = BufReader::new(stdout);
                                 let mut buf: Vec<u8> = Vec::with_capacity(4096);
+                                // 上一次 recording_state 的值，仅在状态翻转时播放提示音
+                                let mut last_recording_flag = false;
                                 loop {
                                     buf.clear();
-                                    match reader.read_until(b'\n', &mut buf).await {
+                                    let read = tokio::select! {
+                                        read = reader.read_until(b'\n', &mut buf) => read,
+                                        _ = hung.notified() => {
+                                            warn!("[tauri] 心跳超时，结束卡死的桥接进程");
+                                            if let Err(err) = child.start_kill() {
+                                                warn!("[tauri] 结束桥接进程失败: {}", err);
+                                            }
+                                            break;
+                                        }
+                                    };
+                                    match read {
                                         Ok(0) => { // EOF
-                                            println!("[tauri] 桥接事件通道到达 EOF");
+                                            info!("[tauri] 桥接事件通道到达 EOF");
                                             break;
                                         }
                                         Ok(_n) => {
@@ -1010,68 +7405,148 @@ pub fn run() {
                                             if line.is_empty() { continue; }
                                             match serde_json::from_str::<Value>(line) {
                                                 Ok(val) => {
+                                                    // 带 req_id 的应答交给等待中的请求，不再广播给前端
+                                                    if let Some(req_id) = val.get("req_id").and_then(|v| v.as_u64()) {
+                                                        let sender = app_handle
+                                                            .state::<BridgeState>()
+                                                            .pending_requests
+                                                            .lock()
+                                                            .ok()
+                                                            .and_then(|mut pending| pending.remove(&req_id));
+                                                        if let Some(sender) = sender {
+                                                            let _ = sender.send(val);
+                                                            continue;
+                                                        }
+                                                    }
                                                     // 同步录音状态 + 统计累加
                                                     if let Some(event_name) = val.get("event").and_then(|v| v.as_str()) {
+                                                        if event_name == "pong" || event_name == "bridge_ready" {
+                                                            if let Ok(mut pong) = last_pong.lock() {
+                                                                *pong = Some(Instant::now());
+                                                            }
+                                                            // 心跳应答不转发给前端
+                                                            if event_name == "pong" { continue; }
+                                                        }
+                                                        if event_name == "bridge_shutdown"
+                                                            && val.get("reason").and_then(|v| v.as_str()) == Some("graceful")
+                                                        {
+                                                            shutdown_done.notify_waiters();
+                                                        }
+                                                        if event_name == "bridge_ready" {
+                                                            // 桥接（重新）就绪：重新下发已保存的设置
+                                                            let app_for_settings = app_handle.clone();
+                                                            tauri::async_runtime::spawn(async move {
+                                                                apply_saved_bridge_settings(&app_for_settings).await;
+                                                            });
+                                                        }
                                                         if event_name == "recording_state" {
                                                             if let Some(flag) = val.get("is_recording").and_then(|v| v.as_bool()) {
                                                                 let app_state = app_handle.state::<AppState>();
                                                                 let mut rec = app_state.is_recording.lock().unwrap();
                                                                 *rec = flag;
-                                                                println!("[tauri] 收到 recording_state 事件：is_recording={}", flag);
+                                                                debug!("[tauri] 收到 recording_state 事件：is_recording={}", flag);
+                                                                drop(rec);
+                                                                // 停止（或重新开始）录音时结束暂停
+                                                                set_paused_state(&app_handle, false);
+                                                                if !flag {
+                                                                    // 桥接侧保留目标直到本次结果输出完成
+                                                                    *app_state.output_target.lock().unwrap() = None;
+                                                                    *app_state.recording_started_at.lock().unwrap() = None;
+                                                                }
+                                                                update_sleep_inhibit(&app_handle, flag);
+                                                                update_tray_recording_state(&app_handle, flag);
+                                                                if flag {
+                                                                    refresh_widget_click_through(&app_handle);
+                                                                } else {
+                                                                    schedule_widget_click_through(&app_handle);
+                                                                }
+                                                                if widget_autohide_delay(&load_ui_settings()).is_some() {
+                                                                    if flag {
+                                                                        // 仅显示不聚焦，避免抢走输出目标窗口的焦点
+                                                                        cancel_widget_autohide(&app_handle);
+                                                                        if let Some(window) = app_handle.get_webview_window("widget") {
+                                                                            let _ = window.show();
+                                                                        }
+                                                                    } else {
+                                                                        schedule_widget_autohide(&app_handle);
+                                                                    }
+                                                                }
+                                                                if flag != last_recording_flag {
+                                                                    last_recording_flag = flag;
+                                                                    play_feedback_sound(&app_handle, flag);
+                                                                    if flag {
+                                                                        arm_max_recording_timer(&app_handle);
+                                                                    }
+                                                                }
+                                                                if !flag {
+                                                                    disarm_max_recording_timer(&app_handle);
+                                                                }
                                                             }
+                                                        } else if event_name == "partial_transcription" {
+                                                            // 中间结果只用于悬浮窗实时显示，不计入统计与历史
+                                                            if load_ui_settings().show_partials {
+                                                                let text = val.get("text").and_then(|v| v.as_str()).unwrap_or_default();
+                                                                if let Some(win) = app_handle.get_webview_window("widget") {
+                                                                    let _ = win.emit("partial-transcription", serde_json::json!({ "text": text }));
+                                                                }
+                                                            }
+                                                        } else if event_name == "recording_paused" {
+                                                            if let Some(paused) = val.get("paused").and_then(|v| v.as_bool()) {
+                                                                debug!("[tauri] 收到 recording_paused 事件：paused={}", paused);
+                                                                set_paused_state(&app_handle, paused);
+                                                            }
+                                                        } else if event_name == "transcription_result"
+                                                            && val.get("cancelled").and_then(|v| v.as_bool()) == Some(true)
+                                                        {
+                                                            // 已取消的录音不计入统计
+                                                            info!("[tauri] 收到已取消的 transcription_result，跳过统计");
                                                         } else if event_name == "transcription_result" {
                                                             let mut changed = false;
-                                                            // 节省时间
-                                                            if let Some(duration) = val.get("duration").and_then(|v| v.as_f64()) {
+                                                            // 已转录字数：仅统计 text（排除空白字符，但不排除标点）。
+                                                            // text 为桥接实际输出的文本（已应用结尾裁剪与语音格式指令），格式指令短语本身不计入
+                                                            let add_chars = val.get("text").and_then(|v| v.as_str()).map(|t| t.chars().filter(|c| !c.is_whitespace()).count() as u64).unwrap_or(0);
+                                                            let settings = load_ui_settings();
+                                                            let add_corr = corrections_from_result(&val, settings.count_dictionary_as_corrections);
+
+                                                            // 录音次数、录音时长、节省时间、字数与自动修正次数一次写入（缺少 duration 时按 0 计）
+                                                            {
+                                                                let duration = val.get("duration").and_then(|v| v.as_f64()).unwrap_or(0.0);
                                                                 let dur = if duration.is_sign_negative() { 0.0 } else { duration };
-                                                                let saved = dur * 2.2_f64;
-                                                                if let Ok(snapshot) = accumulate_saved_time(&app_handle, saved) {
-                                                                    // 将最新快照先广播（后续还会覆盖一次，保持简单）
-                                                                    let _ = app_handle.emit("stats-updated", serde_json::json!({
-                                                                        "today_sec": snapshot.today_sec,
-                                                                        "total_sec": snapshot.total_sec,
-                                                                        "today_chars": snapshot.today_chars,
-                                                                        "total_chars": snapshot.total_chars,
-                                                                        "today_corrections": snapshot.today_corrections,
-                                                                        "total_corrections": snapshot.total_corrections
-                                                                    }));
+                                                                let estimated = record_typing_sample(&app_handle, add_chars, dur);
+                                                                let multiplier = match estimated {
+                                                                    Some(m) if settings.auto_time_multiplier => m,
+                                                                    _ => effective_time_saved_multiplier(&settings),
+                                                                };
+                                                                let inc = UsageIncrement {
+                                                                    duration_sec: dur,
+                                                                    saved_sec: dur * multiplier,
+                                                                    chars: add_chars,
+                                                                    corrections: add_corr,
+                                                                };
+                                                                if let Ok(snapshot) = accumulate_transcription(&app_handle, inc) {
+                                                                    emit_usage_stats(&app_handle, &snapshot);
                                                                     changed = true;
                                                                 }
                                                             }
 
-                                                            // 已转录字数与自动修正次数
-                                                            // 仅统计 text（排除空白字符，但不排除标点）
-                                                            let mut add_chars: u64 = 0;
-                                                            if let Some(text) = val.get("text").and_then(|v| v.as_str()) {
-                                                                let count = text.chars().filter(|c| !c.is_whitespace()).count() as u64;
-                                                                add_chars = count;
-                                                            }
-                                                            let add_corr: u64 = val.get("corrections").and_then(|v| v.as_i64()).map(|v| if v < 0 { 0 } else { v as u64 }).unwrap_or(0);
-
-                                                            if add_chars > 0 || add_corr > 0 {
-                                                                if let Ok(snapshot) = accumulate_chars_and_corrections(&app_handle, add_chars, add_corr) {
-                                                                    let _ = app_handle.emit("stats-updated", serde_json::json!({
-                                                                        "today_sec": snapshot.today_sec,
-                                                                        "total_sec": snapshot.total_sec,
-                                                                        "today_chars": snapshot.today_chars,
-                                                                        "total_chars": snapshot.total_chars,
-                                                                        "today_corrections": snapshot.today_corrections,
-                                                                        "total_corrections": snapshot.total_corrections
-                                                                    }));
-                                                                    changed = true;
+                                                            // 记录转写历史（空文本不记录）
+                                                            if let Some(text) = val.get("text").and_then(|v| v.as_str()).filter(|t| !t.trim().is_empty()) {
+                                                                *app_handle.state::<AppState>().last_transcription.lock().unwrap() = Some(text.to_string());
+                                                                let entry = TranscriptionHistoryEntry {
+                                                                    timestamp: Local::now().to_rfc3339(),
+                                                                    text: text.to_string(),
+                                                                    duration: val.get("duration").and_then(|v| v.as_f64()).filter(|d| d.is_finite() && *d > 0.0).unwrap_or(0.0),
+                                                                    corrections: add_corr,
+                                                                };
+                                                                if let Err(e) = append_transcription_history(&app_handle, entry) {
+                                                                    warn!("[tauri] 记录转写历史失败: {}", e);
                                                                 }
                                                             }
+
                                                             if !changed {
                                                                 // 至少广播一次原样数据，保持前端事件节奏一致
                                                                 if let Ok(snapshot) = get_usage_stats(app_handle.clone()) {
-                                                                    let _ = app_handle.emit("stats-updated", serde_json::json!({
-                                                                        "today_sec": snapshot.today_sec,
-                                                                        "total_sec": snapshot.total_sec,
-                                                                        "today_chars": snapshot.today_chars,
-                                                                        "total_chars": snapshot.total_chars,
-                                                                        "today_corrections": snapshot.today_corrections,
-                                                                        "total_corrections": snapshot.total_corrections
-                                                                    }));
+                                                                    emit_usage_stats(&app_handle, &snapshot);
                                                                 }
                                                             }
                                                         }
@@ -1079,29 +7554,37 @@ pub fn run() {
                                                     let _ = app_handle.emit("bridge-event", val);
                                                 }
                                                 Err(err) => {
-                                                    println!("解析桥接输出失败: {} | 原始: {}", err, line);
+                                                    warn!("解析桥接输出失败: {} | 原始: {}", err, line);
                                                 }
                                             }
                                         }
                                         Err(err) => {
-                                            println!("[tauri] 读取桥接输出失败: {}，继续等待下一行", err);
+                                            warn!("[tauri] 读取桥接输出失败: {}，继续等待下一行", err);
                                             continue;
                                         }
                                     }
                                 }
                             }
 
-                            // 后台耗尽 stderr，避免阻塞（丢弃或按需打印）
+                            // 后台耗尽 stderr，避免阻塞；内容写入 config/bridge.log 便于排查
                             if let Some(stderr) = child.stderr.take() {
+                                let app_for_log = app_handle.clone();
                                 tauri::async_runtime::spawn(async move {
                                     let mut reader = BufReader::new(stderr);
-                                    let mut _buf: Vec<u8> = Vec::with_capacity(2048);
+                                    let mut buf: Vec<u8> = Vec::with_capacity(2048);
+                                    let mut log_failed = false;
                                     loop {
-                                        _buf.clear();
-                                        match reader.read_until(b'\n', &mut _buf).await {
+                                        buf.clear();
+                                        match reader.read_until(b'\n', &mut buf).await {
                                             Ok(0) => break, // EOF
                                             Ok(_) => {
-                                                // 如需调试可 println!("[bridge stderr] {}", String::from_utf8_lossy(&_buf));
+                                                if let Err(e) = append_bridge_log(&app_for_log, &buf) {
+                                                    // 只提示一次，避免磁盘问题时刷屏
+                                                    if !log_failed {
+                                                        warn!("[tauri] {}", e);
+                                                        log_failed = true;
+                                                    }
+                                                }
                                             }
                                             Err(_) => break,
                                         }
@@ -1110,13 +7593,20 @@ pub fn run() {
                             }
 
                             // 等待子进程退出状态，打印退出码
-                            match child.wait().await {
+                            let exit_code = match child.wait().await {
                                 Ok(status) => {
-                                    println!("[tauri] 桥接进程已退出，状态码: {:?}", status);
+                                    info!("[tauri] 桥接进程已退出，状态码: {:?}", status);
+                                    status.code()
                                 }
                                 Err(e) => {
-                                    println!("[tauri] 等待桥接进程退出失败: {}", e);
+                                    warn!("[tauri] 等待桥接进程退出失败: {}", e);
+                                    None
                                 }
+                            };
+                            watchdog.abort();
+                            if let Ok(mut info) = process_info.lock() {
+                                info.pid = None;
+                                info.last_exit_code = exit_code;
                             }
 
                             // 子进程退出：重置stdin、状态，并通知前端
@@ -1128,65 +7618,157 @@ pub fn run() {
                                 let mut guard = stdin_arc.lock().await;
                                 *guard = None;
                             }
+                            // 丢弃未完成的请求：等待方会收到通道关闭错误
+                            if let Ok(mut pending) = app_handle.state::<BridgeState>().pending_requests.lock() {
+                                pending.clear();
+                            }
                             {
                                 let app_state = app_handle.state::<AppState>();
                                 let mut rec = app_state.is_recording.lock().unwrap();
                                 *rec = false;
                             }
+                            set_paused_state(&app_handle, false);
+                            update_tray_recording_state(&app_handle, false);
+                            // 桥接崩溃时录音不会再有结束事件，立即释放防休眠并作废超时计时
+                            update_sleep_inhibit(&app_handle, false);
+                            disarm_max_recording_timer(&app_handle);
+                            // 进程已退出，无需继续等待清理完成事件
+                            shutdown_done.notify_waiters();
                             let _ = app_handle.emit("bridge-event", serde_json::json!({
                                 "event": "bridge_shutdown",
-                                "reason": "process_exit"
+                                "reason": "process_exit",
+                                "attempt": attempts,
+                                "exit_code": exit_code,
+                                "backoff_secs": restart_flag.load(Ordering::SeqCst).then(|| bridge_restart_delay_secs(attempts)),
+                                "timestamp": Local::now().to_rfc3339()
                             }));
 
                             if restart_flag.load(Ordering::SeqCst) {
-                                println!("[tauri] 桥接进程已退出，准备重启...");
+                                info!("[tauri] 桥接进程已退出，准备重启...");
+                            } else if bridge_disabled.load(Ordering::SeqCst) {
+                                info!("[tauri] 桥接进程已按用户要求停用");
+                                continue;
                             } else {
-                                println!("[tauri] 桥接进程已退出，守护已停止");
+                                info!("[tauri] 桥接进程已退出，守护已停止");
                                 break;
                             }
                         }
                         Err(err) => {
-                            println!("启动桥接进程失败: {}", err);
+                            error!("启动桥接进程失败: {}", err);
+                            let record = BridgeErrorRecord {
+                                message: format!("启动失败: {}", err),
+                                timestamp: Local::now().to_rfc3339(),
+                            };
                             let _ = app_handle.emit("bridge-event", serde_json::json!({
                                 "event": "bridge_error",
-                                "message": format!("启动失败: {}", err)
+                                "message": record.message,
+                                "attempt": attempts,
+                                "backoff_secs": restart_flag.load(Ordering::SeqCst).then(|| bridge_restart_delay_secs(attempts)),
+                                "timestamp": record.timestamp
                             }));
+                            if let Ok(mut guard) = last_bridge_error.lock() {
+                                *guard = Some(record);
+                            }
                         }
                     }
 
-                    // 简单退避（最多 30s）
+                    // 退避（默认最多 30s，可配置）；用户手动重启时重置计数并立即重启
                     if restart_flag.load(Ordering::SeqCst) {
-                        let delay_secs: u64 = std::cmp::min(30, 2 * (attempts as u64));
-                        println!("[tauri] {} 秒后重试启动桥接进程...", delay_secs);
-                        tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+                        if manual_restart.swap(false, Ordering::SeqCst) {
+                            info!("[tauri] 用户手动重启桥接进程，重置尝试次数");
+                            attempts = 0;
+                            continue;
+                        }
+                        let settings = load_ui_settings();
+                        if let Some(max_attempts) = settings.bridge_max_restart_attempts.filter(|m| *m > 0) {
+                            if attempts >= max_attempts {
+                                error!("[tauri] 桥接进程已重启 {} 次仍失败，停止重试，等待用户恢复", attempts);
+                                if let Ok(mut info) = process_info.lock() {
+                                    info.gave_up = true;
+                                }
+                                let _ = app_handle.emit("bridge-gave-up", serde_json::json!({
+                                    "attempts": attempts
+                                }));
+                                restart_notify.notified().await;
+                                info!("[tauri] 桥接守护循环已恢复，重置尝试次数");
+                                if let Ok(mut info) = process_info.lock() {
+                                    info.gave_up = false;
+                                }
+                                manual_restart.store(false, Ordering::SeqCst);
+                                attempts = 0;
+                                continue;
+                            }
+                        }
+                        let delay_secs = bridge_restart_delay_secs(attempts);
+                        info!("[tauri] {} 秒后重试启动桥接进程...", delay_secs);
+                        tokio::select! {
+                            _ = tokio::time::sleep(Duration::from_secs(delay_secs)) => {}
+                            _ = restart_notify.notified() => {
+                                info!("[tauri] 退避等待被手动重启打断");
+                                manual_restart.store(false, Ordering::SeqCst);
+                                attempts = 0;
+                            }
+                        }
+                    } else if bridge_disabled.load(Ordering::SeqCst) {
+                        continue;
                     } else {
-                        println!("[tauri] 守护循环收到停止指令，终止退出");
+                        info!("[tauri] 守护循环收到停止指令，终止退出");
                         break;
                     }
                 }
             });
-            // 阻止设置窗口关闭时被销毁，改为隐藏
-            if let Some(settings_window) = app.get_webview_window("settings") {
-                let window_clone = settings_window.clone();
-                settings_window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                        println!("设置窗口关闭请求，隐藏而不是销毁");
-                        api.prevent_close();
-                        let _ = window_clone.hide();
+            // 悬浮窗：恢复上次位置与置顶设置，并在移动/缩放后保存
+            if let Some(widget_window) = app.get_webview_window("widget") {
+                place_widget_window(&widget_window);
+                start_widget_anchor_watcher(app.handle());
+                let ui_settings = load_ui_settings();
+                if let Err(e) = widget_window.set_always_on_top(ui_settings.widget_always_on_top) {
+                    warn!("应用悬浮窗置顶设置失败: {}", e);
+                }
+                // 默认完全不透明时无需调用平台接口
+                if ui_settings.widget_opacity < MAX_WIDGET_OPACITY {
+                    if let Err(e) = apply_window_opacity(&widget_window, ui_settings.widget_opacity) {
+                        warn!("应用悬浮窗不透明度失败: {}", e);
+                    }
+                }
+                let app_handle = app.handle().clone();
+                widget_window.on_window_event(move |event| {
+                    if matches!(event, tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_)) {
+                        schedule_save_window_geometry(&app_handle, "widget");
                     }
                 });
+                // 最小化启动：隐藏悬浮窗，仅保留托盘（桥接进程照常启动）
+                if launched_minimized() || ui_settings.start_minimized || widget_recording_only(&ui_settings) {
+                    info!("[tauri] 以最小化方式启动或仅录音时显示，悬浮窗保持隐藏");
+                    if let Err(e) = widget_window.hide() {
+                        warn!("隐藏悬浮窗失败: {}", e);
+                    }
+                }
+            }
+
+            // 设置窗口：恢复上次位置与尺寸；关闭时隐藏而不是销毁
+            if let Some(settings_window) = app.get_webview_window(SETTINGS_WINDOW_LABEL) {
+                init_settings_window(&settings_window);
             }
 
+            // 应用保存的界面主题（窗口均已创建）
+            apply_window_theme(app.handle(), &load_ui_settings().theme);
+            refresh_widget_click_through(app.handle());
+
             // 设置系统托盘
+            let open_config_i = MenuItem::with_id(app, "open_config", "打开配置文件夹", true, None::<&str>)?;
             let quit_i = MenuItem::with_id(app, "quit", "退出", true, None::<&str>)?;
             let show_i = MenuItem::with_id(app, "show", "显示主窗口", true, None::<&str>)?;
-            let menu = Menu::with_items(app, &[&show_i, &quit_i])?;
+            let settings_i = MenuItem::with_id(app, "open_settings", "设置", true, None::<&str>)?;
+            let toggle_i = MenuItem::with_id(app, "toggle_recording", tray_toggle_label(false), true, None::<&str>)?;
+            *app.state::<AppState>().tray_toggle_item.lock().unwrap() = Some(toggle_i.clone());
+            let menu = Menu::with_items(app, &[&toggle_i, &show_i, &settings_i, &open_config_i, &quit_i])?;
             
             let should_restart_flag = app.state::<BridgeState>().should_restart.clone();
 
             let should_restart_flag_clone = should_restart_flag.clone();
 
-            let _tray = TrayIconBuilder::new()
+            let _tray = TrayIconBuilder::with_id(TRAY_ID)
                 .icon(app.default_window_icon().unwrap().clone())
                 .menu(&menu)
                 .show_menu_on_left_click(false)
@@ -1195,50 +7777,72 @@ pub fn run() {
                         should_restart_flag_clone.store(false, Ordering::SeqCst);
                         let bridge_state = app.state::<BridgeState>();
                         let stdin_arc = bridge_state.stdin.clone();
+                        let shutdown_done = bridge_state.shutdown_done.clone();
                         let app_handle = app.clone();
                         tauri::async_runtime::spawn(async move {
-                            println!("[tauri] 托盘退出：尝试发送 shutdown 指令给桥接进程");
-                            let mut guard = stdin_arc.lock().await;
-                            if let Some(stdin) = guard.as_mut() {
-                                let payload = serde_json::json!({"cmd": "shutdown"}).to_string() + "\n";
-                                if let Err(err) = stdin.write_all(payload.as_bytes()).await {
-                                    println!("[tauri] 托盘退出写入 shutdown 失败: {}", err);
-                                } else if let Err(err) = stdin.flush().await {
-                                    println!("[tauri] 托盘退出刷新 shutdown 失败: {}", err);
-                                } else {
-                                    println!("[tauri] 托盘退出已发送 shutdown 指令");
-                                }
-                                *guard = None;
-                            } else {
-                                println!("[tauri] 托盘退出时 stdin 不可用，跳过 shutdown");
-                            }
-                            // 等待 500ms 以便桥接完成清理
-                            tokio::time::sleep(Duration::from_millis(500)).await;
+                            shutdown_bridge_gracefully(&stdin_arc, &shutdown_done, "托盘退出").await;
+                            update_sleep_inhibit(&app_handle, false);
                             app_handle.exit(0);
                         });
                     }
-                    "show" => {
-                        if let Some(window) = app.get_webview_window("widget") {
-                    let _ = window.set_skip_taskbar(true);
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.unminimize();
+                    "toggle_recording" => {
+                        let app_handle = app.clone();
+                        tauri::async_runtime::spawn(async move {
+                            if let Err(e) = toggle_recording(app_handle.clone(), app_handle.state(), app_handle.state()).await {
+                                warn!("[tauri] 托盘切换录音失败: {}", e);
+                            }
+                        });
+                    }
+                    "open_settings" => {
+                        if let Err(e) = open_settings_window(app) {
+                            warn!("[tauri] 托盘打开设置失败: {}", e);
+                        }
+                    }
+                    "open_config" => {
+                        if let Err(e) = open_config_folder(app) {
+                            warn!("[tauri] {}", e);
+                            let _ = app.emit("tray-error", serde_json::json!({
+                                "action": "open_config",
+                                "message": e
+                            }));
                         }
                     }
+                    "show" => show_widget_window(app),
                     _ => {}
                 })
                 .on_tray_icon_event(|tray, event| {
                     if let TrayIconEvent::Click { button: tauri::tray::MouseButton::Left, .. } = event {
-                        let app = tray.app_handle();
-                        if let Some(window) = app.get_webview_window("widget") {
-                    let _ = window.set_skip_taskbar(true);
-                            let _ = window.show();
-                            let _ = window.set_focus();
-                            let _ = window.unminimize();
-                        }
+                        show_widget_window(tray.app_handle());
                     }
                 })
                 .build(app)?;
+            // 深度链接：开发模式及 Linux/Windows 需在运行时注册协议
+            #[cfg(any(windows, target_os = "linux"))]
+            if let Err(e) = app.deep_link().register_all() {
+                warn!("[tauri] 注册深度链接协议失败: {}", e);
+            }
+            if let Ok(Some(urls)) = app.deep_link().get_current() {
+                for url in urls.iter() {
+                    handle_deep_link(app.handle(), url);
+                }
+            }
+            let deep_link_app = app.handle().clone();
+            app.deep_link().on_open_url(move |event| {
+                for url in event.urls().iter() {
+                    handle_deep_link(&deep_link_app, url);
+                }
+            });
+
+            // 本地 HTTP 接口（仅在设置中启用时监听）
+            let http_app = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                if let Err(e) = start_http_api(&http_app).await {
+                    warn!("[tauri] {}", e);
+                }
+            });
+            if let Ok(snapshot) = get_usage_stats(app.handle().clone()) {
+                update_tray_tooltip(app.handle(), &snapshot);
+            }
 
             Ok(())
         })
@@ -1247,18 +7851,477 @@ pub fn run() {
             stop_recording,
             toggle_recording,
             get_recording_state,
+            get_recording_snapshot,
+            get_foreground_window_title,
+            get_blocked_app_titles,
+            add_blocked_app_title,
+            remove_blocked_app_title,
+            pause_recording,
+            resume_recording,
             get_postprocess_config,
             save_postprocess_config,
             get_usage_stats,
+            broadcast_usage_stats,
+            flush_usage_stats,
+            get_stats_emit_interval,
+            set_stats_emit_interval,
             toggle_window_visibility,
             show_window,
+            open_settings,
             hide_window,
             minimize_window,
             get_autostart_enabled,
             set_autostart_enabled,
+            get_start_minimized,
+            set_start_minimized,
+            get_autostart_delay_secs,
+            set_autostart_delay_secs,
+            get_theme,
+            get_locale,
+            set_locale,
+            set_theme,
+            set_widget_ignore_cursor,
+            get_widget_click_through,
+            get_widget_autohide,
+            set_widget_autohide,
+            get_widget_visibility_mode,
+            set_widget_visibility_mode,
+            set_widget_click_through,
+            reset_widget_position,
+            get_widget_anchor,
+            set_widget_anchor,
+            copy_last_transcription,
+            export_config_bundle,
+            import_config_bundle,
             get_recording_hotkey,
-            set_recording_hotkey
+            set_recording_hotkey,
+            get_hotkey_mode,
+            set_hotkey_mode,
+            get_start_stop_hotkeys,
+            set_start_stop_hotkeys,
+            get_cancel_hotkey,
+            set_cancel_hotkey,
+            list_hotkey_profiles,
+            save_hotkey_profile,
+            delete_hotkey_profile,
+            activate_hotkey_profile,
+            get_retype_hotkey,
+            set_retype_hotkey,
+            get_hotkey_registration_error,
+            get_widget_toggle_hotkey,
+            set_widget_toggle_hotkey,
+            get_hotkey_debounce,
+            set_hotkey_debounce,
+            get_min_recording_ms,
+            set_min_recording_ms,
+            get_double_tap_window,
+            set_double_tap_window,
+            probe_hotkey,
+            capture_next_hotkey,
+            list_registered_shortcuts,
+            trigger_hotkey_test,
+            restart_bridge,
+            stop_bridge,
+            start_bridge,
+            get_bridge_status,
+            get_last_bridge_error,
+            clear_bridge_error,
+            get_versions,
+            get_bridge_restart_policy,
+            set_bridge_restart_policy,
+            resume_bridge_restarts,
+            export_postprocess_csv,
+            import_postprocess_csv,
+            preview_postprocess,
+            restore_postprocess_backup,
+            get_usage_history,
+            reset_usage_stats,
+            get_time_saved_multiplier,
+            set_time_saved_multiplier,
+            get_estimated_time_multiplier,
+            set_auto_time_multiplier,
+            get_count_dictionary_as_corrections,
+            set_count_dictionary_as_corrections,
+            export_usage_stats_csv,
+            get_always_on_top,
+            set_always_on_top,
+            get_widget_opacity,
+            set_widget_opacity,
+            list_audio_devices,
+            get_audio_device,
+            set_audio_device,
+            get_available_models,
+            get_active_model,
+            list_languages,
+            diagnose_environment,
+            check_config_writable,
+            get_runtime_paths,
+            get_config_directory,
+            set_config_directory,
+            get_bridge_override,
+            set_bridge_override,
+            benchmark_transcription,
+            get_last_benchmark,
+            set_active_model,
+            get_output_method,
+            set_output_method,
+            get_trim_trailing,
+            set_trim_trailing,
+            get_type_delay,
+            set_type_delay,
+            get_vad_settings,
+            set_vad_settings,
+            get_show_partials,
+            get_voice_formatting,
+            set_voice_formatting,
+            set_show_partials,
+            get_dataset_collection,
+            set_dataset_collection,
+            get_sound_feedback,
+            set_sound_feedback,
+            get_prevent_sleep,
+            get_max_recording_secs,
+            set_max_recording_secs,
+            set_prevent_sleep,
+            get_http_api_settings,
+            set_http_api_settings,
+            get_first_run,
+            complete_onboarding,
+            get_log_level,
+            set_log_level,
+            get_recent_logs,
+            get_bridge_log_size,
+            clear_bridge_log,
+            get_transcription_history,
+            clear_transcription_history,
+            export_history,
+            import_history,
+            retype_last,
+            type_text
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_ui_settings_upgrades_v0() {
+        let v0 = serde_json::json!({
+            "recording_hotkey": "F3",
+            "hotkey_mode": "Push_To_Talk",
+            "start_hotkey": "",
+            "stop_hotkey": "F6",
+            "cancel_hotkey": "  ",
+            "output_method": "Clipboard",
+            "log_level": "DEBUG"
+        });
+
+        let (value, migrated) = migrate_ui_settings(v0);
+        assert!(migrated);
+        let settings: UiSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.schema_version, UI_SETTINGS_SCHEMA_VERSION);
+        assert_eq!(settings.recording_hotkey, "F3");
+        assert_eq!(settings.hotkey_mode, HOTKEY_MODE_PUSH_TO_TALK);
+        assert_eq!(settings.start_hotkey, None);
+        assert_eq!(settings.stop_hotkey.as_deref(), Some("F6"));
+        assert_eq!(settings.cancel_hotkey, None);
+        assert_eq!(settings.output_method.as_deref(), Some(OUTPUT_METHOD_CLIPBOARD));
+        assert_eq!(settings.log_level, "debug");
+        // 旧配置缺少的字段取默认值
+        assert!(settings.first_run);
+        assert_eq!(settings.history_limit, DEFAULT_HISTORY_LIMIT);
+    }
+
+    #[test]
+    fn migrate_ui_settings_keeps_current_version() {
+        let current = serde_json::to_value(UiSettings::default()).unwrap();
+        let (value, migrated) = migrate_ui_settings(current.clone());
+        assert!(!migrated);
+        assert_eq!(value, current);
+    }
+
+    #[test]
+    fn correction_rate_handles_zero_chars() {
+        assert_eq!(correction_rate(0, 0), 0.0);
+        assert_eq!(correction_rate(5, 0), 0.0);
+        assert_eq!(correction_rate(3, 200), 1.5);
+    }
+
+    #[test]
+    fn correction_rate_handles_large_values() {
+        let rate = correction_rate(u64::MAX / 2, u64::MAX);
+        assert!((rate - 50.0).abs() < 1e-9);
+
+        let mut stats = default_usage_stats();
+        stats.totals.total_chars = 10_000_000_000;
+        stats.totals.corrections = 25_000_000;
+        let snapshot = UsageStatsSnapshot::from(&stats);
+        assert!((snapshot.total_correction_rate - 0.25).abs() < 1e-9);
+        assert_eq!(snapshot.today_correction_rate, 0.0);
+    }
+
+    #[test]
+    fn estimate_time_multiplier_needs_samples_and_clamps() {
+        let sample = TypingSample { chars: 30, duration_sec: 10.0 };
+        assert_eq!(estimate_time_multiplier(&vec![sample; TYPING_SAMPLES_MIN - 1]), None);
+
+        let estimated = estimate_time_multiplier(&vec![sample; TYPING_SAMPLES_MIN]).unwrap();
+        assert!((estimated - 3.0 / REFERENCE_TYPING_CHARS_PER_SEC).abs() < 1e-9);
+
+        let outlier = TypingSample { chars: 10_000, duration_sec: 1.0 };
+        assert_eq!(estimate_time_multiplier(&vec![outlier; TYPING_SAMPLES_MIN]), Some(MAX_AUTO_TIME_MULTIPLIER));
+    }
+
+    #[test]
+    fn merge_payload_overrides_existing_entries() {
+        let mut existing = IndexMap::new();
+        existing.insert("Foo".to_string(), ReplaceEntry::from_value(serde_json::json!("old")));
+        existing.insert("keep".to_string(), ReplaceEntry::from_value(serde_json::json!("kept")));
+        let current = PostprocessConfig {
+            case_insensitive: true,
+            replace_map: existing,
+            limits: PostprocessLimits { max_entries: 3, ..PostprocessLimits::default() },
+        };
+
+        let payload: SavePostprocessPayload = serde_json::from_value(serde_json::json!({
+            "mode": "merge",
+            "replace_map": { "foo": "new", "bar": "added" }
+        }))
+        .unwrap();
+        let cfg = validate_and_clean_payload(payload, current.clone()).unwrap();
+        assert_eq!(cfg.replace_map.len(), 3);
+        let values: Vec<&str> = cfg.replace_map.values().map(|e| e.value.as_str()).collect();
+        assert!(values.contains(&"new"));
+        assert!(!values.contains(&"old"));
+
+        // 合并后的总条目数同样受限
+        let payload: SavePostprocessPayload = serde_json::from_value(serde_json::json!({
+            "mode": "merge",
+            "replace_map": { "bar": "added", "baz": "extra" }
+        }))
+        .unwrap();
+        assert!(validate_and_clean_payload(payload, current).is_err());
+    }
+
+    #[test]
+    fn migrate_config_files_keeps_existing_targets() {
+        let root = std::env::temp_dir().join(format!("sk-migrate-{}", std::process::id()));
+        let (from, to) = (root.join("old"), root.join("new"));
+        fs::create_dir_all(&from).unwrap();
+        fs::create_dir_all(&to).unwrap();
+        fs::write(from.join("ui_settings.json"), "old").unwrap();
+        fs::write(from.join("history.jsonl"), "old").unwrap();
+        fs::write(to.join("ui_settings.json"), "new").unwrap();
+
+        let files = vec![from.join("ui_settings.json"), from.join("history.jsonl"), from.join("missing.json")];
+        assert_eq!(migrate_config_files(&files, &to).unwrap(), 1);
+        for name in ["app.log", "app.log.2024-05-01", "bridge.log.1", "usage_stats.json.lock", "postprocess.json.bak"] {
+            assert!(!is_migratable_config_file(name), "{}", name);
+        }
+        assert!(is_migratable_config_file("usage_stats.json"));
+        assert_eq!(fs::read_to_string(to.join("ui_settings.json")).unwrap(), "new");
+        assert_eq!(fs::read_to_string(to.join("history.jsonl")).unwrap(), "old");
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn ensure_dir_writable_creates_dir_and_cleans_up() {
+        let dir = std::env::temp_dir().join(format!("sk-probe-{}", std::process::id())).join("config");
+        assert!(ensure_dir_writable(&dir).is_ok());
+        assert!(dir.is_dir());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(dir.parent().unwrap());
+    }
+
+    #[test]
+    fn verified_write_restores_backup_on_corrupt_output() {
+        let dir = std::env::temp_dir().join(format!("sk-verify-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("postprocess.json");
+        let backup = dir.join("postprocess.json.bak");
+        let good = serde_json::to_string_pretty(&PostprocessConfig {
+            case_insensitive: true,
+            replace_map: IndexMap::new(),
+            limits: PostprocessLimits::default(),
+        })
+        .unwrap();
+        fs::write(&backup, &good).unwrap();
+
+        // 模拟写入中途中断：内容被截断，无法解析回 PostprocessConfig
+        let truncated = &good[..good.len() / 2];
+        let err = write_json_file_verified::<PostprocessConfig>(&path, truncated, Some(&backup)).unwrap_err();
+        assert!(err.contains("已从备份恢复"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), good);
+
+        write_json_file_verified::<PostprocessConfig>(&path, &good, Some(&backup)).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn messages_follow_locale_with_placeholders() {
+        let en = format_message(lookup_message(LOCALE_EN, "too_many_entries"), &[&250, &200]);
+        assert_eq!(en, "Too many replacement entries: 250 (max 200)");
+        let zh = format_message(lookup_message(LOCALE_ZH, "too_many_entries"), &[&250, &200]);
+        assert_eq!(zh, "替换词典超出上限：250 条（最多 200 条）");
+        // 未知语言回退中文，未知键原样返回
+        assert_eq!(lookup_message("fr", "hotkey_empty"), "快捷键不能为空");
+        assert_eq!(lookup_message(LOCALE_EN, "no_such_key"), "no_such_key");
+    }
+
+    #[test]
+    fn transcription_result_updates_stats_with_single_write() {
+        let mut writes = 0;
+        let inc = UsageIncrement { duration_sec: 4.0, saved_sec: 8.8, chars: 12, corrections: 2 };
+        let snapshot = apply_usage_increment(default_usage_stats(), inc, |_| {
+            writes += 1;
+            Ok(())
+        })
+        .unwrap();
+        assert_eq!(writes, 1);
+        assert_eq!(snapshot.today_recordings, 1);
+        assert_eq!(snapshot.today_chars, 12);
+        assert_eq!(snapshot.total_corrections, 2);
+        assert!((snapshot.today_sec - 8.8).abs() < 1e-9);
+        assert!((snapshot.total_duration_sec - 4.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn blocked_titles_match_case_insensitive_substring() {
+        let blocked = vec!["KeePass".to_string(), "  ".to_string(), "网上银行".to_string()];
+        assert_eq!(match_blocked_title("Database.kdbx - keepassxc", &blocked), Some("KeePass"));
+        assert_eq!(match_blocked_title("招商银行 网上银行 - Edge", &blocked), Some("网上银行"));
+        assert_eq!(match_blocked_title("Notepad", &blocked), None);
+        assert_eq!(match_blocked_title("Notepad", &[]), None);
+    }
+
+    #[test]
+    fn corrections_follow_dictionary_setting() {
+        let legacy = serde_json::json!({"corrections": 3});
+        assert_eq!(corrections_from_result(&legacy, true), 3);
+        assert_eq!(corrections_from_result(&legacy, false), 3);
+
+        let split = serde_json::json!({"corrections": 3, "dict_corrections": 3, "model_corrections": 2});
+        assert_eq!(corrections_from_result(&split, true), 5);
+        assert_eq!(corrections_from_result(&split, false), 2);
+        assert_eq!(corrections_from_result(&serde_json::json!({"dict_corrections": -1}), true), 0);
+    }
+
+    #[test]
+    fn merge_history_dedupes_and_respects_limit() {
+        let entry = |ts: &str, text: &str| TranscriptionHistoryEntry {
+            timestamp: ts.to_string(),
+            text: text.to_string(),
+            duration: 0.0,
+            corrections: 0,
+        };
+        let existing = vec![entry("2024-05-01T10:00:00+08:00", "a"), entry("2024-05-03T10:00:00+08:00", "c")];
+        let incoming = vec![
+            entry("2024-05-01T10:00:00+08:00", "a"),
+            entry("2024-05-02T10:00:00+08:00", "b"),
+            entry("2024-05-02T10:00:00+08:00", "b"),
+            entry("2024-04-30T10:00:00+08:00", "old"),
+        ];
+        let mut summary = HistoryImportSummary::default();
+        let merged = merge_history(existing, incoming, 3, &mut summary);
+        assert_eq!(summary, HistoryImportSummary { imported: 2, duplicates: 2, malformed: 0, dropped: 1 });
+        let texts: Vec<&str> = merged.iter().map(|e| e.text.as_str()).collect();
+        assert_eq!(texts, vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn anchor_position_keeps_widget_inside_work_area() {
+        let area = (0, 0, 1920, 1040);
+        assert_eq!(anchor_position("top_left", area, (200, 80), (24, 24)), (24, 24));
+        assert_eq!(anchor_position("bottom_right", area, (200, 80), (24, 24)), (1696, 936));
+        assert_eq!(anchor_position("top_right", (1920, 0, 1280, 1000), (200, 80), (0, 10)), (3000, 10));
+        // 边距过大时贴住对边而不是移出屏幕
+        assert_eq!(anchor_position("bottom_left", area, (200, 80), (5000, 5000)), (1720, 0));
+    }
+
+    #[test]
+    fn vad_settings_validate_ranges() {
+        assert!(VadSettings::default().validate().is_ok());
+        assert!(VadSettings { silence_threshold: 0.8, ..Default::default() }.validate().is_err());
+        assert!(VadSettings { silence_threshold: f64::NAN, ..Default::default() }.validate().is_err());
+        assert!(VadSettings { min_silence_ms: 10, ..Default::default() }.validate().is_err());
+        assert!(VadSettings { min_silence_ms: 1500, silence_threshold: 0.05 }.validate().is_ok());
+    }
+
+    #[test]
+    fn hotkey_profile_edits_survive_save_and_delete() {
+        let profile = |name: &str, key: &str| HotkeyProfile {
+            name: name.to_string(),
+            recording_hotkey: key.to_string(),
+            start_hotkey: None,
+            stop_hotkey: None,
+            cancel_hotkey: None,
+        };
+        let mut settings = UiSettings {
+            recording_hotkey: "F4".to_string(),
+            hotkey_profiles: vec![profile(DEFAULT_HOTKEY_PROFILE, "F2"), profile("work", "F4")],
+            active_hotkey_profile: Some("work".to_string()),
+            ..Default::default()
+        };
+
+        // 编辑激活方案：保存后再激活，不应被旧的顶层快捷键覆盖
+        assert!(upsert_hotkey_profile(&mut settings, profile("work", "F8")));
+        let work = settings.hotkey_profiles.iter().find(|p| p.name == "work").cloned().unwrap();
+        apply_hotkey_profile_to_settings(&mut settings, &work);
+        assert_eq!(work.recording_hotkey, "F8");
+        assert_eq!(settings.recording_hotkey, "F8");
+
+        // 删除激活方案：回退到默认方案，默认方案的快捷键保持不变
+        assert!(remove_hotkey_profile(&mut settings, "work").unwrap());
+        assert_eq!(settings.active_hotkey_profile.as_deref(), Some(DEFAULT_HOTKEY_PROFILE));
+        assert_eq!(settings.hotkey_profiles, vec![profile(DEFAULT_HOTKEY_PROFILE, "F2")]);
+        assert!(remove_hotkey_profile(&mut settings, "work").is_err());
+    }
+
+    #[test]
+    fn regex_entries_reject_rust_only_syntax() {
+        for pattern in [r"\p{Han}+", r"\PL", r"\x{4e00}", r"foo\z", r"[[:alpha:]]", r"(?<word>\w+)", r"(?U)a+", r"(?iU:a+)"] {
+            assert!(python_unsupported_regex_syntax(pattern).is_some(), "{}", pattern);
+        }
+        for pattern in [r"\d+", r"\\p", r"(?P<word>\w+)", r"(?i)abc", r"(?:a|b)", r"[a-z\x41]", r"\bfoo\b"] {
+            assert_eq!(python_unsupported_regex_syntax(pattern), None, "{}", pattern);
+        }
+    }
+
+    #[test]
+    fn widget_autohide_delay_depends_on_visibility_mode() {
+        let mut settings = UiSettings::default();
+        assert_eq!(widget_autohide_delay(&settings), None);
+        settings.widget_autohide_secs = Some(5);
+        assert_eq!(widget_autohide_delay(&settings), Some(5));
+        settings.widget_visibility_mode = WIDGET_VISIBILITY_RECORDING_ONLY.to_string();
+        assert_eq!(widget_autohide_delay(&settings), Some(5));
+        settings.widget_autohide_secs = None;
+        assert_eq!(widget_autohide_delay(&settings), Some(0));
+
+        let v1 = serde_json::json!({"schema_version": 1, "widget_autohide_secs": 0});
+        let (value, migrated) = migrate_ui_settings(v1);
+        assert!(migrated);
+        let settings: UiSettings = serde_json::from_value(value).unwrap();
+        assert_eq!(settings.widget_autohide_secs, None);
+        assert_eq!(widget_autohide_delay(&settings), None);
+    }
+
+    #[test]
+    fn preview_expands_python_style_backreferences() {
+        assert_eq!(python_replacement_to_rust(r"\2-\1"), "${2}-${1}");
+        assert_eq!(python_replacement_to_rust(r"\g<word>!\g<1>0"), "${word}!${1}0");
+        assert_eq!(python_replacement_to_rust(r"$5 \\ a\nb"), "$$5 \\ a\nb");
+
+        let mut replace_map = IndexMap::new();
+        replace_map.insert(
+            r"(?P<first>\w+) (\w+)".to_string(),
+            ReplaceEntry { value: r"\2 \g<first> $1".to_string(), kind: ReplaceKind::Regex, enabled: true },
+        );
+        let cfg = PostprocessConfig { case_insensitive: false, replace_map, limits: PostprocessLimits::default() };
+        let preview = apply_postprocess_rules("hello world", &cfg).unwrap();
+        assert_eq!(preview.text, "world hello $1");
+    }
+}
@@ -0,0 +1,30 @@
+"""语音格式指令的匹配测试：python -m unittest discover -s tests"""
+
+import importlib.util
+import pathlib
+import unittest
+
+# 直接按路径加载，避免导入 app 包时引入仅限 Windows 的输出模块
+_PATH = pathlib.Path(__file__).resolve().parents[1] / "app" / "voice_formatting.py"
+_SPEC = importlib.util.spec_from_file_location("voice_formatting", _PATH)
+voice_formatting = importlib.util.module_from_spec(_SPEC)
+_SPEC.loader.exec_module(voice_formatting)
+apply = voice_formatting.apply_voice_formatting
+
+
+class VoiceFormattingTest(unittest.TestCase):
+    def test_standalone_commands(self):
+        self.assertEqual(apply("第一句。换行。第二句"), "第一句\n第二句")
+        self.assertEqual(apply("第一段，新段落，第二段"), "第一段\n\n第二段")
+        self.assertEqual(apply("hello. New line. world"), "hello\nworld")
+        self.assertEqual(apply("one new paragraph two"), "one\n\ntwo")
+        self.assertEqual(apply("换行换行"), "换行换行")
+        self.assertEqual(apply("a。换行。换行。b"), "a\n\nb")
+
+    def test_words_containing_commands_are_kept(self):
+        for text in ("一个新行业。", "the new linen shirt", "I renew lines", "银行换行长", "newline"):
+            self.assertEqual(apply(text), text)
+
+
+if __name__ == "__main__":
+    unittest.main()